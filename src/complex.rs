@@ -1,5 +1,6 @@
 use crate::{
     complex::NumStr::{Matrix, Num, Str, Vector},
+    linalg::powm,
     math::inverse,
 };
 use rug::{ops::Pow, Complex};
@@ -10,6 +11,7 @@ pub enum NumStr
     Str(String),
     Vector(Vec<Complex>),
     Matrix(Vec<Vec<Complex>>),
+    Tuple(Vec<NumStr>),
 }
 impl NumStr
 {
@@ -197,7 +199,19 @@ impl NumStr
             ),
             (Matrix(a), Num(b)) =>
             {
-                if b.imag() == &0.0 && b.real().clone().fract() == 0.0
+                if b.eq0() && a.len() == a[0].len()
+                {
+                    Matrix(
+                        (0..a.len())
+                            .map(|i| {
+                                (0..a.len())
+                                    .map(|j| Complex::with_val(a[0][0].prec(), (i == j) as u8))
+                                    .collect()
+                            })
+                            .collect(),
+                    )
+                }
+                else if b.imag() == &0.0 && b.real().clone().fract() == 0.0
                 {
                     let mut mat = Matrix(a.clone());
                     let c = b.real().to_f64().abs() as usize;
@@ -211,12 +225,16 @@ impl NumStr
                     }
                     else
                     {
-                        Matrix(inverse(mat.mat()?)?)
+                        match inverse(mat.mat()?)
+                        {
+                            Ok(m) => Matrix(m),
+                            Err(_) => Str("singular matrix".to_string()),
+                        }
                     }
                 }
                 else
                 {
-                    return Err(());
+                    Matrix(powm(a, b)?)
                 }
             }
             (Vector(a), Matrix(b)) if b.len() == a.len() => Matrix(
@@ -257,6 +275,19 @@ impl NumStr
             _ => Err(()),
         }
     }
+    // the .^ operator, which raises each entry of a matrix instead of the matrix itself
+    pub fn elem_pow(&self, b: &Self) -> Result<Self, ()>
+    {
+        Ok(match (self, b)
+        {
+            (Matrix(a), Num(b)) => Matrix(
+                a.iter()
+                    .map(|a| a.iter().map(|a| a.pow(b.clone())).collect())
+                    .collect(),
+            ),
+            _ => self.pow(b)?,
+        })
+    }
     pub fn vec(&self) -> Result<Vec<Complex>, ()>
     {
         match self
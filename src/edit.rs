@@ -0,0 +1,518 @@
+use crate::{
+    options::{option, Kind, OPTIONS},
+    parse::{free_vars, functions, get_func, input_var, is_equation},
+    Options, COMMANDS,
+};
+use console::{Key, Term};
+pub enum Input
+{
+    Char(char),
+    Paste(String),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+    KillEnd,
+    KillStart,
+    KillWord,
+    Search,
+    Escape,
+    None,
+}
+// console hands back ctrl and alt sequences it doesn't know in pieces, so the rest of them is read here
+pub fn read_input() -> Input
+{
+    let term = Term::stdout();
+    match term.read_key().unwrap()
+    {
+        Key::Enter => Input::Enter,
+        Key::Tab => Input::Tab,
+        Key::Backspace => Input::Backspace,
+        Key::Del | Key::Char('\x04') => Input::Delete,
+        Key::ArrowLeft | Key::Char('\x02') => Input::Left,
+        Key::ArrowRight | Key::Char('\x06') => Input::Right,
+        Key::ArrowUp | Key::Char('\x10') => Input::Up,
+        Key::ArrowDown | Key::Char('\x0e') => Input::Down,
+        Key::Home => Input::Home,
+        Key::End => Input::End,
+        Key::Char('\x0b') => Input::KillEnd,
+        Key::Char('\x15') => Input::KillStart,
+        Key::Char('\x17') => Input::KillWord,
+        Key::Char('\x12') => Input::Search,
+        Key::Escape | Key::Char('\x07') => Input::Escape,
+        Key::Char(c) if !c.is_control() => Input::Char(c),
+        Key::UnknownEscSeq(seq) => match seq.as_slice()
+        {
+            ['b'] => Input::WordLeft,
+            ['f'] => Input::WordRight,
+            ['\x7f'] | ['\x08'] => Input::KillWord,
+            // ctrl or alt with an arrow, \x1b[1;5D
+            ['[', '1', ';'] => match (term.read_key(), term.read_key())
+            {
+                (_, Ok(Key::Char('D'))) => Input::WordLeft,
+                (_, Ok(Key::Char('C'))) => Input::WordRight,
+                (_, Ok(Key::Char('H'))) => Input::Home,
+                (_, Ok(Key::Char('F'))) => Input::End,
+                _ => Input::None,
+            },
+            // bracketed paste, the text comes between \x1b[200~ and \x1b[201~
+            ['[', '2', '0'] => match (term.read_key(), term.read_key())
+            {
+                (Ok(Key::Char('0')), Ok(Key::Char('~'))) =>
+                {
+                    let mut paste = String::new();
+                    loop
+                    {
+                        match term.read_key()
+                        {
+                            Ok(Key::Char(c)) => paste.push(c),
+                            Ok(Key::Tab) => paste.push(' '),
+                            Ok(Key::Enter) => paste.push('\n'),
+                            Ok(Key::UnknownEscSeq(s)) if s == ['[', '2', '0'] =>
+                            {
+                                term.read_key().unwrap();
+                                term.read_key().unwrap();
+                                break;
+                            }
+                            Ok(_) => (),
+                            Err(_) => break,
+                        }
+                    }
+                    Input::Paste(paste)
+                }
+                _ => Input::None,
+            },
+            _ => Input::None,
+        },
+        _ => Input::None,
+    }
+}
+pub fn bracketed_paste(on: bool)
+{
+    print!("\x1b[?2004{}", if on { 'h' } else { 'l' });
+}
+// the input being edited, indexed by char so π and √ are one column each,
+// previous holds the lines already entered when an input spans several
+pub struct Line
+{
+    pub chars: Vec<char>,
+    pub cursor: usize,
+    pub previous: String,
+}
+impl Line
+{
+    pub fn new() -> Self
+    {
+        Self {
+            chars: Vec::new(),
+            cursor: 0,
+            previous: String::new(),
+        }
+    }
+    pub fn set(&mut self, input: &str)
+    {
+        self.chars = input.chars().collect();
+        self.cursor = self.chars.len();
+    }
+    pub fn text(&self) -> String
+    {
+        self.chars.iter().collect()
+    }
+    pub fn input(&self) -> String
+    {
+        self.previous.clone() + &self.text()
+    }
+    // moves on to a new line, dropping the \ that asked for it
+    pub fn carry(&mut self)
+    {
+        let text = self.text();
+        self.previous
+            .push_str(text.strip_suffix('\\').unwrap_or(&text));
+        self.set("");
+    }
+    pub fn insert_str(&mut self, s: &str)
+    {
+        for c in s.chars()
+        {
+            self.chars.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+    pub fn backspace(&mut self)
+    {
+        if self.cursor != 0
+        {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+    pub fn delete(&mut self)
+    {
+        if self.cursor != self.chars.len()
+        {
+            self.chars.remove(self.cursor);
+        }
+    }
+    pub fn left(&mut self)
+    {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+    pub fn right(&mut self)
+    {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+    pub fn word_left(&mut self)
+    {
+        while self.cursor != 0 && !self.chars[self.cursor - 1].is_alphanumeric()
+        {
+            self.cursor -= 1;
+        }
+        while self.cursor != 0 && self.chars[self.cursor - 1].is_alphanumeric()
+        {
+            self.cursor -= 1;
+        }
+    }
+    pub fn word_right(&mut self)
+    {
+        while self.cursor != self.chars.len() && !self.chars[self.cursor].is_alphanumeric()
+        {
+            self.cursor += 1;
+        }
+        while self.cursor != self.chars.len() && self.chars[self.cursor].is_alphanumeric()
+        {
+            self.cursor += 1;
+        }
+    }
+    pub fn kill_end(&mut self)
+    {
+        self.chars.truncate(self.cursor);
+    }
+    pub fn kill_start(&mut self)
+    {
+        self.chars.drain(..self.cursor);
+        self.cursor = 0;
+    }
+    pub fn kill_word(&mut self)
+    {
+        let end = self.cursor;
+        self.word_left();
+        self.chars.drain(self.cursor..end);
+    }
+}
+// an unclosed { or [ or a trailing \ carries the input onto another line,
+// unclosed parentheses are still closed for you
+pub fn incomplete(input: &str) -> bool
+{
+    let mut count = 0;
+    for c in input.chars()
+    {
+        match c
+        {
+            '{' | '[' => count += 1,
+            '}' | ']' => count -= 1,
+            _ => (),
+        }
+    }
+    count > 0 || input.ends_with('\\')
+}
+// completes the name before the cursor as far as it is unambiguous, if that adds
+// nothing the candidates are given back with their arguments
+pub fn complete(line: &mut Line, vars: &[[String; 2]]) -> Vec<String>
+{
+    let start = line.chars[..line.cursor]
+        .iter()
+        .rposition(|c| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map_or(0, |n| n + 1);
+    // names don't start with a digit, 2si is 2*si
+    let start = start
+        + line.chars[start..line.cursor]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+    let word: String = line.chars[start..line.cursor].iter().collect();
+    if word.is_empty()
+    {
+        return Vec::new();
+    }
+    // what gets inserted and what is shown
+    let mut names: Vec<(String, String)> = functions()
+        .iter()
+        .map(|f| (format!("{}(", f), signature(f)))
+        .collect();
+    for v in vars
+    {
+        let name = v[0].split('(').next().unwrap();
+        if v[0].contains('(')
+        {
+            names.push((format!("{}(", name), v[0].clone()));
+        }
+        else
+        {
+            names.push((name.to_string(), name.to_string()));
+        }
+    }
+    if start == 0 && line.previous.is_empty()
+    {
+        for o in &OPTIONS
+        {
+            let s = match o.kind
+            {
+                Kind::Toggle | Kind::Switch => o.name.to_string(),
+                Kind::Value | Kind::Range => format!("{}=", o.name),
+            };
+            names.push((s.clone(), s));
+        }
+        for s in COMMANDS
+        {
+            names.push((s.to_string(), s.to_string()));
+        }
+    }
+    names.retain(|(n, _)| n.starts_with(&word));
+    names.sort();
+    names.dedup_by(|a, b| a.0 == b.0);
+    let mut add = match names.first()
+    {
+        Some((n, _)) if names.len() == 1 => n.clone(),
+        Some((n, _)) => names.iter().fold(n.clone(), |p, (n, _)| {
+            p.chars()
+                .zip(n.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        }),
+        None => return Vec::new(),
+    };
+    if add.len() == word.len() && names.len() != 1
+    {
+        return names.into_iter().map(|(_, s)| s).collect();
+    }
+    if add.ends_with('(') && line.chars.get(line.cursor) == Some(&'(')
+    {
+        add.pop();
+    }
+    line.insert_str(&add[word.len()..]);
+    Vec::new()
+}
+fn signature(f: &str) -> String
+{
+    let args = match f
+    {
+        "log" => "base,num",
+        "root" => "base,exp",
+        "sum" | "summation" | "prod" | "product" => "func,var,start,end",
+        "if" => "cond,a,b",
+        "piecewise" => "{cond,value},...,else",
+        "map" | "filter" | "reduce" => "f,{vec}",
+        "fold" => "f,init,{vec}",
+        "zipwith" => "f,{vec1},{vec2}",
+        "table" => "expr,var,start,end,step",
+        "atan" | "arctan" | "atan2" | "max" | "min" => "x,y",
+        "bi" | "binomial" => "n,k",
+        "dot" | "cross" | "proj" | "project" | "angle" => "{vec1},{vec2}",
+        "part" => "{vec},col,row",
+        "linsolve" => "{mat},{vec}",
+        "rotate" => "theta",
+        "polyval" => "{poly},x",
+        "polyroots" | "polyder" => "{poly}",
+        "polymul" | "polydiv" => "{poly1},{poly2}",
+        "polyint" => "{poly},constant",
+        "polyfit" => "{xs},{ys},degree",
+        _ => "x",
+    };
+    format!("{}({})", f, args)
+}
+// lays the candidates out in columns that fit the terminal
+pub fn columns(list: &[String], width: usize) -> Vec<String>
+{
+    let col = list.iter().map(|s| s.chars().count()).max().unwrap_or(0) + 2;
+    list.chunks((width / col).max(1))
+        .map(|row| {
+            row.iter()
+                .map(|s| format!("{:<1$}", s, col))
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+// colors the chars start..end of the line by what the parser will make of them, the bracket
+// at the cursor is shown with its match and brackets without one are red
+pub fn highlight(
+    line: &Line,
+    start: usize,
+    end: usize,
+    cursor: Option<usize>,
+    vars: &[[String; 2]],
+    options: Options,
+) -> String
+{
+    if !options.color
+    {
+        return line.chars[start..end].iter().collect();
+    }
+    let chars: Vec<char> = line
+        .previous
+        .chars()
+        .chain(line.chars.iter().copied())
+        .collect();
+    let offset = chars.len() - line.chars.len();
+    let input: String = chars.iter().collect();
+    let mut colors = vec!["\x1b[0m"; chars.len()];
+    let functions = functions();
+    let names: Vec<(&str, bool)> = vars
+        .iter()
+        .map(|v| (v[0].split('(').next().unwrap(), v[0].contains('(')))
+        .collect();
+    let command = input
+        .split([' ', '='])
+        .next()
+        .is_some_and(|w| option(w).is_some() || COMMANDS.contains(&w));
+    // unknown names are only flagged when they would be left unbound, so sum's index and
+    // whatever an equation solves for are not
+    let free = if command || is_equation(&input)
+    {
+        Some(Vec::new())
+    }
+    else
+    {
+        get_func(&input_var(&input, vars, None), options.prec)
+            .ok()
+            .map(|f| free_vars(&f))
+    };
+    let mut i = 0;
+    while i < chars.len()
+    {
+        let c = chars[i];
+        if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+            || (c == 'E' && i != 0 && chars[i - 1].is_ascii_digit())
+        {
+            colors[i] = "\x1b[0;96m";
+        }
+        else if c == '_' || c == '$'
+        {
+            colors[i] = "\x1b[0;93m";
+        }
+        else if c.is_ascii_alphabetic()
+        {
+            let run = chars[i..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .count();
+            if command && i == 0
+            {
+                colors[..run].fill("\x1b[0;95m");
+                i += run;
+                continue;
+            }
+            let word: String = chars[i..i + run].iter().collect();
+            let mut unknown = None;
+            let mut j = 0;
+            while j <= run
+            {
+                let rest = &word[j..];
+                let known = functions
+                    .iter()
+                    .map(|f| (*f, true))
+                    .chain(names.iter().copied())
+                    .filter(|(n, _)| !n.is_empty() && rest.starts_with(n))
+                    .max_by_key(|(n, _)| n.len());
+                let (len, color) = match (known, rest.chars().next())
+                {
+                    (Some((n, true)), _) => (n.len(), "\x1b[0;95m"),
+                    (Some((n, false)), _) => (n.len(), "\x1b[0;93m"),
+                    (None, Some('x' | 'y' | 'z')) => (1, "\x1b[0;93m"),
+                    (None, Some('i')) => (1, "\x1b[0;96m"),
+                    (None, Some(_)) =>
+                    {
+                        unknown.get_or_insert(j);
+                        j += 1;
+                        continue;
+                    }
+                    (None, None) => (1, ""),
+                };
+                if let Some(k) = unknown.take()
+                {
+                    let name = &word[k..j];
+                    colors[i + k..i + j].fill(
+                        if free.as_ref().is_none_or(|f| f.iter().any(|v| v == name))
+                        {
+                            "\x1b[0;91m"
+                        }
+                        else
+                        {
+                            "\x1b[0;93m"
+                        },
+                    );
+                }
+                if j < run
+                {
+                    colors[i + j..i + j + len].fill(color);
+                }
+                j += len;
+            }
+            i += run;
+            continue;
+        }
+        i += 1;
+    }
+    // each bracket's partner, none if it is never closed or closed by the wrong kind
+    let mut partner = vec![None; chars.len()];
+    let mut open = Vec::new();
+    for (i, c) in chars.iter().enumerate()
+    {
+        match c
+        {
+            '(' | '{' | '[' => open.push(i),
+            ')' | '}' | ']' => match open.last()
+            {
+                Some(&o) if matches!((chars[o], c), ('(', ')') | ('{', '}') | ('[', ']')) =>
+                {
+                    open.pop();
+                    partner[o] = Some(i);
+                    partner[i] = Some(o);
+                }
+                _ => colors[i] = "\x1b[0;91m",
+            },
+            _ => (),
+        }
+    }
+    for o in open
+    {
+        colors[o] = "\x1b[0;91m";
+    }
+    if let Some(cursor) = cursor.map(|c| c + offset)
+    {
+        let at = [cursor, cursor.wrapping_sub(1)]
+            .into_iter()
+            .find(|&n| n < chars.len() && "(){}[]".contains(chars[n]));
+        if let Some(n) = at
+        {
+            if let Some(p) = partner[n]
+            {
+                colors[n] = "\x1b[0;1;92m";
+                colors[p] = "\x1b[0;1;92m";
+            }
+        }
+    }
+    let mut output = String::new();
+    let mut last = "";
+    for i in offset + start..offset + end
+    {
+        if colors[i] != last
+        {
+            output.push_str(colors[i]);
+            last = colors[i];
+        }
+        output.push(chars[i]);
+    }
+    output
+}
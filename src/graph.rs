@@ -4,8 +4,8 @@ use crate::{
         NumStr::{Matrix, Num, Str, Vector},
     },
     math::do_math,
-    Options,
-    AngleType,
+    parse::{free_vars, Function},
+    AngleType, Options,
 };
 use gnuplot::{AxesCommon, Caption, Color, Figure, Fix, PointSymbol};
 use rug::Complex;
@@ -17,8 +17,36 @@ pub fn graph(
     deg: AngleType,
     prec: u32,
     watch: Option<Instant>,
+    functions: Vec<Function>,
 ) -> JoinHandle<()>
 {
+    // z is graphed over the complex plane as x+yi
+    let func: Vec<Vec<NumStr>> = func
+        .into_iter()
+        .map(|f| {
+            f.into_iter()
+                .flat_map(|n| {
+                    if n.str_is("z")
+                    {
+                        vec![
+                            Str("(".to_string()),
+                            Str("x".to_string()),
+                            Str("+".to_string()),
+                            Str("y".to_string()),
+                            Str("*".to_string()),
+                            Num(Complex::with_val(prec, (0, 1))),
+                            Str(")".to_string()),
+                        ]
+                    }
+                    else
+                    {
+                        vec![n]
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let vars = free_vars(&func[0]);
     thread::spawn(move || {
         let mut fg = Figure::new();
         fg.set_enhanced_text(false);
@@ -38,7 +66,7 @@ pub fn graph(
         let yticks = Some((Fix((options.yr[1] - options.yr[0]) / 20.0), 1));
         let mut re_cap: [String; 6] = Default::default();
         let mut im_cap: [String; 6] = Default::default();
-        if !input[0].contains('x')
+        if vars.is_empty()
         {
             let mut re = Vec::new();
             let mut matrix = false;
@@ -48,7 +76,7 @@ pub fn graph(
             let mut d3 = false;
             for (i, f) in func.iter().enumerate()
             {
-                re.push(match do_math(f.to_vec(), deg, prec).unwrap()
+                re.push(match do_math(f.to_vec(), deg, prec, &functions).unwrap()
                 {
                     Vector(n) =>
                     {
@@ -463,7 +491,7 @@ pub fn graph(
                     );
             }
         }
-        else if input[0].contains('y')
+        else if vars.iter().any(|v| v == "y")
         {
             let zticks = Some((Fix((options.zr[1] - options.zr[0]) / 20.0), 1));
             let mut re = vec![Vec::new(); 6];
@@ -471,7 +499,7 @@ pub fn graph(
             let (mut re2, mut im2);
             for (i, f) in func.iter().enumerate()
             {
-                (re2, im2) = get_list_3d(f, options, deg, prec);
+                (re2, im2) = get_list_3d(f, options, deg, prec, &functions);
                 if re2
                     .iter()
                     .map(|i| ((i[2] * 1e15).round() / 1e15) == 0.0)
@@ -604,10 +632,10 @@ pub fn graph(
             let (mut re2, mut im2);
             for (i, f) in func.iter().enumerate()
             {
-                (re2, im2) = get_list_2d(f, options, deg, prec);
+                (re2, im2) = get_list_2d(f, options, deg, prec, &functions);
                 if re2
                     .iter()
-                    .map(|i| ((i[1] * 1e15).round() / 1e15) == 0.0)
+                    .map(|i| i[1].is_nan() || ((i[1] * 1e15).round() / 1e15) == 0.0)
                     .all(|i| i)
                 {
                     re2.clear();
@@ -618,7 +646,7 @@ pub fn graph(
                 }
                 if im2
                     .iter()
-                    .map(|i| ((i[1] * 1e15).round() / 1e15) == 0.0)
+                    .map(|i| i[1].is_nan() || ((i[1] * 1e15).round() / 1e15) == 0.0)
                     .all(|i| i)
                 {
                     im2.clear();
@@ -808,6 +836,7 @@ pub fn get_list_2d(
     range: Options,
     deg: AngleType,
     prec: u32,
+    functions: &[Function],
 ) -> (Vec<[f64; 2]>, Vec<[f64; 2]>)
 {
     if let Num(n) = &func[0]
@@ -837,6 +866,7 @@ pub fn get_list_2d(
                 .collect(),
             deg,
             prec,
+            functions,
         )
         {
             Ok(n) => match n.num()
@@ -855,13 +885,46 @@ pub fn get_list_2d(
             im.push([n, num.imag().to_f64()]);
         }
     }
-    (re, im)
+    let jump = (range.yr[1] - range.yr[0]).abs() / den;
+    (
+        break_jumps(re, den_range, jump),
+        break_jumps(im, den_range, jump),
+    )
+}
+// a jump much bigger than the steps either side of it, or samples missing in between, is a
+// discontinuity, a NaN between the two points stops the line being drawn across it
+fn break_jumps(points: Vec<[f64; 2]>, step: f64, jump: f64) -> Vec<[f64; 2]>
+{
+    let d: Vec<f64> = points
+        .windows(2)
+        .map(|w| (w[1][1] - w[0][1]).abs())
+        .collect();
+    let mut broken = Vec::with_capacity(points.len());
+    for (k, p) in points.iter().enumerate()
+    {
+        broken.push(*p);
+        if let Some(next) = points.get(k + 1)
+        {
+            let around = k
+                .checked_sub(1)
+                .and_then(|k| d.get(k))
+                .copied()
+                .unwrap_or(0.0)
+                .max(d.get(k + 1).copied().unwrap_or(0.0));
+            if (d[k] > jump && d[k] > 4.0 * around) || next[0] - p[0] > 1.5 * step
+            {
+                broken.push([(p[0] + next[0]) / 2.0, f64::NAN]);
+            }
+        }
+    }
+    broken
 }
 pub fn get_list_3d(
     func: &[NumStr],
     range: Options,
     deg: AngleType,
     prec: u32,
+    functions: &[Function],
 ) -> (Vec<[f64; 3]>, Vec<[f64; 3]>)
 {
     if let Num(n) = &func[0]
@@ -907,6 +970,7 @@ pub fn get_list_3d(
                     .collect(),
                 deg,
                 prec,
+                functions,
             )
             {
                 Ok(n) => match n.num()
@@ -0,0 +1,918 @@
+use crate::math::inverse;
+use rug::{float::Special, ops::Pow, Assign, Complex, Float};
+use std::cmp::Ordering;
+fn abs(z: &Complex) -> Float
+{
+    Float::with_val(z.prec().0, z.abs_ref())
+}
+fn norm(v: &[Complex]) -> Float
+{
+    let mut n = Float::new(v[0].prec().0);
+    for z in v
+    {
+        n += abs(z).square();
+    }
+    n.sqrt()
+}
+fn zeros(rows: usize, cols: usize, prec: (u32, u32)) -> Vec<Vec<Complex>>
+{
+    vec![vec![Complex::new(prec); cols]; rows]
+}
+fn identity(n: usize, prec: (u32, u32)) -> Vec<Vec<Complex>>
+{
+    let mut a = zeros(n, n, prec);
+    for (i, row) in a.iter_mut().enumerate()
+    {
+        row[i] += 1;
+    }
+    a
+}
+fn mul(a: &[Vec<Complex>], b: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    a.iter()
+        .map(|row| {
+            (0..b[0].len())
+                .map(|j| {
+                    let mut n = Complex::new(row[0].prec());
+                    for (x, r) in row.iter().zip(b.iter())
+                    {
+                        n += x.clone() * &r[j];
+                    }
+                    n
+                })
+                .collect()
+        })
+        .collect()
+}
+fn transpose(a: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    (0..a[0].len())
+        .map(|j| a.iter().map(|row| row[j].clone()).collect())
+        .collect()
+}
+// conjugate transpose
+fn adjoint(a: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    (0..a[0].len())
+        .map(|j| a.iter().map(|row| row[j].clone().conj()).collect())
+        .collect()
+}
+// entries this small relative to the largest entry are treated as zero, so only an all zero
+// matrix has a tolerance of zero
+pub fn tolerance(a: &[Vec<Complex>], bits: u32) -> Float
+{
+    let prec = a[0][0].prec().0;
+    let mut max = Float::new(prec);
+    for z in a.iter().flatten()
+    {
+        let n = abs(z);
+        if n > max
+        {
+            max = n;
+        }
+    }
+    max >> bits
+}
+fn clean(v: &mut [Complex], tol: &Float)
+{
+    for z in v.iter_mut()
+    {
+        if z.real().clone().abs() <= *tol
+        {
+            z.mut_real().assign(0);
+        }
+        if z.imag().clone().abs() <= *tol
+        {
+            z.mut_imag().assign(0);
+        }
+    }
+}
+fn set_prec(a: &[Vec<Complex>], prec: u32) -> Vec<Vec<Complex>>
+{
+    a.iter()
+        .map(|row| row.iter().map(|z| Complex::with_val(prec, z)).collect())
+        .collect()
+}
+fn is_square(a: &[Vec<Complex>]) -> bool
+{
+    !a.is_empty() && a.iter().all(|row| row.len() == a.len())
+}
+fn finite(a: &[Vec<Complex>]) -> bool
+{
+    a.iter()
+        .flatten()
+        .all(|z| z.real().is_finite() && z.imag().is_finite())
+}
+// householder vector v with (I-2vv*)x a multiple of the first unit vector
+fn reflector(x: &[Complex]) -> Option<Vec<Complex>>
+{
+    let n = norm(x);
+    if n.is_zero()
+    {
+        return None;
+    }
+    let phase = if x[0].eq0()
+    {
+        Complex::with_val(x[0].prec(), 1)
+    }
+    else
+    {
+        x[0].clone() / abs(&x[0])
+    };
+    let mut v = x.to_vec();
+    v[0] += phase * n;
+    let n = norm(&v);
+    if n.is_zero()
+    {
+        return None;
+    }
+    Some(v.into_iter().map(|z| z / &n).collect())
+}
+// applies I-2vv* to rows k.. of a
+fn reflect_rows(a: &mut [Vec<Complex>], v: &[Complex], k: usize)
+{
+    for j in 0..a[0].len()
+    {
+        let mut s = Complex::new(v[0].prec());
+        for (i, z) in v.iter().enumerate()
+        {
+            s += z.clone().conj() * &a[k + i][j];
+        }
+        s *= 2;
+        for (i, z) in v.iter().enumerate()
+        {
+            a[k + i][j] -= z.clone() * &s;
+        }
+    }
+}
+// applies I-2vv* to columns k.. of a
+fn reflect_cols(a: &mut [Vec<Complex>], v: &[Complex], k: usize)
+{
+    for row in a.iter_mut()
+    {
+        let mut s = Complex::new(v[0].prec());
+        for (i, z) in v.iter().enumerate()
+        {
+            s += row[k + i].clone() * z;
+        }
+        s *= 2;
+        for (i, z) in v.iter().enumerate()
+        {
+            row[k + i] -= s.clone() * z.clone().conj();
+        }
+    }
+}
+// gauss-jordan elimination, also returning the pivot columns
+fn reduce(a: &[Vec<Complex>], tol: &Float) -> (Vec<Vec<Complex>>, Vec<usize>)
+{
+    let mut m = a.to_vec();
+    let prec = m[0][0].prec();
+    let mut pivots = Vec::new();
+    let mut r = 0;
+    for c in 0..m[0].len()
+    {
+        if r == m.len()
+        {
+            break;
+        }
+        let p = (r..m.len())
+            .max_by(|x, y| {
+                abs(&m[*x][c])
+                    .partial_cmp(&abs(&m[*y][c]))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+        if abs(&m[p][c]) <= *tol
+        {
+            for row in m.iter_mut().skip(r)
+            {
+                row[c] = Complex::new(prec);
+            }
+            continue;
+        }
+        m.swap(r, p);
+        let d = m[r][c].clone();
+        for z in m[r].iter_mut()
+        {
+            *z /= &d;
+        }
+        let pivot = m[r].clone();
+        for (k, row) in m.iter_mut().enumerate()
+        {
+            if k != r && !row[c].eq0()
+            {
+                let f = row[c].clone();
+                for (z, p) in row.iter_mut().zip(pivot.iter())
+                {
+                    *z -= f.clone() * p;
+                }
+            }
+        }
+        pivots.push(c);
+        r += 1;
+    }
+    for row in m.iter_mut()
+    {
+        clean(row, tol);
+    }
+    (m, pivots)
+}
+fn null_vectors(a: &[Vec<Complex>], tol: &Float) -> Vec<Vec<Complex>>
+{
+    let prec = a[0][0].prec();
+    let (m, pivots) = reduce(a, tol);
+    (0..a[0].len())
+        .filter(|c| !pivots.contains(c))
+        .map(|f| {
+            let mut v = vec![Complex::new(prec); a[0].len()];
+            v[f] += 1;
+            for (row, p) in m.iter().zip(pivots.iter())
+            {
+                v[*p] = -row[f].clone();
+            }
+            v
+        })
+        .collect()
+}
+// gram-schmidt on the given vectors, then filled up to n with unit vectors
+fn complete(vectors: Vec<Vec<Complex>>, n: usize, prec: (u32, u32)) -> Vec<Vec<Complex>>
+{
+    let mut basis: Vec<Vec<Complex>> = Vec::new();
+    let project = |basis: &Vec<Vec<Complex>>, mut v: Vec<Complex>| {
+        for b in basis
+        {
+            let mut s = Complex::new(prec);
+            for (x, y) in b.iter().zip(v.iter())
+            {
+                s += x.clone().conj() * y;
+            }
+            for (x, y) in v.iter_mut().zip(b.iter())
+            {
+                *x -= s.clone() * y;
+            }
+        }
+        v
+    };
+    let tol = Float::with_val(prec.0, 1) >> (prec.0 / 2);
+    for v in vectors
+    {
+        let v = project(&basis, v);
+        let n = norm(&v);
+        if n > tol
+        {
+            basis.push(v.into_iter().map(|z| z / &n).collect());
+        }
+    }
+    while basis.len() < n
+    {
+        let v = (0..n)
+            .map(|i| {
+                let mut e = vec![Complex::new(prec); n];
+                e[i] += 1;
+                project(&basis, e)
+            })
+            .max_by(|x, y| norm(x).partial_cmp(&norm(y)).unwrap_or(Ordering::Equal))
+            .unwrap();
+        let n = norm(&v);
+        basis.push(v.into_iter().map(|z| z / &n).collect());
+    }
+    basis
+}
+pub fn rref(a: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    reduce(a, &tolerance(a, a[0][0].prec().0 / 2)).0
+}
+pub fn rank(a: &[Vec<Complex>]) -> usize
+{
+    reduce(a, &tolerance(a, a[0][0].prec().0 / 2)).1.len()
+}
+// basis vectors are the columns, none if only the trivial solution exists
+pub fn nullspace(a: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    let v = null_vectors(a, &tolerance(a, a[0][0].prec().0 / 2));
+    if v.is_empty()
+    {
+        v
+    }
+    else
+    {
+        transpose(&v)
+    }
+}
+// returns l, u and p with p*a=l*u
+pub fn lu(a: &[Vec<Complex>]) -> Result<Vec<Vec<Vec<Complex>>>, ()>
+{
+    if !is_square(a)
+    {
+        return Err(());
+    }
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let mut u = a.to_vec();
+    let mut l = zeros(n, n, prec);
+    let mut p = identity(n, prec);
+    for i in 0..n
+    {
+        let k = (i..n)
+            .max_by(|x, y| {
+                abs(&u[*x][i])
+                    .partial_cmp(&abs(&u[*y][i]))
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+        u.swap(i, k);
+        l.swap(i, k);
+        p.swap(i, k);
+        l[i][i] += 1;
+        if u[i][i].eq0()
+        {
+            continue;
+        }
+        let (top, bottom) = u.split_at_mut(i + 1);
+        for (row, lrow) in bottom.iter_mut().zip(l.iter_mut().skip(i + 1))
+        {
+            let f = row[i].clone() / &top[i][i];
+            for (z, p) in row.iter_mut().zip(top[i].iter()).skip(i)
+            {
+                *z -= f.clone() * p;
+            }
+            row[i] = Complex::new(prec);
+            lrow[i] = f;
+        }
+    }
+    Ok(vec![l, u, p])
+}
+// returns q and r with a=q*r, q unitary and the diagonal of r real and non negative
+pub fn qr(a: &[Vec<Complex>]) -> Vec<Vec<Vec<Complex>>>
+{
+    let (m, n) = (a.len(), a[0].len());
+    let prec = a[0][0].prec();
+    let mut r = a.to_vec();
+    let mut q = identity(m, prec);
+    for k in 0..n.min(m.saturating_sub(1))
+    {
+        let x: Vec<Complex> = r[k..].iter().map(|row| row[k].clone()).collect();
+        if let Some(v) = reflector(&x)
+        {
+            reflect_rows(&mut r, &v, k);
+            reflect_rows(&mut q, &v, k);
+        }
+        for row in r.iter_mut().skip(k + 1)
+        {
+            row[k] = Complex::new(prec);
+        }
+    }
+    let mut q = adjoint(&q);
+    for k in 0..n.min(m)
+    {
+        if !r[k][k].eq0()
+        {
+            let d = r[k][k].clone() / abs(&r[k][k]);
+            for z in r[k].iter_mut()
+            {
+                *z /= &d;
+            }
+            for row in q.iter_mut()
+            {
+                row[k] *= &d;
+            }
+        }
+    }
+    let tol = tolerance(a, prec.0 / 2);
+    for row in q.iter_mut().chain(r.iter_mut())
+    {
+        clean(row, &tol);
+    }
+    vec![q, r]
+}
+// returns l with a=l*l* for a hermitian positive definite a
+pub fn cholesky(a: &[Vec<Complex>]) -> Result<Vec<Vec<Complex>>, ()>
+{
+    if !is_square(a)
+    {
+        return Err(());
+    }
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let tol = tolerance(a, prec.0 / 2);
+    for (i, row) in a.iter().enumerate()
+    {
+        for (j, z) in row.iter().enumerate()
+        {
+            if abs(&(z.clone() - a[j][i].clone().conj())) > tol
+            {
+                return Err(());
+            }
+        }
+    }
+    let mut l = zeros(n, n, prec);
+    for j in 0..n
+    {
+        let mut s = Float::with_val(prec.0, a[j][j].real());
+        for z in &l[j][..j]
+        {
+            s -= abs(z).square();
+        }
+        if s <= tol
+        {
+            return Err(());
+        }
+        let d = Complex::with_val(prec, s.sqrt());
+        l[j][j] = d.clone();
+        for i in j + 1..n
+        {
+            let mut s = a[i][j].clone();
+            for (x, y) in l[i][..j].iter().zip(l[j][..j].iter())
+            {
+                s -= x.clone() * y.clone().conj();
+            }
+            l[i][j] = s / &d;
+        }
+    }
+    Ok(l)
+}
+fn hessenberg(a: &[Vec<Complex>]) -> Vec<Vec<Complex>>
+{
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let mut h = a.to_vec();
+    for k in 0..n.saturating_sub(2)
+    {
+        let x: Vec<Complex> = h[k + 1..].iter().map(|row| row[k].clone()).collect();
+        if let Some(v) = reflector(&x)
+        {
+            reflect_rows(&mut h, &v, k + 1);
+            reflect_cols(&mut h, &v, k + 1);
+        }
+        for row in h.iter_mut().skip(k + 2)
+        {
+            row[k] = Complex::new(prec);
+        }
+    }
+    h
+}
+// shifted qr iteration on the hessenberg form, deflating from the bottom
+pub fn eigvals(a: &[Vec<Complex>]) -> Result<Vec<Complex>, ()>
+{
+    if !is_square(a) || !finite(a)
+    {
+        return Err(());
+    }
+    let prec = a[0][0].prec();
+    let eps = tolerance(a, prec.0.saturating_sub(8));
+    let mut h = hessenberg(a);
+    let mut values = Vec::new();
+    let mut m = h.len();
+    let mut iter = 0;
+    while m > 1
+    {
+        if abs(&h[m - 1][m - 2]) <= eps
+        {
+            values.push(h[m - 1][m - 1].clone());
+            m -= 1;
+            iter = 0;
+            continue;
+        }
+        iter += 1;
+        if iter > 100 + prec.0
+        {
+            return Err(());
+        }
+        let (p, q, r, s) = (
+            h[m - 2][m - 2].clone(),
+            h[m - 2][m - 1].clone(),
+            h[m - 1][m - 2].clone(),
+            h[m - 1][m - 1].clone(),
+        );
+        // wilkinson shift, with an occasional exceptional shift to break cycles
+        let mu = if iter % 11 == 0
+        {
+            s + abs(&r)
+        }
+        else
+        {
+            let mid: Complex = (p.clone() + &s) / 2;
+            let half: Complex = (p.clone() - &s) / 2;
+            let disc = half.square() + q * r;
+            let disc = disc.sqrt();
+            let e1 = mid.clone() + &disc;
+            let e2 = mid - disc;
+            if abs(&(e1.clone() - &s)) < abs(&(e2.clone() - &s))
+            {
+                e1
+            }
+            else
+            {
+                e2
+            }
+        };
+        for (i, row) in h.iter_mut().enumerate().take(m)
+        {
+            row[i] -= &mu;
+        }
+        let mut rotations = Vec::new();
+        for k in 0..m - 1
+        {
+            let (x, y) = (h[k][k].clone(), h[k + 1][k].clone());
+            let r = (abs(&x).square() + abs(&y).square()).sqrt();
+            let (c, s) = if r.is_zero()
+            {
+                (Complex::with_val(prec, 1), Complex::new(prec))
+            }
+            else
+            {
+                (x / &r, y / &r)
+            };
+            let (top, bottom) = h.split_at_mut(k + 1);
+            for (p, q) in top[k][k..m].iter_mut().zip(bottom[0][k..m].iter_mut())
+            {
+                let (x, y) = (p.clone(), q.clone());
+                *p = c.clone().conj() * &x + s.clone().conj() * &y;
+                *q = c.clone() * y - s.clone() * x;
+            }
+            rotations.push((c, s));
+        }
+        for (k, (c, s)) in rotations.into_iter().enumerate()
+        {
+            for row in h.iter_mut().take((k + 2).min(m))
+            {
+                let (x, y) = (row[k].clone(), row[k + 1].clone());
+                row[k] = c.clone() * &x + s.clone() * &y;
+                row[k + 1] = c.clone().conj() * y - s.clone().conj() * x;
+            }
+        }
+        for (i, row) in h.iter_mut().enumerate().take(m)
+        {
+            row[i] += &mu;
+        }
+    }
+    values.push(h[0][0].clone());
+    clean(&mut values, &tolerance(a, prec.0 / 2));
+    values.sort_by(|a, b| {
+        a.real()
+            .partial_cmp(b.real())
+            .unwrap_or(Ordering::Equal)
+            .then(a.imag().partial_cmp(b.imag()).unwrap_or(Ordering::Equal))
+    });
+    Ok(values)
+}
+// eigenvalues and unit eigenvectors as columns, repeated where a matrix is defective
+pub fn eig(a: &[Vec<Complex>]) -> Result<(Vec<Complex>, Vec<Vec<Complex>>), ()>
+{
+    let values = eigvals(a)?;
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let same = tolerance(a, prec.0 / 3);
+    let mut vectors: Vec<Option<Vec<Complex>>> = vec![None; n];
+    for i in 0..n
+    {
+        if vectors[i].is_some()
+        {
+            continue;
+        }
+        let group: Vec<usize> = (i..n)
+            .filter(|j| vectors[*j].is_none() && abs(&(values[*j].clone() - &values[i])) <= same)
+            .collect();
+        let mut b = a.to_vec();
+        for (k, row) in b.iter_mut().enumerate()
+        {
+            row[k] -= &values[i];
+        }
+        // widen the tolerance until the nearly singular system gives a solution
+        let mut bits = prec.0 / 2;
+        let mut v = null_vectors(&b, &tolerance(a, bits));
+        while v.is_empty() && bits > 8
+        {
+            bits -= bits / 8 + 1;
+            v = null_vectors(&b, &tolerance(a, bits));
+        }
+        if v.is_empty()
+        {
+            return Err(());
+        }
+        for (k, j) in group.into_iter().enumerate()
+        {
+            let mut x = v[k.min(v.len() - 1)].clone();
+            let n = norm(&x);
+            for z in x.iter_mut()
+            {
+                *z /= &n;
+            }
+            vectors[j] = Some(x);
+        }
+    }
+    let vectors: Vec<Vec<Complex>> = vectors.into_iter().map(|v| v.unwrap()).collect();
+    let mut vectors = transpose(&vectors);
+    let tol = tolerance(a, prec.0 / 2);
+    for row in vectors.iter_mut()
+    {
+        clean(row, &tol);
+    }
+    Ok((values, vectors))
+}
+// returns u, s and v with a=u*s*v*, from the eigenvectors of a*a worked out at twice the precision
+pub fn svd(a: &[Vec<Complex>]) -> Result<Vec<Vec<Vec<Complex>>>, ()>
+{
+    let (m, n) = (a.len(), a[0].len());
+    let prec = a[0][0].prec();
+    let wide = set_prec(a, prec.0 * 2);
+    let (values, vectors) = eig(&mul(&adjoint(&wide), &wide))?;
+    let columns = transpose(&vectors);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|x, y| {
+        values[*y]
+            .real()
+            .partial_cmp(values[*x].real())
+            .unwrap_or(Ordering::Equal)
+    });
+    let v = complete(
+        order.iter().map(|i| columns[*i].clone()).collect(),
+        n,
+        (prec.0 * 2, prec.1 * 2),
+    );
+    let tol = tolerance(a, prec.0 / 2);
+    let mut sigma = Vec::new();
+    let mut u = Vec::new();
+    for (k, i) in order.iter().enumerate()
+    {
+        let s = Float::with_val(prec.0 * 2, values[*i].real()).max(&Float::new(prec.0 * 2));
+        let s = s.sqrt();
+        if k < m && s > tol
+        {
+            u.push(
+                wide.iter()
+                    .map(|row| {
+                        let mut z = Complex::new(prec.0 * 2);
+                        for (x, y) in row.iter().zip(v[k].iter())
+                        {
+                            z += x.clone() * y;
+                        }
+                        z / &s
+                    })
+                    .collect(),
+            );
+        }
+        sigma.push(s);
+    }
+    let u = complete(u, m, (prec.0 * 2, prec.1 * 2));
+    let mut s = zeros(m, n, prec);
+    for (i, x) in sigma.into_iter().enumerate().take(m)
+    {
+        s[i][i] = Complex::with_val(prec, x);
+    }
+    let mut u = set_prec(&transpose(&u), prec.0);
+    let mut v = set_prec(&transpose(&v), prec.0);
+    for row in u.iter_mut().chain(v.iter_mut())
+    {
+        clean(row, &tol);
+    }
+    Ok(vec![u, s, v])
+}
+// applies f to the eigenvalues of a diagonalizable matrix, worked out at twice the precision
+fn spectral<F>(a: &[Vec<Complex>], f: F) -> Result<Vec<Vec<Complex>>, ()>
+where
+    F: Fn(Complex) -> Complex,
+{
+    if !is_square(a)
+    {
+        return Err(());
+    }
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let wide = set_prec(a, prec.0 * 2);
+    let (values, vectors) = eig(&wide)?;
+    if rank(&vectors) < n
+    {
+        return Err(());
+    }
+    let inv = inverse(vectors.clone())?;
+    let mut d = zeros(n, n, (prec.0 * 2, prec.1 * 2));
+    for (i, l) in values.into_iter().enumerate()
+    {
+        d[i][i] = f(l);
+        if !d[i][i].real().is_finite() || !d[i][i].imag().is_finite()
+        {
+            return Err(());
+        }
+    }
+    let mut m = set_prec(&mul(&mul(&vectors, &d), &inv), prec.0);
+    let tol = tolerance(&m, prec.0 / 2);
+    for row in m.iter_mut()
+    {
+        clean(row, &tol);
+    }
+    Ok(m)
+}
+// a matrix without a basis of eigenvectors goes through logm and expm instead
+pub fn powm(a: &[Vec<Complex>], p: &Complex) -> Result<Vec<Vec<Complex>>, ()>
+{
+    spectral(a, |l| l.pow(p)).or_else(|_| {
+        let l: Vec<Vec<Complex>> = logm(a)?
+            .into_iter()
+            .map(|row| row.into_iter().map(|z| z * p).collect())
+            .collect();
+        expm(&l)
+    })
+}
+pub fn sqrtm(a: &[Vec<Complex>]) -> Result<Vec<Vec<Complex>>, ()>
+{
+    spectral(a, |l| l.sqrt()).or_else(|_| defective(a, root))
+}
+pub fn logm(a: &[Vec<Complex>]) -> Result<Vec<Vec<Complex>>, ()>
+{
+    spectral(a, |l| l.ln()).or_else(|_| defective(a, log))
+}
+// f worked out at twice the precision, for the matrices spectral can't diagonalize
+fn defective<F>(a: &[Vec<Complex>], f: F) -> Result<Vec<Vec<Complex>>, ()>
+where
+    F: Fn(Vec<Vec<Complex>>) -> Result<Vec<Vec<Complex>>, ()>,
+{
+    if !is_square(a)
+    {
+        return Err(());
+    }
+    let prec = a[0][0].prec().0;
+    let mut m = set_prec(&f(set_prec(a, prec * 2))?, prec);
+    let tol = tolerance(&m, prec / 2);
+    for row in m.iter_mut()
+    {
+        clean(row, &tol);
+    }
+    Ok(m)
+}
+// the principal square root by denman-beavers iteration, y goes to the root of a and z to its
+// inverse, it fails for a singular a or one with an eigenvalue on the negative real axis
+fn root(a: Vec<Vec<Complex>>) -> Result<Vec<Vec<Complex>>, ()>
+{
+    let prec = a[0][0].prec();
+    let tol = Float::with_val(prec.0, 1) >> (prec.0 * 3 / 4);
+    let mut z = identity(a.len(), prec);
+    let mut y = a;
+    for _ in 0..100
+    {
+        let (yi, zi) = (inverse(y.clone())?, inverse(z.clone())?);
+        let next: Vec<Vec<Complex>> = y
+            .iter()
+            .zip(zi.iter())
+            .map(|(r, s)| {
+                r.iter()
+                    .zip(s.iter())
+                    .map(|(u, v)| (u.clone() + v) / 2)
+                    .collect()
+            })
+            .collect();
+        z = z
+            .iter()
+            .zip(yi.iter())
+            .map(|(r, s)| {
+                r.iter()
+                    .zip(s.iter())
+                    .map(|(u, v)| (u.clone() + v) / 2)
+                    .collect()
+            })
+            .collect();
+        let change = size(
+            &next
+                .iter()
+                .zip(y.iter())
+                .map(|(r, s)| r.iter().zip(s.iter()).map(|(u, v)| u.clone() - v).collect())
+                .collect::<Vec<Vec<Complex>>>(),
+        );
+        y = next;
+        if change <= tol.clone() * (size(&y) + 1)
+        {
+            return Ok(y);
+        }
+    }
+    Err(())
+}
+// inverse scaling and squaring, square roots bring a near the identity where the series for
+// log(1+x) converges quickly and log(a)=2^k log(a^(1/2^k))
+fn log(mut a: Vec<Vec<Complex>>) -> Result<Vec<Vec<Complex>>, ()>
+{
+    let prec = a[0][0].prec();
+    let mut k = 0;
+    let x = loop
+    {
+        let mut x = a.clone();
+        for (i, row) in x.iter_mut().enumerate()
+        {
+            row[i] -= 1;
+        }
+        if size(&x) < 1.0 / 256.0
+        {
+            break x;
+        }
+        if k == 64
+        {
+            return Err(());
+        }
+        a = root(a)?;
+        k += 1;
+    };
+    let eps = Float::with_val(prec.0, 1) >> prec.0;
+    let mut term = x.clone();
+    let mut sum = zeros(a.len(), a.len(), prec);
+    for j in 1..=prec.0
+    {
+        for (s, t) in sum.iter_mut().flatten().zip(term.iter().flatten())
+        {
+            if j % 2 == 1
+            {
+                *s += t.clone() / j;
+            }
+            else
+            {
+                *s -= t.clone() / j;
+            }
+        }
+        if size(&term) < eps
+        {
+            break;
+        }
+        term = mul(&term, &x);
+    }
+    Ok(sum
+        .into_iter()
+        .map(|row| row.into_iter().map(|z| z << k).collect())
+        .collect())
+}
+// the largest row sum of absolute values
+fn size(a: &[Vec<Complex>]) -> Float
+{
+    let mut norm = Float::new(a[0][0].prec().0);
+    for row in a
+    {
+        let mut s = Float::new(norm.prec());
+        for z in row
+        {
+            s += abs(z);
+        }
+        if s > norm
+        {
+            norm = s;
+        }
+    }
+    norm
+}
+// scaling and squaring with a taylor series, so defective matrices work too
+pub fn expm(a: &[Vec<Complex>]) -> Result<Vec<Vec<Complex>>, ()>
+{
+    if !is_square(a)
+    {
+        return Err(());
+    }
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let mut norm = size(a);
+    // an infinite or nan entry would never scale down, so there is no finite answer
+    if !finite(a) || !norm.is_finite()
+    {
+        let nan = Complex::with_val(prec, (Float::with_val(prec.0, Special::Nan), 0));
+        return Ok(vec![vec![nan; n]; n]);
+    }
+    let mut squarings = 0;
+    while norm > 0.5
+    {
+        norm >>= 1;
+        squarings += 1;
+    }
+    let work = (prec.0 + squarings + 32, prec.1 + squarings + 32);
+    let b: Vec<Vec<Complex>> = set_prec(a, work.0)
+        .into_iter()
+        .map(|row| row.into_iter().map(|z| z >> squarings).collect())
+        .collect();
+    let eps = Float::with_val(work.0, 1) >> work.0;
+    let mut term = identity(n, work);
+    let mut sum = identity(n, work);
+    for k in 1..=work.0
+    {
+        term = mul(&term, &b);
+        let mut max = Float::new(work.0);
+        for (t, s) in term.iter_mut().flatten().zip(sum.iter_mut().flatten())
+        {
+            *t /= k;
+            *s += &*t;
+            let n = abs(t);
+            if n > max
+            {
+                max = n;
+            }
+        }
+        if max < eps
+        {
+            break;
+        }
+    }
+    for _ in 0..squarings
+    {
+        sum = mul(&sum, &sum);
+    }
+    let mut m = set_prec(&sum, prec.0);
+    let tol = tolerance(&m, prec.0 / 2);
+    for row in m.iter_mut()
+    {
+        clean(row, &tol);
+    }
+    Ok(m)
+}
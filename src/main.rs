@@ -1,26 +1,47 @@
 mod complex;
+mod edit;
 mod fraction;
 mod graph;
+mod linalg;
+mod markup;
 mod math;
 mod options;
 mod parse;
+mod poly;
 mod print;
+mod script;
+mod session;
+mod symbolic;
 #[cfg(test)]
 mod tests;
 use crate::{
-    complex::NumStr::{Matrix, Num, Str, Vector},
+    complex::{
+        NumStr,
+        NumStr::{Matrix, Num, Str, Tuple, Vector},
+    },
+    edit::{bracketed_paste, columns, complete, highlight, incomplete, read_input, Input, Line},
     graph::graph,
-    math::do_math,
-    options::{arg_opts, file_opts, AngleType},
-    parse::{get_func, get_vars, input_var},
-    print::{get_output, print_answer, print_concurrent},
+    markup::{from_latex, markup},
+    math::{do_math, recall},
+    options::{
+        arg_opts, file_opts, option, set_option, setting, AngleType, Config, Format, Kind,
+        Notation, OPTIONS,
+    },
+    parse::{
+        conversion, forward_default, get_func, get_vars, graphs, input_var, is_equation, is_func,
+        is_graph, split_equation, user_functions,
+    },
+    poly::{expand, factor, solve_equation},
+    print::{get_output, json_answer, json_error, print_answer, print_concurrent, tuple_output},
+    script::Script,
+    session::{load, save},
+    symbolic::exact,
 };
-use console::{Key, Term};
 #[cfg(unix)]
 use libc::{ioctl, winsize, STDOUT_FILENO, TIOCGWINSZ};
 use std::{
     env::{args, var},
-    fs::{File, OpenOptions},
+    fs::{read_to_string, File, OpenOptions},
     io::{stdin, stdout, BufRead, BufReader, IsTerminal, Write},
     thread::JoinHandle,
 };
@@ -33,12 +54,14 @@ use term_size::dimensions;
 #[derive(Clone, Copy)]
 pub struct Options
 {
-    sci: bool,
+    notation: Notation,
+    sig: usize,
     deg: AngleType,
     base: usize,
     tau: bool,
     polar: bool,
     frac: bool,
+    exact: bool,
     real_time_output: bool,
     decimal_places: usize,
     color: bool,
@@ -46,6 +69,7 @@ pub struct Options
     comma: bool,
     prec: u32,
     frac_iter: usize,
+    history_size: usize,
     xr: [f64; 2],
     yr: [f64; 2],
     zr: [f64; 2],
@@ -55,7 +79,11 @@ pub struct Options
     lines: bool,
     multi: bool,
     tabbed: bool,
+    pretty: bool,
+    format: Format,
+    json: bool,
     allow_vars: bool,
+    session: bool,
     debug: bool,
 }
 impl Default for Options
@@ -63,12 +91,14 @@ impl Default for Options
     fn default() -> Self
     {
         Options {
-            sci: false,
+            notation: Notation::Auto,
+            sig: 6,
             deg: AngleType::Radians,
             base: 10,
             tau: false,
             polar: false,
             frac: true,
+            exact: false,
             real_time_output: true,
             decimal_places: 12,
             color: true,
@@ -76,6 +106,7 @@ impl Default for Options
             comma: false,
             prec: 512,
             frac_iter: 50,
+            history_size: 1000,
             xr: [-10.0, 10.0],
             yr: [-10.0, 10.0],
             zr: [-10.0, 10.0],
@@ -85,7 +116,11 @@ impl Default for Options
             lines: false,
             multi: false,
             tabbed: false,
+            pretty: false,
+            format: Format::Text,
+            json: false,
             allow_vars: true,
+            session: false,
             debug: false,
         }
     }
@@ -103,7 +138,8 @@ fn main()
         var("USERNAME").unwrap()
     );
     let mut args = args().collect::<Vec<String>>();
-    if file_opts(&mut options, file_path) || arg_opts(&mut options, &mut args)
+    let config = file_opts(&mut options, file_path);
+    if arg_opts(&mut options, &mut args, &config)
     {
         std::process::exit(1);
     }
@@ -116,6 +152,8 @@ fn main()
         Vec::new()
     };
     let mut old = vars.clone();
+    // the constants were made at this precision, a session may change it
+    let mut prec = options.prec;
     #[cfg(unix)]
     let file_path = &(var("HOME").unwrap() + "/.config/kalc.vars");
     #[cfg(not(unix))]
@@ -129,14 +167,30 @@ fn main()
             .lines()
             .map(|l| l.unwrap())
             .collect::<Vec<String>>();
-        let mut split;
         for i in lines
         {
-            split = i.split('=');
-            vars.push([
-                split.next().unwrap().to_string(),
-                split.next().unwrap().to_string(),
-            ]);
+            if let Some((l, r)) = split_equation(&i)
+            {
+                vars.push([l.to_string(), r.to_string()]);
+            }
+        }
+    }
+    let mut answers: Vec<(String, NumStr)> = Vec::new();
+    #[cfg(unix)]
+    let session_path = &(var("HOME").unwrap() + "/.config/kalc.session");
+    #[cfg(not(unix))]
+    let session_path = &format!(
+        "C:\\Users\\{}\\AppData\\Roaming\\kalc.session",
+        var("USERNAME").unwrap()
+    );
+    if options.session
+    {
+        if let Ok(text) = read_to_string(session_path)
+        {
+            for (line, e) in load(&text, &mut vars, &mut options, &mut answers)
+            {
+                println!("{} line {}: {}", session_path, line, e);
+            }
         }
     }
     let mut input = String::new();
@@ -161,14 +215,18 @@ fn main()
     {
         File::create(file_path).unwrap();
     }
-    let mut file = OpenOptions::new().append(true).open(file_path).unwrap();
+    // only the newest copy of a repeated input is kept
+    let mut history: Vec<String> = Vec::new();
+    for l in BufReader::new(File::open(file_path).unwrap()).lines()
+    {
+        let l = l.unwrap();
+        history.retain(|h| *h != l);
+        history.push(l);
+    }
     let mut lines: Vec<String>;
-    let mut unmod_lines: Vec<String>;
-    let mut last = String::new();
     let mut current = String::new();
     let mut inputs: Vec<String>;
     let (
-        mut c,
         mut i,
         mut max,
         mut frac,
@@ -179,9 +237,10 @@ fn main()
         mut v,
         mut start,
         mut end,
-        mut placement,
+        mut line,
     );
     let mut exit = false;
+    let mut script: Option<Script> = None;
     'main: loop
     {
         if exit
@@ -194,19 +253,105 @@ fn main()
         }
         input.clear();
         frac = 0;
-        if !args.is_empty()
+        // the constants follow the precision whichever way it was changed
+        if options.prec != prec
         {
+            prec = options.prec;
+            if options.allow_vars
+            {
+                v = get_vars(prec);
+                for i in &old
+                {
+                    for (j, var) in vars.iter_mut().enumerate()
+                    {
+                        if v.len() > j && i[0] == v[j][0] && i[1] == var[1]
+                        {
+                            *var = v[j].clone();
+                        }
+                    }
+                }
+                old = v;
+            }
+        }
+        if !args.is_empty() || script.is_some()
+        {
+            // a script hands over its lines one at a time and runs its control flow in between
+            // save and load take .kalc files too without them being scripts
+            if script.is_none()
+                && args[0].ends_with(".kalc")
+                && !COMMANDS.contains(&args[0].split(' ').next().unwrap())
+            {
+                let name = args.remove(0);
+                script = match read_to_string(&name).map(|s| Script::new(&name, &s))
+                {
+                    Ok(Ok(s)) => Some(s),
+                    Ok(Err(())) => std::process::exit(1),
+                    Err(_) =>
+                    {
+                        println!("Invalid file");
+                        std::process::exit(1);
+                    }
+                };
+            }
+            if let Some(s) = script.as_mut()
+            {
+                match s.next(&vars, options)
+                {
+                    Ok(Some(l)) => args.insert(0, l),
+                    Ok(None) =>
+                    {
+                        script = None;
+                        exit = args.is_empty();
+                        continue;
+                    }
+                    Err(()) => std::process::exit(1),
+                }
+            }
             if options.debug
             {
                 watch = Some(std::time::Instant::now());
             }
-            input = args.first().unwrap().replace('_', &format!("({})", last));
+            input = from_latex(args.first().unwrap());
             args.remove(0);
-            print_answer(
+            if let Some(s) = command(&input, &mut vars, &old, &mut options, &config, &mut answers)
+            {
+                println!("{}", reply(&input, s, options));
+                if args.is_empty() && script.is_none()
+                {
+                    exit = true;
+                }
+                continue;
+            }
+            let (expr, base) = match conversion(&input)
+            {
+                None => (input.as_str(), options.base),
+                Some(Ok(c)) if !graph_input(c.0, &vars, options.prec) => c,
+                // a graph has no one value to write in another base
+                e =>
+                {
+                    let e = e
+                        .and_then(Result::err)
+                        .unwrap_or("only a value can be converted".to_string());
+                    if options.json
+                    {
+                        println!("{}", json_error(&input, &e));
+                    }
+                    else
+                    {
+                        println!("{}", e);
+                    }
+                    if args.is_empty() && script.is_none()
+                    {
+                        exit = true;
+                    }
+                    continue;
+                }
+            };
+            let value = print_answer(
                 &input,
                 match get_func(
                     &input_var(
-                        &input
+                        &expr
                             .chars()
                             .map(convert)
                             .collect::<String>()
@@ -239,653 +384,226 @@ fn main()
                     ),
                     options.prec,
                 )
+                .and_then(|f| recall(f, &answers, options.prec))
                 {
                     Ok(f) => f,
                     Err(()) =>
                     {
+                        // a program reading json gets an error object and the rest of its input
+                        if options.json
+                        {
+                            println!("{}", json_error(&input, "invalid function"));
+                            if args.is_empty() && script.is_none()
+                            {
+                                exit = true;
+                            }
+                            continue;
+                        }
                         println!("Invalid function.");
                         return;
                     }
                 },
-                options,
+                Options { base, ..options },
+                &vars,
             );
             if let Some(time) = watch
             {
                 print!(" {}", time.elapsed().as_nanos());
             }
             if !(input.is_empty()
-                || input.contains('#')
-                || (input
-                    .replace("exp", "")
-                    .replace("max", "")
-                    .replace("}x{", "")
-                    .replace("]x[", "")
-                    .contains('x')
-                    && vars.iter().all(|i| i[0] != "x"))
-                || (input.contains('y') && vars.iter().all(|i| i[0] != "y"))
-                || (input
-                    .replace("zeta", "")
-                    .replace("normalize", "")
-                    .contains('z')
-                    && vars.iter().all(|i| i[0] != "z"))
-                || input
-                    .replace("==", "")
-                    .replace("!=", "")
-                    .replace(">=", "")
-                    .replace("<=", "")
-                    .contains('='))
+                || graph_input(&input, &vars, options.prec)
+                || is_equation(&input))
             {
                 println!();
             }
-            last = input.clone();
-            if args.is_empty()
+            // a message like "no answer 9" isn't kept as an answer
+            if let Some(v) =
+                value.filter(|v| !matches!(v, Str(_)) && answered(&input, &vars, options.prec))
+            {
+                answers.push((input.clone(), v));
+            }
+            if args.is_empty() && script.is_none()
             {
                 exit = true;
             }
         }
         else
         {
+            bracketed_paste(true);
             if options.prompt
             {
                 print!("{}> \x1b[0m", if options.color { "\x1b[94m" } else { "" });
                 stdout().flush().unwrap();
             }
             current.clear();
-            lines = BufReader::new(File::open(file_path).unwrap())
-                .lines()
-                .map(|l| l.unwrap())
-                .collect();
-            unmod_lines = lines.clone();
+            lines = history.clone();
             i = lines.len() as i32;
             max = i;
-            placement = 0;
-            last = lines.last().unwrap_or(&String::new()).clone();
             start = 0;
-            'outer: loop
+            line = Line::new();
+            loop
             {
-                c = read_single_char();
+                let mut key = read_input();
+                if let Input::Search = key
+                {
+                    key = search(&mut line, &history, &answers, &vars, options);
+                    start = scroll(&line, 0, options);
+                }
                 if options.debug
                 {
                     watch = Some(std::time::Instant::now());
                 }
-                match c
+                let mut edited = true;
+                match key
                 {
-                    '\n' =>
+                    Input::Enter =>
                     {
-                        end = start + get_terminal_width() - if options.prompt { 3 } else { 0 };
-                        if end > input.len()
+                        if incomplete(&line.input())
                         {
-                            end = input.len()
+                            line.carry();
+                            start = 0;
+                            println!();
+                            frac = draw(&line, start, true, &answers, &vars, options, watch)
+                                .unwrap_or(frac);
+                            stdout().flush().unwrap();
+                            continue;
                         }
+                        input = line.input();
                         if !options.real_time_output
                         {
+                            end = (start + get_terminal_width()
+                                - if options.prompt { 3 } else { 0 })
+                            .min(line.chars.len());
                             frac = print_concurrent(
                                 &input,
-                                &input_var(
-                                    &input.replace('_', &format!("({})", last)),
-                                    &vars,
-                                    None,
-                                ),
+                                &input_var(&input, &vars, None),
                                 options,
-                                start,
-                                end,
+                                &vars,
+                                &answers,
+                                &highlight(&line, start, end, None, &vars, options),
                             );
                         }
-                        if !(input.is_empty()
-                            || input.contains('#')
-                            || (input
-                                .replace("exp", "")
-                                .replace("max", "")
-                                .replace("}x{", "")
-                                .replace("]x[", "")
-                                .contains('x')
-                                && vars.iter().all(|i| i[0] != "x"))
-                            || (input.contains('y') && vars.iter().all(|i| i[0] != "y"))
-                            || (input
-                                .replace("zeta", "")
-                                .replace("normalize", "")
-                                .contains('z')
-                                && vars.iter().all(|i| i[0] != "z"))
-                            || input
-                                .replace("==", "")
-                                .replace("!=", "")
-                                .replace(">=", "")
-                                .replace("<=", "")
-                                .contains('='))
-                        {
-                            println!();
-                        }
-                        println!("{}", "\n".repeat(frac));
-                        break;
-                    }
-                    '\x08' =>
-                    {
-                        if placement - start == 0 && start != 0
-                        {
-                            start -= 1;
-                        }
-                        if placement == 0
-                        {
-                            if input.is_empty()
-                            {
-                                print!(
-                                    "\x1B[0J\x1B[2K\x1B[1G{}",
-                                    if options.prompt
-                                    {
-                                        if options.color
-                                        {
-                                            "\x1b[94m> \x1b[0m"
-                                        }
-                                        else
-                                        {
-                                            "> "
-                                        }
-                                    }
-                                    else
-                                    {
-                                        ""
-                                    }
-                                );
-                                stdout().flush().unwrap();
-                            }
-                            continue;
-                        }
-                        placement -= 1;
-                        input.remove(placement);
-                        end = start + get_terminal_width() - if options.prompt { 3 } else { 0 };
-                        if end > input.len()
-                        {
-                            end = input.len()
-                        }
-                        if i == max
-                        {
-                            current = input.clone();
-                        }
-                        else
-                        {
-                            lines[i as usize] = input.clone();
-                        }
-                        frac = if input.is_empty()
-                        {
-                            0
-                        }
-                        else if options.real_time_output
-                        {
-                            print_concurrent(
-                                &input,
-                                &input_var(
-                                    &input.replace('_', &format!("({})", last)),
-                                    &vars,
-                                    None,
-                                ),
-                                options,
-                                start,
-                                end,
-                            )
-                        }
                         else
                         {
+                            // drawn again without the cursor's bracket match
+                            end = (start + get_terminal_width()
+                                - if options.prompt { 3 } else { 0 })
+                            .min(line.chars.len());
                             print!(
-                                "\x1B[2K\x1B[1G{}{}\x1b[0m",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..end]
-                            );
-                            0
-                        };
-                        if let Some(time) = watch
-                        {
-                            let time = time.elapsed().as_nanos();
-                            print!(
-                                " {}{}",
-                                time,
-                                "\x08".repeat(
-                                    time.to_string().len() + 1 + end - start - (placement - start)
-                                )
+                                "\x1B[2K\x1B[1G{}{}{}",
+                                prompt(&options),
+                                highlight(&line, start, end, None, &vars, options),
+                                if options.color { "\x1b[0m" } else { "" }
                             );
                         }
-                        else
-                        {
-                            print!("{}", "\x08".repeat(end - start - (placement - start)));
-                        }
-                        if placement == 0 && input.is_empty()
+                        if !(input.is_empty()
+                            || graph_input(&input, &vars, options.prec)
+                            || is_equation(&input))
                         {
-                            print!(
-                                "\x1B[0J\x1B[2K\x1B[1G{}",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[0m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else
-                                {
-                                    ""
-                                }
-                            );
+                            println!();
                         }
+                        println!("{}", "\n".repeat(frac));
+                        break;
                     }
-                    '\x1D' =>
+                    Input::Char(c) if convert(c) == '\0' => continue,
+                    Input::Char(c) => type_str(&mut line, &c.to_string()),
+                    Input::Paste(s) => type_str(&mut line, &from_latex(&s)),
+                    Input::Backspace => line.backspace(),
+                    Input::Delete => line.delete(),
+                    Input::KillEnd => line.kill_end(),
+                    Input::KillStart => line.kill_start(),
+                    Input::KillWord => line.kill_word(),
+                    Input::Up | Input::Down =>
                     {
-                        // up history
-                        i -= if i > 0 { 1 } else { 0 };
-                        input = lines[i as usize].clone();
-                        placement = input.len();
-                        end = input.len();
-                        start = if get_terminal_width() - if options.prompt { 3 } else { 0 }
-                            > input.len()
+                        if let Input::Up = key
                         {
-                            0
-                        }
-                        else
-                        {
-                            input.len()
-                                - (get_terminal_width() - if options.prompt { 3 } else { 0 })
-                        };
-                        if options.real_time_output
-                        {
-                            frac = print_concurrent(
-                                &input,
-                                &input_var(
-                                    &input.replace('_', &format!("({})", last)),
-                                    &vars,
-                                    None,
-                                ),
-                                options,
-                                start,
-                                end,
-                            );
+                            i -= if i > 0 { 1 } else { 0 };
                         }
                         else
                         {
-                            print!(
-                                "\x1B[0J\x1B[2K\x1B[1G{}{}\x1b[0m",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..]
-                            );
+                            i = (i + 1).min(max);
                         }
-                    }
-                    '\x1E' =>
-                    {
-                        // down history
-                        i += 1;
-                        if i >= max
-                        {
-                            input = current.clone();
-                            i = max;
-                            if input.is_empty()
+                        line.set("");
+                        type_str(
+                            &mut line,
+                            if i == max
                             {
-                                print!("\x1B[0J\n\x1B[2K\x1B[1G\n\x1B[2K\x1B[1G\x1b[A\x1b[A\x1B[2K\x1B[1G{}",
-                                       if options.prompt
-                                       {
-                                           if options.color
-                                           {
-                                               "\x1b[94m> \x1b[0m"
-                                           }
-                                           else
-                                           {
-                                               "> "
-                                           }
-                                       }
-                                       else
-                                       {
-                                           ""
-                                       });
-                                stdout().flush().unwrap();
-                                placement = 0;
-                                start = 0;
-                                continue 'outer;
+                                &current
                             }
-                        }
-                        else
-                        {
-                            input = lines[i as usize].clone();
-                        }
-                        placement = input.len();
-                        end = input.len();
-                        start = if get_terminal_width() - if options.prompt { 3 } else { 0 }
-                            > input.len()
-                        {
-                            0
-                        }
-                        else
-                        {
-                            input.len()
-                                - (get_terminal_width() - if options.prompt { 3 } else { 0 })
-                        };
-                        if options.real_time_output
-                        {
-                            frac = print_concurrent(
-                                &input,
-                                &input_var(
-                                    &input.replace('_', &format!("({})", last)),
-                                    &vars,
-                                    None,
-                                ),
-                                options,
-                                start,
-                                end,
-                            );
-                        }
-                        else
+                            else
+                            {
+                                &lines[i as usize]
+                            },
+                        );
+                        start = 0;
+                        edited = false;
+                    }
+                    Input::Left
+                    | Input::Right
+                    | Input::WordLeft
+                    | Input::WordRight
+                    | Input::Home
+                    | Input::End =>
+                    {
+                        match key
                         {
-                            print!(
-                                "\x1B[2K\x1B[1G{}{}\x1b[0m",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..]
-                            );
+                            Input::Left => line.left(),
+                            Input::Right => line.right(),
+                            Input::WordLeft => line.word_left(),
+                            Input::WordRight => line.word_right(),
+                            Input::Home => line.cursor = 0,
+                            _ => line.cursor = line.chars.len(),
                         }
+                        start = scroll(&line, start, options);
+                        draw(&line, start, false, &answers, &vars, options, watch);
+                        stdout().flush().unwrap();
+                        continue;
                     }
-                    '\x1B' =>
+                    Input::Tab =>
                     {
-                        // go left
-                        if placement - start == 0 && placement != 0 && start != 0
+                        let list = complete(&mut line, &vars);
+                        if !list.is_empty()
                         {
-                            start -= 1;
-                            placement -= 1;
-                            end = start + get_terminal_width() - if options.prompt { 3 } else { 0 };
-                            if end > input.len()
-                            {
-                                end = input.len()
-                            }
+                            let rows = columns(&list, get_terminal_width());
                             print!(
-                                "\x1B[2K\x1B[1G{}{}\x1b[0m{}",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..end],
-                                "\x08".repeat(end - start - (placement - start))
+                                "\x1B[0J\n\x1B[2K\x1B[1G{}{}",
+                                rows.join("\n"),
+                                "\x1b[A".repeat(rows.len())
                             );
+                            draw(&line, start, false, &answers, &vars, options, watch);
                             stdout().flush().unwrap();
-                        }
-                        else if placement != 0
-                        {
-                            placement -= 1;
-                            print!("\x08");
+                            continue;
                         }
                     }
-                    '\x1C' =>
+                    Input::Search | Input::Escape | Input::None => continue,
+                }
+                if edited
+                {
+                    if i == max
                     {
-                        // go right
-                        end = start + get_terminal_width() - if options.prompt { 3 } else { 0 };
-                        if end > input.len()
-                        {
-                            end = input.len()
-                        }
-                        if placement == end && end != input.len()
-                        {
-                            start += 1;
-                            placement += 1;
-                            print!(
-                                "\x1B[2K\x1B[1G{}{}\x1b[0m",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..end + 1]
-                            );
-                        }
-                        else if placement != input.len()
-                        {
-                            placement += 1;
-                            print!("\x1b[1C")
-                        }
+                        current = line.text();
                     }
-                    '\0' =>
-                    {}
-                    _ =>
+                    else
                     {
-                        convert_str(&mut input, c, &mut placement);
-                        end = start + get_terminal_width() - if options.prompt { 3 } else { 0 } + 1;
-                        if end > input.len()
-                        {
-                            end = input.len()
-                        }
-                        else if placement == end
-                        {
-                            if c == 'π'
-                            {
-                                start += 2;
-                            }
-                            else if c == 'τ'
-                            {
-                                start += 3;
-                            }
-                            else
-                            {
-                                start += 1;
-                            }
-                        }
-                        else if c == 'π'
-                        {
-                            end -= 2;
-                        }
-                        else if c == 'τ'
-                        {
-                            end -= 3;
-                        }
-                        else
-                        {
-                            end -= 1;
-                        }
-                        if i == max
-                        {
-                            current = input.clone();
-                        }
-                        else
-                        {
-                            lines[i as usize] = input.clone();
-                        }
-                        if options.real_time_output
-                        {
-                            frac = print_concurrent(
-                                &input,
-                                &input_var(
-                                    &input.replace('_', &format!("({})", last)),
-                                    &vars,
-                                    None,
-                                ),
-                                options,
-                                start,
-                                end,
-                            );
-                        }
-                        else
-                        {
-                            print!(
-                                "\x1B[0J\x1B[2K\x1B[1G{}{}\x1b[0m",
-                                if options.prompt
-                                {
-                                    if options.color
-                                    {
-                                        "\x1b[94m> \x1b[96m"
-                                    }
-                                    else
-                                    {
-                                        "> "
-                                    }
-                                }
-                                else if options.color
-                                {
-                                    "\x1b[96m"
-                                }
-                                else
-                                {
-                                    ""
-                                },
-                                &input[start..end]
-                            );
-                        }
-                        if let Some(time) = watch
-                        {
-                            let time = time.elapsed().as_nanos();
-                            print!(
-                                " {}{}",
-                                time,
-                                "\x08".repeat(
-                                    time.to_string().len() + 1 + end - start - (placement - start)
-                                )
-                            );
-                        }
-                        else if placement != input.len()
-                        {
-                            print!("{}", "\x08".repeat(end - start - (placement - start)));
-                        }
+                        lines[i as usize] = line.text();
                     }
                 }
+                start = scroll(&line, start, options);
+                frac = draw(&line, start, true, &answers, &vars, options, watch).unwrap_or(frac);
                 stdout().flush().unwrap();
             }
+            bracketed_paste(false);
             if input.is_empty()
             {
                 continue;
             }
             match input.as_str()
             {
-                "color" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.color = !options.color;
-                }
-                "prompt" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.prompt = !options.prompt;
-                }
-                "deg" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.deg = AngleType::Degrees;
-                }
-                "rad" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.deg = AngleType::Radians;
-                }
-                "grad" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.deg = AngleType::Gradians;
-                }
-                "rt" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.real_time_output = !options.real_time_output;
-                }
-                "tau" => options.tau = true,
-                "pi" => options.tau = false,
-                "sci" | "scientific" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.sci = !options.sci;
-                }
                 "clear" =>
                 {
                     print!("\x1B[2J\x1B[1;1H");
                     stdout().flush().unwrap();
                 }
-                "debug" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.debug = !options.debug;
-                    watch = None;
-                }
                 "help" =>
                 {
                     print!("\x1b[A\x1B[2K\x1B[1G");
@@ -893,42 +611,6 @@ fn main()
                     help();
                     continue;
                 }
-                "line" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.lines = !options.lines;
-                }
-                "polar" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.polar = !options.polar;
-                }
-                "frac" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.frac = !options.frac;
-                }
-                "multi" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.multi = !options.multi;
-                }
-                "tabbed" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.tabbed = !options.tabbed;
-                }
-                "comma" =>
-                {
-                    print!("\x1b[A\x1B[2K\x1B[1G");
-                    stdout().flush().unwrap();
-                    options.comma = !options.comma;
-                }
                 "history" =>
                 {
                     print!("\x1b[A\x1B[2K\x1B[1G");
@@ -959,6 +641,7 @@ fn main()
                                         .unwrap(),
                                     options.deg,
                                     options.prec,
+                                    &user_functions(&vars, options.prec),
                                 )
                                 .unwrap()
                                 .num()
@@ -990,52 +673,62 @@ fn main()
                 }
                 _ =>
                 {
+                    if let Some(opt) =
+                        option(&input).filter(|o| o.kind == Kind::Toggle || o.kind == Kind::Switch)
+                    {
+                        print!("\x1b[A\x1B[2K\x1B[1G");
+                        stdout().flush().unwrap();
+                        opt.toggle(&mut options);
+                        if opt.name == "debug"
+                        {
+                            watch = None;
+                        }
+                    }
+                    else if let Some(s) =
+                        command(&input, &mut vars, &old, &mut options, &config, &mut answers)
+                    {
+                        println!("{}", reply(&input, s, options));
+                        write(&input, file_path, &mut history, options.history_size);
+                        continue;
+                    }
                     split = input.splitn(2, ' ');
                     if split.next().unwrap() == "history"
                     {
                         print!("\x1b[A\x1B[2K\x1B[1G");
                         stdout().flush().unwrap();
-                        r = split.next().unwrap();
-                        for i in lines
+                        for i in history_matches(&lines, split.next().unwrap())
                         {
-                            if i.contains(r)
-                            {
-                                println!("{}", i);
-                            }
+                            println!("{}", i);
                         }
                         continue;
                     }
                 }
             }
-            write(&input, &mut file, &unmod_lines);
+            if answered(&input, &vars, options.prec)
+            {
+                if let Ok(v) = answer(&input, &vars, options, &answers).and_then(|v| {
+                    if let Str(_) = v
+                    {
+                        Err(())
+                    }
+                    else
+                    {
+                        Ok(v)
+                    }
+                })
+                {
+                    answers.push((input.clone(), v));
+                }
+            }
+            write(&input, file_path, &mut history, options.history_size);
         }
         if input.ends_with('=')
         {
             l = &input[..input.len() - 1];
-            match l
+            match setting(l)
             {
-                "color" => println!("{}", options.color),
-                "prompt" => println!("{}", options.prompt),
-                "rt" => println!("{}", options.real_time_output),
-                "sci" | "scientific" => println!("{}", options.sci),
-                "debug" => println!("{}", options.debug),
-                "line" => println!("{}", options.lines),
-                "polar" => println!("{}", options.polar),
-                "frac" => println!("{}", options.frac),
-                "multi" => println!("{}", options.multi),
-                "tabbed" => println!("{}", options.tabbed),
-                "comma" => println!("{}", options.comma),
-                "point" => println!("{}", options.point_style),
-                "base" => println!("{}", options.base),
-                "decimal" | "deci" | "decimals" => println!("{}", options.decimal_places),
-                "prec" | "precision" => println!("{}", options.prec),
-                "xr" => println!("{},{}", options.xr[0], options.xr[1]),
-                "yr" => println!("{},{}", options.yr[0], options.yr[1]),
-                "zr" => println!("{},{}", options.zr[0], options.zr[1]),
-                "frac_iter" => println!("{}", options.frac_iter),
-                "2d" => println!("{}", options.samples_2d),
-                "3d" => println!("{}", options.samples_3d),
-                _ =>
+                Some(opt) => println!("{}", opt.get(&options)),
+                None =>
                 {
                     for i in match get_func(&input_var(l, &vars, None), options.prec)
                     {
@@ -1093,6 +786,7 @@ fn main()
                                 print!("{{{}}}", str)
                             }
                             Str(n) => print!("{}", n),
+                            Tuple(n) => print!("{}", tuple_output(&Tuple(n), options).unwrap()),
                         }
                     }
                     println!();
@@ -1100,248 +794,74 @@ fn main()
             }
             continue;
         }
-        if input
-            .replace("==", "")
-            .replace("!=", "")
-            .replace(">=", "")
-            .replace("<=", "")
-            .contains('=')
+        if is_equation(&input)
         {
-            print!("\x1B[0J");
-            stdout().flush().unwrap();
-            split = input.splitn(2, '=');
-            l = split.next().unwrap();
-            r = split.next().unwrap();
+            // json goes to another program, which has no screen to clear
+            if !options.json
+            {
+                print!("\x1B[0J");
+                stdout().flush().unwrap();
+            }
+            (l, r) = split_equation(&input).unwrap();
             if l.is_empty()
             {
                 continue;
             }
-            match l
+            if !assignable(l)
             {
-                "point" =>
-                {
-                    if r == "."
-                        || r == "+"
-                        || r == "x"
-                        || r == "*"
-                        || r == "s"
-                        || r == "S"
-                        || r == "o"
-                        || r == "O"
-                        || r == "t"
-                        || r == "T"
-                        || r == "d"
-                        || r == "D"
-                        || r == "r"
-                        || r == "R"
-                    {
-                        options.point_style = r.chars().next().unwrap();
-                    }
-                    else
-                    {
-                        println!("Invalid point type");
-                    }
-                    continue;
-                }
-                "base" =>
-                {
-                    options.base = match r.parse::<usize>()
-                    {
-                        Ok(n) =>
-                        {
-                            if !(2..=36).contains(&n)
-                            {
-                                println!("Invalid base");
-                                options.base
-                            }
-                            else
-                            {
-                                n
-                            }
-                        }
-                        Err(_) =>
-                        {
-                            println!("Invalid base");
-                            options.base
-                        }
-                    };
-                    continue;
-                }
-                "decimal" | "deci" | "decimals" =>
-                {
-                    if r == "-1"
-                    {
-                        options.decimal_places = usize::MAX - 1;
-                    }
-                    else if r == "-2"
-                    {
-                        options.decimal_places = usize::MAX;
-                    }
-                    else
-                    {
-                        options.decimal_places = match r.parse::<usize>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid decimal");
-                                options.decimal_places
-                            }
-                        };
-                    }
-                    continue;
-                }
-                "prec" | "precision" =>
+                let solved = solve_equation(
+                    l,
+                    r,
+                    &vars,
+                    Options {
+                        color: options.color && !options.json,
+                        ..options
+                    },
+                );
+                if options.json
                 {
-                    options.prec = if r == "0"
-                    {
-                        println!("Invalid precision");
-                        options.prec
-                    }
-                    else
-                    {
-                        match r.parse::<u32>()
+                    println!(
+                        "{}",
+                        match solved
                         {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid precision");
-                                options.prec
-                            }
+                            Some(s) => json_answer(&input, &Str(s)),
+                            None => json_error(&input, "could not solve"),
                         }
-                    };
-                    if options.allow_vars
-                    {
-                        v = get_vars(options.prec);
-                        for i in &old
-                        {
-                            for (j, var) in vars.iter_mut().enumerate()
-                            {
-                                if v.len() > j && i[0] == v[j][0] && i[1] == var[1]
-                                {
-                                    *var = v[j].clone();
-                                }
-                            }
-                        }
-                        old = v;
-                    }
-                    continue;
-                }
-                "xr" =>
-                {
-                    if r.contains(',')
-                    {
-                        options.xr[0] = match r.split(',').next().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid x range");
-                                options.xr[0]
-                            }
-                        };
-                        options.xr[1] = match r.split(',').last().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid x range");
-                                options.xr[1]
-                            }
-                        };
-                        continue;
-                    }
+                    );
                 }
-                "yr" =>
+                else
                 {
-                    if r.contains(',')
-                    {
-                        options.yr[0] = match r.split(',').next().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid y range");
-                                options.yr[0]
-                            }
-                        };
-                        options.yr[1] = match r.split(',').last().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid y range");
-                                options.yr[1]
-                            }
-                        };
-                        continue;
-                    }
+                    println!("{}", solved.unwrap_or("could not solve".to_string()));
                 }
-                "zr" =>
+                continue;
+            }
+            if let Some(opt) = setting(l)
+            {
+                if let Err(e) = opt.set(&mut options, r)
                 {
-                    if r.contains(',')
+                    if options.json
                     {
-                        options.zr[0] = match r.split(',').next().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid z range");
-                                options.zr[0]
-                            }
-                        };
-                        options.zr[1] = match r.split(',').last().unwrap().parse::<f64>()
-                        {
-                            Ok(n) => n,
-                            Err(_) =>
-                            {
-                                println!("Invalid z range");
-                                options.zr[1]
-                            }
-                        };
-                        continue;
+                        println!("{}", json_error(&input, &e));
+                    }
+                    else
+                    {
+                        println!("{}", e);
                     }
                 }
-                "frac_iter" =>
-                {
-                    options.frac_iter = match r.parse::<usize>()
-                    {
-                        Ok(n) => n,
-                        Err(_) =>
-                        {
-                            println!("Invalid frac_iter");
-                            options.frac_iter
-                        }
-                    };
-                    continue;
-                }
-                "2d" =>
+                continue;
+            }
+            if let Some((p, q)) = forward_default(l, options.prec)
+            {
+                let e = format!("the default of {} uses {}, which has no value yet", p, q);
+                if options.json
                 {
-                    options.samples_2d = match r.parse::<f64>()
-                    {
-                        Ok(n) => n,
-                        Err(_) =>
-                        {
-                            println!("Invalid 2d sample size");
-                            options.samples_2d
-                        }
-                    };
-                    continue;
+                    println!("{}", json_error(&input, &e));
                 }
-                "3d" =>
+                else
                 {
-                    options.samples_3d = match r.parse::<f64>()
-                    {
-                        Ok(n) => n,
-                        Err(_) =>
-                        {
-                            println!("Invalid 3d sample size");
-                            options.samples_3d
-                        }
-                    };
-                    continue;
+                    println!("{}", e);
                 }
-                _ => (),
+                continue;
             }
             for (i, v) in vars.iter().enumerate()
             {
@@ -1366,29 +886,11 @@ fn main()
             vars.push([l.to_string(), r.to_string()]);
             continue;
         }
-        else if input.contains('#')
-            || (input
-                .replace("exp", "")
-                .replace("max", "")
-                .replace("}x{", "")
-                .replace("]x[", "")
-                .contains('x')
-                && vars.iter().all(|i| i[0] != "x"))
-            || (input
-                .replace("zeta", "")
-                .replace("normalize", "")
-                .contains('z')
-                && vars.iter().all(|i| i[0] != "z"))
+        else if graph_input(&input, &vars, options.prec)
         {
-            input = input
-                .replace("zeta", "##ta##")
-                .replace("normalize", "##ma##")
-                .replace('z', "(x+y*i)")
-                .replace("##ta##", "zeta")
-                .replace("##ma##", "normalize");
             print!("\x1b[2K\x1b[1G");
             stdout().flush().unwrap();
-            inputs = input.split('#').map(String::from).collect();
+            inputs = graphs(&input);
             funcs = Vec::new();
             for i in &inputs
             {
@@ -1409,10 +911,339 @@ fn main()
                 options.deg,
                 options.prec,
                 watch,
+                user_functions(&vars, options.prec),
             ));
             continue;
         }
     }
+    if options.session && save(session_path, &vars, &old, &options, &answers).is_err()
+    {
+        println!("could not save {}", session_path);
+    }
+}
+fn prompt(options: &Options) -> &'static str
+{
+    if options.prompt
+    {
+        if options.color
+        {
+            "\x1b[94m> \x1b[96m"
+        }
+        else
+        {
+            "> "
+        }
+    }
+    else if options.color
+    {
+        "\x1b[96m"
+    }
+    else
+    {
+        ""
+    }
+}
+// first visible char of the input, moved just enough to keep the cursor on screen
+fn scroll(line: &Line, start: usize, options: Options) -> usize
+{
+    let width = get_terminal_width() - if options.prompt { 3 } else { 0 };
+    let start = if line.cursor < start
+    {
+        line.cursor
+    }
+    else if line.cursor > start + width
+    {
+        line.cursor - width
+    }
+    else
+    {
+        start
+    };
+    start.min(line.chars.len().saturating_sub(width))
+}
+// redraws the input line, when the text changed it is evaluated again and the new frac is given back
+fn draw(
+    line: &Line,
+    start: usize,
+    changed: bool,
+    answers: &[(String, NumStr)],
+    vars: &[[String; 2]],
+    options: Options,
+    watch: Option<std::time::Instant>,
+) -> Option<usize>
+{
+    let end =
+        (start + get_terminal_width() - if options.prompt { 3 } else { 0 }).min(line.chars.len());
+    let input = line.input();
+    let text = highlight(line, start, end, Some(line.cursor), vars, options);
+    let frac = if changed && options.real_time_output && !input.is_empty()
+    {
+        Some(print_concurrent(
+            &input,
+            &input_var(&input, vars, None),
+            options,
+            vars,
+            answers,
+            &text,
+        ))
+    }
+    else
+    {
+        print!(
+            "\x1B[2K\x1B[1G{}{}{}{}",
+            if changed { "\x1B[0J" } else { "" },
+            prompt(&options),
+            text,
+            if options.color { "\x1b[0m" } else { "" }
+        );
+        changed.then_some(0)
+    };
+    if let Some(time) = watch
+    {
+        let time = time.elapsed().as_nanos();
+        print!(
+            " {}{}",
+            time,
+            "\x08".repeat(time.to_string().len() + 1 + end - line.cursor)
+        );
+    }
+    else
+    {
+        print!("{}", "\x08".repeat(end - line.cursor));
+    }
+    frac
+}
+// ctrl-r shows the newest input containing what's typed and ctrl-r again goes further back,
+// the key that ends it is handed back so enter runs the match, escape puts the line back
+fn search(
+    line: &mut Line,
+    history: &[String],
+    answers: &[(String, NumStr)],
+    vars: &[[String; 2]],
+    options: Options,
+) -> Input
+{
+    let original = line.text();
+    let mut query = String::new();
+    let mut found = history.len();
+    let mut failed = false;
+    loop
+    {
+        draw(
+            line,
+            scroll(line, 0, options),
+            true,
+            answers,
+            vars,
+            options,
+            None,
+        );
+        print!(
+            " {}({}search: {}){}",
+            if options.color { "\x1b[90m" } else { "" },
+            if failed { "failed " } else { "" },
+            query,
+            if options.color { "\x1b[0m" } else { "" }
+        );
+        stdout().flush().unwrap();
+        let from = match read_input()
+        {
+            Input::Char(c) =>
+            {
+                query.push(c);
+                (found + 1).min(history.len())
+            }
+            Input::Backspace =>
+            {
+                query.pop();
+                history.len()
+            }
+            Input::Search => found,
+            Input::Escape =>
+            {
+                line.set("");
+                type_str(line, &original);
+                draw(
+                    line,
+                    scroll(line, 0, options),
+                    true,
+                    answers,
+                    vars,
+                    options,
+                    None,
+                );
+                stdout().flush().unwrap();
+                return Input::None;
+            }
+            Input::None => continue,
+            key => return key,
+        };
+        match history[..from].iter().rposition(|l| l.contains(&query))
+        {
+            Some(n) if !query.is_empty() =>
+            {
+                found = n;
+                failed = false;
+                line.set("");
+                type_str(line, &history[n]);
+            }
+            _ => failed = !query.is_empty(),
+        }
+    }
+}
+// anything with only x, y or z left unbound once variables are substituted gets graphed
+fn graph_input(input: &str, vars: &[[String; 2]], prec: u32) -> bool
+{
+    graphs(input).len() > 1
+        || get_func(&input_var(input, vars, None), prec)
+            .map(|f| is_graph(&f))
+            .unwrap_or(false)
+}
+// a name or name(args) not taken by a function is assigned to, anything else is an equation to solve
+fn assignable(l: &str) -> bool
+{
+    let name = l.split('(').next().unwrap();
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !is_func(name)
+        && (name.len() == l.len() || l.ends_with(')'))
+}
+pub const COMMANDS: [&str; 18] = [
+    "clear", "help", "history", "vars", "lvars", "version", "exit", "simplify", "expand", "factor",
+    "get", "set", "reset", "profile", "save", "load", "latex", "mathml",
+];
+// an input's value with the answers it refers to filled in
+fn answer(
+    input: &str,
+    vars: &[[String; 2]],
+    options: Options,
+    answers: &[(String, NumStr)],
+) -> Result<NumStr, ()>
+{
+    let input = match conversion(input)
+    {
+        Some(Ok((expr, _))) => expr,
+        Some(Err(_)) => return Err(()),
+        None => input,
+    };
+    do_math(
+        recall(
+            get_func(&input_var(input, vars, None), options.prec)?,
+            answers,
+            options.prec,
+        )?,
+        options.deg,
+        options.prec,
+        &user_functions(vars, options.prec),
+    )
+}
+// an input that was evaluated to a value, what a session keeps as its answers
+fn answered(input: &str, vars: &[[String; 2]], prec: u32) -> bool
+{
+    let input = match conversion(input)
+    {
+        Some(Ok((expr, _))) => expr,
+        Some(Err(_)) => return false,
+        None => input,
+    };
+    !(input.is_empty()
+        || input.ends_with('=')
+        || is_equation(input)
+        || graph_input(input, vars, prec)
+        || COMMANDS.contains(&input)
+        || option(input).is_some())
+        && get_func(&input_var(input, vars, None), prec).is_ok()
+}
+fn command(
+    input: &str,
+    vars: &mut Vec<[String; 2]>,
+    old: &[[String; 2]],
+    options: &mut Options,
+    config: &Config,
+    answers: &mut Vec<(String, NumStr)>,
+) -> Option<String>
+{
+    if input == "profile"
+    {
+        return Some(config.profiles().join(" "));
+    }
+    let (l, r) = input.split_once(' ')?;
+    // json output is kept free of color escapes
+    let plain = Options {
+        color: options.color && !options.json,
+        ..*options
+    };
+    let out = match l
+    {
+        "simplify" => exact(r, vars, plain),
+        "expand" => expand(r, vars, plain),
+        "factor" => factor(r, vars, plain),
+        "get" | "set" | "reset" => return Some(set_option(l, r, options)),
+        "latex" | "mathml" => markup(
+            r,
+            if is_equation(r)
+            {
+                None
+            }
+            else
+            {
+                answer(r, vars, *options, answers).ok()
+            }
+            .as_ref(),
+            Options {
+                format: if l == "latex"
+                {
+                    Format::Latex
+                }
+                else
+                {
+                    Format::MathML
+                },
+                ..plain
+            },
+            vars,
+        ),
+        "profile" =>
+        {
+            return Some(
+                config
+                    .profile(r.trim(), options)
+                    .unwrap_or(format!("Unknown profile {}", r.trim())),
+            )
+        }
+        "save" => save(r.trim(), vars, old, options, answers)
+            .ok()
+            .map(|_| format!("saved {}", r.trim())),
+        "load" => read_to_string(r.trim()).ok().map(|text| {
+            let errors = load(&text, vars, options, answers);
+            if errors.is_empty()
+            {
+                format!("loaded {}", r.trim())
+            }
+            else
+            {
+                errors
+                    .iter()
+                    .map(|(line, e)| format!("{} line {}: {}", r.trim(), line, e))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            }
+        }),
+        _ => return None,
+    };
+    Some(out.unwrap_or(format!("could not {}", l)))
+}
+// what a command printed, as a json string when the json option is on
+fn reply(input: &str, s: String, options: Options) -> String
+{
+    if options.json
+    {
+        json_answer(input, &Str(s))
+    }
+    else
+    {
+        s
+    }
 }
 #[cfg(unix)]
 fn get_terminal_width() -> usize
@@ -1578,273 +1409,65 @@ pub fn parse(output: &mut String, c: char, i: usize, chars: &Vec<char>) -> bool
         _ => false,
     }
 }
-fn convert_str(input: &mut String, c: char, placement: &mut usize)
+// enters text as if typed, so the input only holds what the parser reads
+fn type_str(line: &mut Line, s: &str)
 {
+    for c in s.chars().map(convert).filter(|c| *c != '\0')
+    {
+        line.insert_str(&convert_str(c, line.chars[..line.cursor].last().copied()));
+    }
+}
+// what a typed character is entered as, superscripts after a number become powers
+fn convert_str(c: char, prev: Option<char>) -> String
+{
+    let sup = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
     match c
     {
-        'π' =>
-        {
-            input.insert_str(*placement, "pi");
-            *placement += 2;
-        }
-        'τ' =>
-        {
-            input.insert_str(*placement, "tau");
-            *placement += 3;
-        }
-        '√' =>
-        {
-            input.insert_str(*placement, "sqrt");
-            *placement += 4;
-        }
-        '∛' =>
-        {
-            input.insert_str(*placement, "cbrt");
-            *placement += 4;
-        }
-        '¼' =>
-        {
-            input.insert_str(*placement, "1/4");
-            *placement += 3;
-        }
-        '½' =>
-        {
-            input.insert_str(*placement, "1/2");
-            *placement += 3;
-        }
-        '¾' =>
+        'π' => "pi".to_string(),
+        'τ' => "tau".to_string(),
+        '√' => "sqrt".to_string(),
+        '∛' => "cbrt".to_string(),
+        '¼' => "1/4".to_string(),
+        '½' => "1/2".to_string(),
+        '¾' => "3/4".to_string(),
+        '⅐' => "1/7".to_string(),
+        '⅑' => "1/9".to_string(),
+        '⅒' => "1/10".to_string(),
+        '⅓' => "1/3".to_string(),
+        '⅔' => "2/3".to_string(),
+        '⅕' => "1/5".to_string(),
+        '⅖' => "2/5".to_string(),
+        '⅗' => "3/5".to_string(),
+        '⅘' => "4/5".to_string(),
+        '⅙' => "1/6".to_string(),
+        '⅚' => "5/6".to_string(),
+        '⅛' => "1/8".to_string(),
+        '⅜' => "3/8".to_string(),
+        '⅝' => "5/8".to_string(),
+        '⅞' => "7/8".to_string(),
+        '⅟' => "1/".to_string(),
+        '↉' => "0/3".to_string(),
+        c if sup.contains(&c) =>
         {
-            input.insert_str(*placement, "3/4");
-            *placement += 3;
-        }
-        '⅐' =>
-        {
-            input.insert_str(*placement, "1/7");
-            *placement += 3;
-        }
-        '⅑' =>
-        {
-            input.insert_str(*placement, "1/9");
-            *placement += 3;
-        }
-        '⅒' =>
-        {
-            input.insert_str(*placement, "1/10");
-            *placement += 4;
-        }
-        '⅓' =>
-        {
-            input.insert_str(*placement, "1/3");
-            *placement += 3;
-        }
-        '⅔' =>
-        {
-            input.insert_str(*placement, "2/3");
-            *placement += 3;
-        }
-        '⅕' =>
-        {
-            input.insert_str(*placement, "1/5");
-            *placement += 3;
-        }
-        '⅖' =>
-        {
-            input.insert_str(*placement, "2/5");
-            *placement += 3;
-        }
-        '⅗' =>
-        {
-            input.insert_str(*placement, "3/5");
-            *placement += 3;
-        }
-        '⅘' =>
-        {
-            input.insert_str(*placement, "4/5");
-            *placement += 3;
-        }
-        '⅙' =>
-        {
-            input.insert_str(*placement, "1/6");
-            *placement += 3;
-        }
-        '⅚' =>
-        {
-            input.insert_str(*placement, "5/6");
-            *placement += 3;
-        }
-        '⅛' =>
-        {
-            input.insert_str(*placement, "1/8");
-            *placement += 3;
-        }
-        '⅜' =>
-        {
-            input.insert_str(*placement, "3/8");
-            *placement += 3;
-        }
-        '⅝' =>
-        {
-            input.insert_str(*placement, "5/8");
-            *placement += 3;
-        }
-        '⅞' =>
-        {
-            input.insert_str(*placement, "7/8");
-            *placement += 3;
-        }
-        '⅟' =>
-        {
-            input.insert_str(*placement, "1/");
-            *placement += 3;
-        }
-        '↉' =>
-        {
-            input.insert_str(*placement, "0/3");
-            *placement += 3;
-        }
-        '⁰' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^0");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '0');
-                *placement += 1;
-            }
-        }
-        '⁹' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^9");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '9');
-                *placement += 1;
-            }
-        }
-        '⁸' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^8");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '8');
-                *placement += 1;
-            }
-        }
-        '⁷' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^7");
-                *placement += 2;
-            }
-            else
+            let n = sup.iter().position(|s| *s == c).unwrap();
+            if prev.is_some_and(|p| p.is_numeric())
             {
-                input.insert(*placement, '7');
-                *placement += 1;
-            }
-        }
-        '⁶' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^6");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '6');
-                *placement += 1;
-            }
-        }
-        '⁵' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^5");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '5');
-                *placement += 1;
-            }
-        }
-        '⁴' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^4");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '4');
-                *placement += 1;
-            }
-        }
-        '³' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^3");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '3');
-                *placement += 1;
-            }
-        }
-        '²' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^2");
-                *placement += 2;
-            }
-            else
-            {
-                input.insert(*placement, '2');
-                *placement += 1;
-            }
-        }
-        '¹' =>
-        {
-            if !input.is_empty() && input.chars().last().unwrap().is_numeric()
-            {
-                input.insert_str(*placement, "^1");
-                *placement += 2;
+                format!("^{}", n)
             }
             else
             {
-                input.insert(*placement, '1');
-                *placement += 1;
+                n.to_string()
             }
         }
-        _ =>
-        {
-            input.insert(*placement, c);
-            *placement += 1;
-        }
+        _ => c.to_string(),
     }
 }
 fn convert(c: char) -> char
 {
     let valid_chars = [
-        '+', '^', '(', ')', '.', '=', ',', '#', '|', '&', '!', '%', '_', '<', '>', ' ', '[', ']',
-        '{', '}', '√', '∛', '¼', '½', '¾', '⅐', '⅑', '⅒', '⅓', '⅔', '⅕', '⅖', '⅗', '⅘', '⅙', '⅚',
-        '⁹', '⁸', '⁷', '⁶', '⁵', '⁴', '³', '²', '¹', '⁰', '⅛', '⅜', '⅝', '⅞', '⅟', '↉',
+        '+', '^', '(', ')', '.', '=', ',', '#', '|', '&', '!', '%', '_', '$', '<', '>', ' ', '[',
+        ']', '{', '}', '√', '∛', '¼', '½', '¾', '⅐', '⅑', '⅒', '⅓', '⅔', '⅕', '⅖', '⅗', '⅘', '⅙',
+        '⅚', '⁹', '⁸', '⁷', '⁶', '⁵', '⁴', '³', '²', '¹', '⁰', '⅛', '⅜', '⅝', '⅞', '⅟', '↉',
     ];
     match c
     {
@@ -1867,84 +1490,107 @@ fn convert(c: char) -> char
         _ => '\0',
     }
 }
-fn read_single_char() -> char
+// the input goes last and an older copy of it is dropped, the file is only
+// rewritten when that or the size limit takes a line out
+fn write(input: &str, file_path: &str, history: &mut Vec<String>, size: usize)
 {
-    let term = Term::stdout();
-    let key = term.read_key().unwrap();
-    match key
+    if history.last().is_some_and(|l| l == input)
     {
-        Key::Char(c) => convert(c),
-        Key::Enter => '\n',
-        Key::Backspace => '\x08',
-        Key::ArrowLeft => '\x1B',
-        Key::ArrowRight => '\x1C',
-        Key::ArrowUp => '\x1D',
-        Key::ArrowDown => '\x1E',
-        _ => '\0',
+        return;
     }
-}
-fn write(input: &str, file: &mut File, lines: &Vec<String>)
-{
-    if lines.is_empty() || lines[lines.len() - 1] != *input
+    let len = history.len();
+    history.retain(|l| l != input);
+    history.push(input.to_string());
+    if history.len() > size
+    {
+        history.drain(..history.len() - size);
+    }
+    if history.len() == len + 1
     {
+        let mut file = OpenOptions::new().append(true).open(file_path).unwrap();
         file.write_all(input.as_bytes()).unwrap();
         file.write_all(b"\n").unwrap();
     }
+    else
+    {
+        let mut file = File::create(file_path).unwrap();
+        for l in history.iter()
+        {
+            file.write_all(l.as_bytes()).unwrap();
+            file.write_all(b"\n").unwrap();
+        }
+    }
+}
+// the lines "history text" and "history search text" list
+fn history_matches<'a>(history: &'a [String], arg: &str) -> Vec<&'a String>
+{
+    let query = arg.strip_prefix("search ").unwrap_or(arg);
+    history.iter().filter(|l| l.contains(query)).collect()
 }
 fn help()
 {
     println!(
-             "Usage: kalc [FLAGS] function_1 function_2 function_3...\n\
-FLAGS: --help (this message)\n\
---tau fractions are shown in tau instead of pi\n\
---deg compute in degrees\n\
---rad compute in radians\n\
---grad compute in gradians\n\
---2d=[num] number of points to graph in 2D\n\
---3d=[num] number of points to graph in 3D\n\
---xr=[min],[max] x range for graphing\n\
---yr=[min],[max] y range for graphing\n\
---zr=[min],[max] z range for graphing\n\
---point [char] point style for graphing\n\
---sci toggles scientific notation\n\
---base=[num] sets the number base (2,8,16)\n\
---prompt toggles the prompt\n\
---color toggles color\n\
---comma toggles comma seperation\n\
---vars toggles default variables\n\
---line toggles line graphing\n\
---rt toggles real time printing\n\
---polar toggles displaying polar vectors\n\
---frac toggles fraction display\n\
---frac_iter=[num] how many iterations to check for fractions\n\
---prec=[num] sets the precision\n\
---deci=[num] sets how many decimals to display, -1 for length of terminal, -2 for maximum decimal places, may need to up precision for more decimals\n\
---def ignores config file\n\
---multi toggles multi line display for matrixes\n\
---tabbed toggles tabbed display for matrixes\n\
---debug displays computation time in nanoseconds\n\n\
-- flags can be executed in runtime just without the dashes\n\
+        "Usage: kalc [FLAGS] function_1 function_2 function_3...\nFLAGS: --help (this message)"
+    );
+    for opt in &OPTIONS
+    {
+        println!("{}", opt.flag());
+    }
+    println!(
+             "--def ignores config file\n\
+--profile=[name] applies [profile.name] from the config file\n\n\
+- flags can be executed in runtime just without the dashes, name=value sets the ones that take a value\n\
+- Type \"get [option]\", \"set [option]=[value]\" or \"reset [option]\" to read, change or restore any option\n\
+- Type \"profile [name]\" to switch to a profile from the config file, \"profile\" lists them\n\
+- Type \"save [file]\" to write your variables, functions, options and answers to a file and \"load [file]\" to bring them back\n\
 - Type \"exit\" to exit the program\n\
 - Type \"clear\" to clear the screen\n\
-- Type \"history [arg]\" to see the history, arg indexes it if specified\n\
+- Type \"history\" to see the history, \"history search [text]\" to only see inputs containing text\n\
+- Up/Down or Ctrl-P/N go through the history, Left/Right or Ctrl-B/F move the cursor\n\
+- Home/End or Ctrl-A/E jump to the start/end, Ctrl-Left/Right or Alt-B/F move by word\n\
+- Delete or Ctrl-D deletes forward, Ctrl-K/U delete to the end/start, Ctrl-W or Alt-Backspace delete a word\n\
+- Tab completes functions, variables, options and commands, pressing it again lists the choices with their arguments\n\
+- Ctrl-R searches the history as you type, Ctrl-R again finds older matches, Enter runs it and Escape or Ctrl-G cancels\n\
+- The input is colored as you type, unknown names and unmatched brackets are red and the bracket at the cursor is shown with its match\n\
+- Anything that can't be evaluated gets a hint under it, like an unknown name or a dimension mismatch, or what Enter will plot or solve for\n\
+- An unclosed {{ or [ or a trailing \\ continues the input on the next line, pasted lines are joined\n\
+- Latex that is pasted or given as an argument, like \\frac{{1}}{{2}}, \\sqrt[3]{{x}}, \\sin^{{2}}(x), \\sum_{{n=1}}^{{10}} n^2 or a pmatrix, is read as the same input in kalc's syntax\n\
+- Type \"simplify [expr]\" to show the exact form of an expression\n\
+- Type \"expand [expr]\" or \"factor [expr]\" to expand or factor a polynomial in x with integer coefficients\n\
+- Type \"latex [expr]\" or \"mathml [expr]\" to write an expression and its answer as latex or mathml\n\
 - Type \"vars\" to list all variables\n\
 - Type \"lvars\" to list all variables without equating them\n\
-- Type \"_\" to use the previous answer\n\
+- Type \"_\" to use the previous answer, \"$n\" or \"ans(n)\" for the nth answer and \"ans(-n)\" to count back from the last\n\
+- Type \"0x1F\", \"0b1010\", \"0o17\", \"36#zz\" or \"0x1.8p3\" for numbers in other bases and \"x to hex\", \"x to bin\", \"x to oct\" or \"x to base n\" to show an answer in another base\n\
 - Type \"a={{expr}}\" to define a variable\n\
 - Type \"f(x)=...\" to define a function\n\
 - Type \"f(x,y,z...)=...\" to define a multi variable function\n\
+- Functions can call themselves like \"fib(n)=if(n<2,n,fib(n-1)+fib(n-2))\", parameters don't see variables of the same name\n\
+- Type \"f(x,n=2)=...\" to give a parameter a default used when it's left out, a default can use\n\
+  the parameters before it like f(x,y=x)\n\
 - Type \"...=\" display parsed input, show values of stuff like xr/deci/prec etc\n\
 - Type \"f...=null\" to delete a function or variable\n\
+- Type an equation in one unknown like \"2x+1=5\" or \"cos(t)=t\" to solve it\n\
+- Anything left with only x, y or z unknown is graphed\n\
 - Type \"{{x,y,z...}}\" to define a cartesian vector\n\
 - Type \"[radius,theta,phi]\" to define a polar vector (same as car{{vec}})\n\
 - Type \"{{vec}}#\" to graph a vector\n\
 - Type \"{{mat}}#\" to graph a matrix\n\
 - Type \"number#\" to graph a complex number\n\
 - Type \"{{{{a,b,c}},{{d,e,f}},{{g,h,i}}}}\" to define a 3x3 matrix\n\n\
+Scripts:\n\
+- \"kalc file.kalc\" runs the file a line at a time, variables take their value when assigned\n\
+- a # at the start of a line or after a space starts a comment, without the space it still separates graphs\n\
+- if [cond], else if [cond], else and end pick which lines run, a condition is true when it isn't 0\n\
+- \"while [cond]\" ... \"end\" repeats while the condition holds\n\
+- \"for i in 1..10\" or \"for v in {{vec}}\" ... \"end\" runs once per number, entry or matrix row\n\
+- print [expr] shows a value, print \"text {{expr}}\" fills in each {{expr}} and {{{{ }}}} are literal braces\n\
+\n\
 Operators:\n\
 - +, -, *, /, ^, %, <, >, <=, >=\n\
 - !x (subfact), x! (fact)\n\
 - && (and), || (or), == (equals), != (not equals)\n\
+- comparisons are 1 when true and 0 when false, so (x>0)*x is a ramp, step functions graph without vertical lines at their jumps\n\
 - >> (right shift), << (left shift)\n\n\
 Trigonometric functions:\n\
 - sin, cos, tan, asin, acos, atan, atan(x,y)\n\
@@ -1954,6 +1600,8 @@ Trigonometric functions:\n\
 Other functions:\n\
 - sqrt, cbrt, square, cube\n\
 - ln, log(base,num), root(base,exp), sum(func,var,start,end), prod(func,var,start,end) (start and end are rounded to integers)\n\
+- if(cond,a,b) is a when cond isn't 0 and b otherwise, only the one picked is evaluated, if(cond,a) is undefined when cond is 0\n\
+- piecewise({{cond1,v1}},{{cond2,v2}},...,else) is the value of the first condition that holds, else if none do (undefined without one)\n\
 - abs, sgn, arg\n\
 - ceil, floor, round, int, frac\n\
 - fact(real), subfact(natural)\n\
@@ -1968,18 +1616,35 @@ Vector operations/functions:\n\
 - norm, normalize\n\
 - abs, len\n\
 - part({{vec}},col)\n\
+- x->x^2 and (a,b)->a+b are functions for the ones below, so are names like sqrt or a defined f\n\
+- map(f,{{vec}}) applies f to each entry, filter(f,{{vec}}) keeps the entries f is true for (rows for a matrix)\n\
+- reduce(f,{{vec}}) and fold(f,init,{{vec}}) combine the entries left to right, zipwith(f,{{vec1}},{{vec2}}) pairs them up\n\
+- table(expr,var,start,end,step) lists expr for var from start to end, step defaults to 1\n\
 - convert to polar: pol{{vec}} outputs (radius, theta, phi)\n\
 - convert to cartesian: car{{vec}} outputs (x, y, z)\n\
 - other functions are applied like sqrt{{2,4}}={{sqrt(2),sqrt(4)}}\n\n\
+Polynomial functions (coefficients as a vector, highest degree first):\n\
+- polyval({{poly}},x), polyroots({{poly}})\n\
+- polymul({{poly1}},{{poly2}}), polydiv({{poly1}},{{poly2}}) (quotient and remainder)\n\
+- polyder({{poly}}), polyint({{poly}}), polyint({{poly}},constant)\n\
+- polyfit({{xs}},{{ys}},degree) (least squares)\n\n\
 Matrix operations/functions:\n\
-- *,/,+,-,^\n\
+- *,/,+,-,^,.^\n\
 - trace/tr, determinant/det, inverse/inv\n\
+- linsolve({{{{mat}}}},{{vec}}) solves Ax=b, b can also be a matrix of right hand sides\n\
 - transpose/trans, adjugate/adj, cofactor/cof, minor\n\
+- eigvals, rank, rref, cholesky/chol, nullspace/null (basis vectors as columns)\n\
+- decompositions return tuples, pick one with part(qr(a),2):\n\
+  eig: (eigenvalues,eigenvectors), lu: (L,U,P) with PA=LU, qr: (Q,R), svd: (U,S,V) with A=USV*\n\
 - part({{mat}},col,row)\n\
 - abs, norm\n\
 - len, wid\n\
 - rotate(theta) produces a rotational matrix\n\
-- other functions are applied like sqrt{{{{2,4}},{{5,6}}}}={{{{sqrt(2),sqrt(4)}},{{sqrt(5),sqrt(6)}}}}\n\n\
+- A^p for any p, expm/exp, logm/ln, sqrtm/sqrt are matrix functions on square matrices\n\
+  (logm, sqrtm and non integer powers of a matrix that is not diagonalizable need its\n\
+  eigenvalues off zero and the negative real axis)\n\
+- .^ raises each entry instead, {{{{1,2}},{{3,4}}}}.^2={{{{1,4}},{{9,16}}}}\n\
+- other functions are applied like sin{{{{2,4}},{{5,6}}}}={{{{sin(2),sin(4)}},{{sin(5),sin(6)}}}}\n\n\
 Constants:\n\
 - c: speed of light, 299792458 m/s\n\
 - g: gravity, 9.80665 m/s^2\n\
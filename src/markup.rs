@@ -0,0 +1,1111 @@
+use crate::{
+    complex::NumStr, fraction::fraction, options::Format, parse::functions, print::get_output,
+    Options,
+};
+use rug::{Complex, Float};
+// what an input and its answer are laid out as before being written as latex or mathml
+enum Node
+{
+    Num(String),
+    // a variable or constant, π and the like are kept as their symbol
+    Ident(String),
+    // written as its unicode symbol, · for *, − for - and so on
+    Op(&'static str),
+    Row(Vec<Node>),
+    Frac(Box<Node>, Box<Node>),
+    Sup(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Root(Box<Node>, Option<Box<Node>>),
+    Fence(&'static str, Box<Node>, &'static str),
+    // name, subscript like log's base and the argument
+    Func(String, Option<Box<Node>>, Box<Node>),
+    // ∑ or ∏ with what is under and over it and what it is over
+    Big(&'static str, Box<Node>, Box<Node>, Box<Node>),
+    Table(Vec<Vec<Node>>),
+    Text(String),
+}
+use Node::*;
+// the input rendered next to its answer, or whichever of the two there is
+pub fn markup(
+    input: &str,
+    value: Option<&NumStr>,
+    options: Options,
+    vars: &[[String; 2]],
+) -> Option<String>
+{
+    let node = match (parse(input, vars), value)
+    {
+        (Some(l), Some(r)) => Row(vec![l, Op("="), answer(r, options)]),
+        (Some(l), None) => l,
+        (None, Some(r)) => answer(r, options),
+        (None, None) => return None,
+    };
+    Some(match options.format
+    {
+        Format::MathML => format!(
+            "<math xmlns=\"http://www.w3.org/1998/Math/MathML\">{}</math>",
+            mathml(&node)
+        ),
+        _ => latex(&node),
+    })
+}
+fn answer(num: &NumStr, options: Options) -> Node
+{
+    match num
+    {
+        NumStr::Num(n) => complex(n, options),
+        NumStr::Vector(v) => Table(vec![v.iter().map(|n| complex(n, options)).collect()]),
+        NumStr::Matrix(m) => Table(
+            m.iter()
+                .map(|v| v.iter().map(|n| complex(n, options)).collect())
+                .collect(),
+        ),
+        NumStr::Tuple(t) => Fence(
+            "(",
+            Box::new(list(t.iter().map(|n| answer(n, options)).collect())),
+            ")",
+        ),
+        NumStr::Str(s) => Text(s.clone()),
+    }
+}
+// a+bi, with each part a fraction when there is one for it
+fn complex(n: &Complex, options: Options) -> Node
+{
+    let (re, im) = (n.real(), n.imag());
+    if im.is_zero()
+    {
+        return part(re, options);
+    }
+    let i = if im.clone().abs() == 1
+    {
+        Ident("i".to_string())
+    }
+    else
+    {
+        Row(vec![
+            part(&im.clone().abs(), options),
+            Ident("i".to_string()),
+        ])
+    };
+    let sign = if im.is_sign_negative() { "−" } else { "+" };
+    if re.is_zero()
+    {
+        if im.is_sign_negative()
+        {
+            Row(vec![Op("−"), i])
+        }
+        else
+        {
+            i
+        }
+    }
+    else
+    {
+        Row(vec![part(re, options), Op(sign), i])
+    }
+}
+fn part(n: &Float, options: Options) -> Node
+{
+    if n.is_sign_negative() && !n.is_zero()
+    {
+        return Row(vec![Op("−"), part(&n.clone().abs(), options)]);
+    }
+    if options.frac
+    {
+        // fraction writes kalc's own syntax, 3π/4 or sqrt(2)/2, so it is read back like an input
+        if let Some(node) = parse(&fraction(n.clone(), options), &[])
+        {
+            return node;
+        }
+    }
+    let s = get_output(
+        &Options {
+            color: false,
+            comma: false,
+            ..options
+        },
+        &Complex::with_val(n.prec(), n),
+    )
+    .0;
+    match s.split_once('E')
+    {
+        Some((m, e)) => Row(vec![
+            Num(m.to_string()),
+            Op("×"),
+            Sup(
+                Box::new(Num("10".to_string())),
+                Box::new(Num(e.to_string())),
+            ),
+        ]),
+        None => Num(s),
+    }
+}
+fn list(items: Vec<Node>) -> Node
+{
+    let mut row = Vec::new();
+    for (i, n) in items.into_iter().enumerate()
+    {
+        if i != 0
+        {
+            row.push(Op(","));
+        }
+        row.push(n);
+    }
+    Row(row)
+}
+// the brackets around a fraction's parts, an exponent or a root's argument are already implied
+fn bare(node: Node) -> Node
+{
+    match node
+    {
+        Fence("(", n, ")") => *n,
+        n => n,
+    }
+}
+fn latex(node: &Node) -> String
+{
+    match node
+    {
+        Num(s) => s.clone(),
+        Ident(s) => match s.as_str()
+        {
+            "π" => "\\pi".to_string(),
+            "τ" => "\\tau".to_string(),
+            "φ" => "\\phi".to_string(),
+            "∞" => "\\infty".to_string(),
+            s if s.chars().count() == 1 => s.to_string(),
+            s => format!("\\mathrm{{{}}}", s.replace('_', "\\_")),
+        },
+        Op(s) => match *s
+        {
+            "·" => "\\cdot ",
+            "×" => "\\times ",
+            "−" => "-",
+            "≤" => "\\le ",
+            "≥" => "\\ge ",
+            "≠" => "\\ne ",
+            "∧" => "\\land ",
+            "∨" => "\\lor ",
+            "≪" => "\\ll ",
+            "≫" => "\\gg ",
+            "↦" => "\\mapsto ",
+            "mod" => "\\bmod ",
+            s => s,
+        }
+        .to_string(),
+        Row(v) => v.iter().map(latex).collect(),
+        Frac(a, b) => format!("\\frac{{{}}}{{{}}}", latex(a), latex(b)),
+        Sup(a, b) => format!("{{{}}}^{{{}}}", latex(a), latex(b)),
+        Sub(a, b) => format!("{{{}}}_{{{}}}", latex(a), latex(b)),
+        Root(a, None) => format!("\\sqrt{{{}}}", latex(a)),
+        Root(a, Some(n)) => format!("\\sqrt[{}]{{{}}}", latex(n), latex(a)),
+        Fence(l, a, r) => format!(
+            "\\left{}{}\\right{}",
+            if *l == "{" { "\\{" } else { l },
+            latex(a),
+            if *r == "}" { "\\}" } else { r }
+        ),
+        Func(name, sub, a) => format!(
+            "{}{}{}",
+            match name.as_str()
+            {
+                "sin" | "cos" | "tan" | "sec" | "csc" | "cot" | "sinh" | "cosh" | "tanh"
+                | "coth" | "arcsin" | "arccos" | "arctan" | "ln" | "log" | "exp" | "det"
+                | "max" | "min" | "gcd" | "arg" => format!("\\{}", name),
+                _ => format!("\\operatorname{{{}}}", name),
+            },
+            sub.as_ref()
+                .map_or(String::new(), |s| format!("_{{{}}}", latex(s))),
+            latex(a)
+        ),
+        Big(op, under, over, a) => format!(
+            "{}_{{{}}}^{{{}}}{}",
+            if *op == "∑" { "\\sum" } else { "\\prod" },
+            latex(under),
+            latex(over),
+            latex(a)
+        ),
+        Table(rows) => format!(
+            "\\begin{{pmatrix}}{}\\end{{pmatrix}}",
+            rows.iter()
+                .map(|r| r.iter().map(latex).collect::<Vec<String>>().join(" & "))
+                .collect::<Vec<String>>()
+                .join(" \\\\ ")
+        ),
+        Text(s) => format!("\\text{{{}}}", s),
+    }
+}
+fn mathml(node: &Node) -> String
+{
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    match node
+    {
+        Num(s) => format!("<mn>{}</mn>", s),
+        Ident(s) => format!("<mi>{}</mi>", escape(s)),
+        Op(s) => format!("<mo>{}</mo>", escape(s)),
+        Row(v) => format!("<mrow>{}</mrow>", v.iter().map(mathml).collect::<String>()),
+        Frac(a, b) => format!("<mfrac>{}{}</mfrac>", mathml(a), mathml(b)),
+        Sup(a, b) => format!("<msup>{}{}</msup>", mathml(a), mathml(b)),
+        Sub(a, b) => format!("<msub>{}{}</msub>", mathml(a), mathml(b)),
+        Root(a, None) => format!("<msqrt>{}</msqrt>", mathml(a)),
+        Root(a, Some(n)) => format!("<mroot>{}{}</mroot>", mathml(a), mathml(n)),
+        Fence(l, a, r) => format!("<mrow><mo>{}</mo>{}<mo>{}</mo></mrow>", l, mathml(a), r),
+        Func(name, sub, a) =>
+        {
+            let name = format!("<mi>{}</mi>", name);
+            format!(
+                "<mrow>{}<mo>&#x2061;</mo>{}</mrow>",
+                match sub
+                {
+                    Some(s) => format!("<msub>{}{}</msub>", name, mathml(s)),
+                    None => name,
+                },
+                mathml(a)
+            )
+        }
+        Big(op, under, over, a) => format!(
+            "<mrow><munderover><mo>{}</mo>{}{}</munderover>{}</mrow>",
+            op,
+            mathml(under),
+            mathml(over),
+            mathml(a)
+        ),
+        Table(rows) => format!(
+            "<mrow><mo>(</mo><mtable>{}</mtable><mo>)</mo></mrow>",
+            rows.iter()
+                .map(|r| format!(
+                    "<mtr>{}</mtr>",
+                    r.iter()
+                        .map(|n| format!("<mtd>{}</mtd>", mathml(n)))
+                        .collect::<String>()
+                ))
+                .collect::<String>()
+        ),
+        Text(s) => format!("<mtext>{}</mtext>", escape(s)),
+    }
+}
+// reads an input the way get_func would, but keeps its shape instead of evaluating it
+fn parse(input: &str, vars: &[[String; 2]]) -> Option<Node>
+{
+    let mut names: Vec<String> = functions().iter().map(|f| f.to_string()).collect();
+    names.extend(
+        ["pi", "tau", "phi", "inf"]
+            .iter()
+            .map(|n| n.to_string())
+            .chain(
+                vars.iter()
+                    .map(|v| v[0].split('(').next().unwrap().to_string()),
+            ),
+    );
+    let mut parser = Parser {
+        chars: input.chars().filter(|c| !c.is_whitespace()).collect(),
+        i: 0,
+        names,
+    };
+    if parser.chars.is_empty()
+    {
+        return None;
+    }
+    let node = parser.relation()?;
+    if parser.i == parser.chars.len()
+    {
+        Some(node)
+    }
+    else
+    {
+        None
+    }
+}
+struct Parser
+{
+    chars: Vec<char>,
+    i: usize,
+    names: Vec<String>,
+}
+impl Parser
+{
+    fn peek(&self) -> Option<char>
+    {
+        self.chars.get(self.i).copied()
+    }
+    fn eat(&mut self, s: &str) -> bool
+    {
+        let n = s.chars().count();
+        if self.chars.len() >= self.i + n
+            && self.chars[self.i..self.i + n].iter().copied().eq(s.chars())
+        {
+            self.i += n;
+            true
+        }
+        else
+        {
+            false
+        }
+    }
+    // comparisons, logic and lambdas, all left to right at the loosest level
+    fn relation(&mut self) -> Option<Node>
+    {
+        let mut row = vec![self.sum()?];
+        'outer: loop
+        {
+            for (s, op) in [
+                ("->", "↦"),
+                ("&&", "∧"),
+                ("||", "∨"),
+                ("==", "="),
+                ("!=", "≠"),
+                ("<=", "≤"),
+                (">=", "≥"),
+                ("<<", "≪"),
+                (">>", "≫"),
+                ("<", "<"),
+                (">", ">"),
+                ("=", "="),
+            ]
+            {
+                if self.eat(s)
+                {
+                    row.push(Op(op));
+                    row.push(self.sum()?);
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+        Some(
+            if row.len() == 1
+            {
+                row.remove(0)
+            }
+            else
+            {
+                Row(row)
+            },
+        )
+    }
+    fn sum(&mut self) -> Option<Node>
+    {
+        let mut row = vec![self.term()?];
+        loop
+        {
+            if self.peek() == Some('+')
+            {
+                self.i += 1;
+                row.push(Op("+"));
+            }
+            else if self.peek() == Some('-') && self.chars.get(self.i + 1) != Some(&'>')
+            {
+                self.i += 1;
+                row.push(Op("−"));
+            }
+            else
+            {
+                break;
+            }
+            row.push(self.term()?);
+        }
+        Some(
+            if row.len() == 1
+            {
+                row.remove(0)
+            }
+            else
+            {
+                Row(row)
+            },
+        )
+    }
+    // a/b takes everything multiplied before it as the numerator, so 2x/3 is 2x over 3
+    fn term(&mut self) -> Option<Node>
+    {
+        let mut row = vec![self.unary()?];
+        loop
+        {
+            match self.peek()
+            {
+                Some('*') =>
+                {
+                    self.i += 1;
+                    row.push(Op("·"));
+                    row.push(self.unary()?);
+                }
+                Some('%') =>
+                {
+                    self.i += 1;
+                    row.push(Op("mod"));
+                    row.push(self.unary()?);
+                }
+                Some('/') =>
+                {
+                    self.i += 1;
+                    let num = if row.len() == 1
+                    {
+                        row.remove(0)
+                    }
+                    else
+                    {
+                        Row(row)
+                    };
+                    row = vec![Frac(Box::new(bare(num)), Box::new(bare(self.unary()?)))];
+                }
+                // multiplication without a sign
+                Some(c)
+                    if c.is_alphanumeric()
+                        || matches!(c, '(' | '{' | '$' | '_' | '√' | '∛' | '.') =>
+                {
+                    // 2e3 is 2*e*3 here, without the dot it would read as a power of ten
+                    if c.is_ascii_digit() || c == '.'
+                    {
+                        row.push(Op("·"));
+                    }
+                    row.push(self.unary()?)
+                }
+                _ => break,
+            }
+        }
+        Some(
+            if row.len() == 1
+            {
+                row.remove(0)
+            }
+            else
+            {
+                Row(row)
+            },
+        )
+    }
+    fn unary(&mut self) -> Option<Node>
+    {
+        if self.eat("-")
+        {
+            Some(Row(vec![Op("−"), self.unary()?]))
+        }
+        else if self.eat("+")
+        {
+            self.unary()
+        }
+        else if self.peek() == Some('!') && self.chars.get(self.i + 1) != Some(&'=')
+        {
+            self.i += 1;
+            Some(Row(vec![Op("!"), self.unary()?]))
+        }
+        else
+        {
+            self.power()
+        }
+    }
+    fn power(&mut self) -> Option<Node>
+    {
+        let base = self.postfix()?;
+        if self.eat("^") || self.eat(".^")
+        {
+            Some(Sup(Box::new(base), Box::new(bare(self.unary()?))))
+        }
+        else
+        {
+            Some(base)
+        }
+    }
+    fn postfix(&mut self) -> Option<Node>
+    {
+        let mut node = self.atom()?;
+        while self.peek() == Some('!') && self.chars.get(self.i + 1) != Some(&'=')
+        {
+            self.i += 1;
+            node = Row(vec![node, Op("!")]);
+        }
+        Some(node)
+    }
+    // the comma separated arguments up to the closing bracket
+    fn args(&mut self, close: char) -> Option<Vec<Node>>
+    {
+        let mut args = Vec::new();
+        if self.peek() == Some(close)
+        {
+            self.i += 1;
+            return Some(args);
+        }
+        loop
+        {
+            args.push(self.relation()?);
+            match self.peek()
+            {
+                Some(',') => self.i += 1,
+                Some(c) if c == close =>
+                {
+                    self.i += 1;
+                    return Some(args);
+                }
+                // a bracket left open closes at the end like it does for get_func
+                None => return Some(args),
+                _ => return None,
+            }
+        }
+    }
+    fn atom(&mut self) -> Option<Node>
+    {
+        let c = self.peek()?;
+        if c.is_ascii_digit() || c == '.'
+        {
+            let start = self.i;
+            while self
+                .peek()
+                .is_some_and(|c| c.is_ascii_digit() || (c == '.' && !self.ahead('^')))
+            {
+                self.i += 1;
+            }
+            return Some(Num(self.chars[start..self.i].iter().collect()));
+        }
+        self.i += 1;
+        match c
+        {
+            '(' =>
+            {
+                let args = self.args(')')?;
+                Some(Fence("(", Box::new(list(args)), ")"))
+            }
+            '{' =>
+            {
+                let args = self.args('}')?;
+                // a list of lists is a matrix, anything else is a row
+                if !args.is_empty() && args.iter().all(|a| matches!(a, Table(r) if r.len() == 1))
+                {
+                    Some(Table(
+                        args.into_iter()
+                            .filter_map(|a| match a
+                            {
+                                Table(mut r) => r.pop(),
+                                _ => None,
+                            })
+                            .collect(),
+                    ))
+                }
+                else
+                {
+                    Some(Table(vec![args]))
+                }
+            }
+            '|' =>
+            {
+                let n = self.sum()?;
+                self.eat("|");
+                Some(Fence("|", Box::new(n), "|"))
+            }
+            '$' =>
+            {
+                let n = self.atom()?;
+                Some(Sub(Box::new(Ident("ans".to_string())), Box::new(n)))
+            }
+            '_' => Some(Ident("ans".to_string())),
+            'π' | 'τ' => Some(Ident(c.to_string())),
+            '√' => Some(Root(Box::new(bare(self.power()?)), None)),
+            '∛' => Some(Root(
+                Box::new(bare(self.power()?)),
+                Some(Box::new(Num("3".to_string()))),
+            )),
+            c if c.is_ascii_alphabetic() =>
+            {
+                self.i -= 1;
+                self.word()
+            }
+            _ => None,
+        }
+    }
+    fn ahead(&self, c: char) -> bool
+    {
+        self.chars.get(self.i + 1) == Some(&c)
+    }
+    // the longest known name the letters start with, or a single letter
+    fn word(&mut self) -> Option<Node>
+    {
+        let run: String = self.chars[self.i..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+            .collect();
+        let word = self
+            .names
+            .iter()
+            .filter(|n| run.starts_with(n.as_str()))
+            .max_by_key(|n| n.len())
+            .cloned()
+            .unwrap_or(run[..1].to_string());
+        self.i += word.len();
+        if !functions().contains(word.as_str())
+        {
+            return Some(Ident(
+                match word.as_str()
+                {
+                    "pi" => "π",
+                    "tau" => "τ",
+                    "phi" => "φ",
+                    "inf" => "∞",
+                    w => w,
+                }
+                .to_string(),
+            ));
+        }
+        let mut args = if self.eat("(")
+        {
+            self.args(')')?
+        }
+        else
+        {
+            vec![self.power()?]
+        };
+        let n = args.len();
+        let fenced = |args: Vec<Node>| Box::new(Fence("(", Box::new(list(args)), ")"));
+        Some(match (word.as_str(), n)
+        {
+            ("sqrt", 1) => Root(Box::new(args.remove(0)), None),
+            ("cbrt", 1) => Root(
+                Box::new(args.remove(0)),
+                Some(Box::new(Num("3".to_string()))),
+            ),
+            ("root", 2) =>
+            {
+                let a = args.remove(0);
+                Root(Box::new(a), Some(Box::new(args.remove(0))))
+            }
+            ("abs", 1) => Fence("|", Box::new(args.remove(0)), "|"),
+            ("exp", 1) => Sup(Box::new(Ident("e".to_string())), Box::new(args.remove(0))),
+            ("square", 1) => Sup(
+                Box::new(fence(args.remove(0))),
+                Box::new(Num("2".to_string())),
+            ),
+            ("cube", 1) => Sup(
+                Box::new(fence(args.remove(0))),
+                Box::new(Num("3".to_string())),
+            ),
+            ("fact", 1) => Row(vec![fence(args.remove(0)), Op("!")]),
+            ("log", 2) =>
+            {
+                let base = args.remove(0);
+                Func("log".to_string(), Some(Box::new(base)), fenced(args))
+            }
+            ("sum" | "summation" | "product" | "prod", 4) =>
+            {
+                let over = args.pop()?;
+                let start = args.pop()?;
+                let var = args.pop()?;
+                Big(
+                    if word.starts_with('s') { "∑" } else { "∏" },
+                    Box::new(Row(vec![var, Op("="), start])),
+                    Box::new(over),
+                    Box::new(fence(args.remove(0))),
+                )
+            }
+            ("asin" | "acos" | "atan", 1) => Func(format!("arc{}", &word[1..]), None, fenced(args)),
+            _ => Func(word, None, fenced(args)),
+        })
+    }
+}
+// brackets for what isn't a single symbol, so (x+1)^2 keeps them and x^2 doesn't get any
+fn fence(node: Node) -> Node
+{
+    match node
+    {
+        Row(_) | Frac(..) => Fence("(", Box::new(node), ")"),
+        n => n,
+    }
+}
+// latex pasted or given as an argument is turned into kalc's own syntax first, only an input
+// with a \ in it is read this way since {} are vectors otherwise
+pub fn from_latex(input: &str) -> String
+{
+    if !input.contains('\\')
+    {
+        return input.to_string();
+    }
+    let mut reader = Latex {
+        chars: input.chars().collect(),
+        i: 0,
+    };
+    reader.text(&[], false)
+}
+struct Latex
+{
+    chars: Vec<char>,
+    i: usize,
+}
+impl Latex
+{
+    fn at(&self, s: &str) -> bool
+    {
+        let n = s.chars().count();
+        self.chars.len() >= self.i + n
+            && self.chars[self.i..self.i + n].iter().copied().eq(s.chars())
+    }
+    fn eat(&mut self, s: &str) -> bool
+    {
+        let at = self.at(s);
+        if at
+        {
+            self.i += s.chars().count();
+        }
+        at
+    }
+    fn skip_space(&mut self)
+    {
+        while self.chars.get(self.i).is_some_and(|c| c.is_whitespace())
+        {
+            self.i += 1;
+        }
+    }
+    // converts up to one of end outside of any brackets, which is left unread, a term stops at
+    // the first + or - so the body of a sum is only what it is taken over
+    fn text(&mut self, end: &[&str], term: bool) -> String
+    {
+        let mut out = String::new();
+        let mut depth = 0;
+        let mut command = false;
+        while self.i < self.chars.len()
+        {
+            let c = self.chars[self.i];
+            if depth == 0
+                && (end.iter().any(|e| self.at(e))
+                    || (c == ')' && !end.is_empty())
+                    || (term && !out.is_empty() && (c == '+' || c == '-')))
+            {
+                break;
+            }
+            match c
+            {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => (),
+            }
+            let is_command = c == '\\';
+            let token = self.token();
+            // \pi r is pi*r, not a name pir
+            if (command || is_command)
+                && out.ends_with(|c: char| c.is_ascii_alphabetic())
+                && token.starts_with(|c: char| c.is_ascii_alphanumeric())
+            {
+                out.push('*');
+            }
+            if !token.is_empty()
+            {
+                command = is_command;
+            }
+            out.push_str(&token);
+        }
+        out
+    }
+    // a {} group or a single token, what \frac, ^ and the like take as their argument
+    fn arg(&mut self) -> String
+    {
+        self.skip_space();
+        if self.eat("{")
+        {
+            let s = self.text(&["}"], false);
+            self.eat("}");
+            s
+        }
+        else
+        {
+            self.token()
+        }
+    }
+    // what a function is applied to, brackets included so sin(x)^2 stays one thing
+    fn operand(&mut self) -> String
+    {
+        self.skip_space();
+        if self.eat("(")
+        {
+            let s = self.text(&[")"], false);
+            self.eat(")");
+            format!("({})", s)
+        }
+        else if self.at("\\left")
+        {
+            self.token()
+        }
+        else
+        {
+            format!("({})", self.arg())
+        }
+    }
+    // nothing is left at the end of a cut off input like \frac{1}
+    fn token(&mut self) -> String
+    {
+        let Some(&c) = self.chars.get(self.i)
+        else
+        {
+            return String::new();
+        };
+        self.i += 1;
+        match c
+        {
+            '\\' => self.command(),
+            '{' =>
+            {
+                let s = self.text(&["}"], false);
+                self.eat("}");
+                format!("({})", s)
+            }
+            '_' =>
+            {
+                // x_{12} is the name x_12
+                let s = self.arg();
+                format!("_{}", s.trim_start_matches('(').trim_end_matches(')'))
+            }
+            '^' => format!("^{}", self.bracketed()),
+            '}' | '&' => String::new(),
+            c if c.is_whitespace() => String::new(),
+            c => c.to_string(),
+        }
+    }
+    fn bracketed(&mut self) -> String
+    {
+        let s = self.arg();
+        if s.chars().count() == 1 || (s.starts_with('(') && s.ends_with(')'))
+        {
+            s
+        }
+        else
+        {
+            format!("({})", s)
+        }
+    }
+    fn command(&mut self) -> String
+    {
+        let name: String = if self
+            .chars
+            .get(self.i)
+            .is_some_and(|c| c.is_ascii_alphabetic())
+        {
+            self.chars[self.i..]
+                .iter()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect()
+        }
+        else
+        {
+            self.chars
+                .get(self.i)
+                .map_or(String::new(), |c| c.to_string())
+        };
+        self.i += name.chars().count();
+        match name.as_str()
+        {
+            "frac" | "dfrac" | "tfrac" =>
+            {
+                let a = self.arg();
+                let b = self.arg();
+                format!("(({})/({}))", a, b)
+            }
+            "sqrt" =>
+            {
+                if self.eat("[")
+                {
+                    let n = self.text(&["]"], false);
+                    self.eat("]");
+                    format!("root({},{})", self.arg(), n)
+                }
+                else
+                {
+                    format!("sqrt({})", self.arg())
+                }
+            }
+            "binom" =>
+            {
+                let a = self.arg();
+                format!("binomial({},{})", a, self.arg())
+            }
+            "left" | "right" =>
+            {
+                self.skip_space();
+                if name == "left"
+                {
+                    let open = self.token();
+                    let s = self.text(&["\\right"], false);
+                    self.eat("\\right");
+                    self.skip_space();
+                    let close = self.token();
+                    match open.as_str()
+                    {
+                        "|" => format!("|{}|", s),
+                        "floor(" | "ceil(" => format!("{}{}{}", open, s, close),
+                        _ => format!("({})", s),
+                    }
+                }
+                else
+                {
+                    self.token();
+                    String::new()
+                }
+            }
+            "cdot" | "times" | "ast" => "*".to_string(),
+            "div" => "/".to_string(),
+            "le" | "leq" => "<=".to_string(),
+            "ge" | "geq" => ">=".to_string(),
+            "ne" | "neq" => "!=".to_string(),
+            "lt" => "<".to_string(),
+            "gt" => ">".to_string(),
+            "lfloor" => "floor(".to_string(),
+            "lceil" => "ceil(".to_string(),
+            "rfloor" | "rceil" => ")".to_string(),
+            "{" => "(".to_string(),
+            "}" => ")".to_string(),
+            "|" => "|".to_string(),
+            "mathrm" | "text" | "operatorname" | "mathit" | "mathbf" =>
+            {
+                let s = self.arg();
+                s.chars().filter(|c| !c.is_whitespace()).collect()
+            }
+            "begin" =>
+            {
+                let env = self.arg();
+                self.matrix(&env)
+            }
+            "end" =>
+            {
+                self.arg();
+                String::new()
+            }
+            "sum" | "prod" =>
+            {
+                let (mut under, mut over) = (String::new(), String::new());
+                loop
+                {
+                    self.skip_space();
+                    if self.eat("_")
+                    {
+                        under = self.arg();
+                    }
+                    else if self.eat("^")
+                    {
+                        over = self.arg();
+                    }
+                    else
+                    {
+                        break;
+                    }
+                }
+                let body = self.text(&["\\right", "&", "\\\\", "\\end", "}"], true);
+                match under.split_once('=')
+                {
+                    Some((var, start)) => format!("{}({},{},{},{})", name, body, var, start, over),
+                    None => format!("{}({})", name, body),
+                }
+            }
+            _ if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) =>
+            {
+                // spacing, a \\ outside of a matrix and the like
+                String::new()
+            }
+            "quad" | "qquad" => String::new(),
+            _ =>
+            {
+                let name = match name.as_str()
+                {
+                    "arcsin" | "arccos" | "arctan" => name.replacen("arc", "a", 1),
+                    _ => name,
+                };
+                if !functions().contains(name.as_str())
+                {
+                    return name;
+                }
+                self.skip_space();
+                let base = if self.eat("_")
+                {
+                    Some(self.arg())
+                }
+                else
+                {
+                    None
+                };
+                // \sin^{2}(x) is sin(x)^2
+                let power = if self.eat("^")
+                {
+                    Some(self.bracketed())
+                }
+                else
+                {
+                    None
+                };
+                // but \sin^{-1}(x) is the inverse function asin(x)
+                let inverse = matches!(power.as_deref(), Some("-1") | Some("(-1)"))
+                    && matches!(
+                        name.trim_end_matches('h'),
+                        "sin" | "cos" | "tan" | "sec" | "csc" | "cot"
+                    );
+                let (name, power) = if inverse
+                {
+                    (format!("a{}", name), None)
+                }
+                else
+                {
+                    (name, power)
+                };
+                let operand = self.operand();
+                let call = match base
+                {
+                    Some(b) => format!(
+                        "{}({},{})",
+                        name,
+                        b,
+                        operand.trim_start_matches('(').trim_end_matches(')')
+                    ),
+                    None => format!("{}{}", name, operand),
+                };
+                match power
+                {
+                    Some(p) => format!("{}^{}", call, p),
+                    None => call,
+                }
+            }
+        }
+    }
+    // a matrix environment's cells, a single row or column is a vector
+    fn matrix(&mut self, env: &str) -> String
+    {
+        let mut rows = vec![Vec::new()];
+        loop
+        {
+            let cell = self.text(&["&", "\\\\", "\\end"], false);
+            rows.last_mut().unwrap().push(cell.trim().to_string());
+            if self.eat("&")
+            {
+                continue;
+            }
+            if self.eat("\\\\")
+            {
+                rows.push(Vec::new());
+                continue;
+            }
+            if self.eat("\\end")
+            {
+                self.arg();
+            }
+            break;
+        }
+        rows.retain(|r| r.iter().any(|c| !c.is_empty()));
+        let body = if rows.len() == 1 || rows.iter().all(|r| r.len() == 1)
+        {
+            format!("{{{}}}", rows.concat().join(","))
+        }
+        else
+        {
+            format!(
+                "{{{}}}",
+                rows.iter()
+                    .map(|r| format!("{{{}}}", r.join(",")))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        };
+        if env == "vmatrix"
+        {
+            format!("det({})", body)
+        }
+        else
+        {
+            body
+        }
+    }
+}
@@ -1,11 +1,141 @@
 use crate::complex::{
     NumStr,
-    NumStr::{Matrix, Num, Str, Vector},
+    NumStr::{Matrix, Num, Str, Tuple, Vector},
 };
-use crate::options::AngleType;
-use rug::{float::Constant::Pi, ops::Pow, Complex, Float};
-use std::ops::{Shl, Shr};
-pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, ()>
+use crate::{
+    linalg::{
+        cholesky, eig, eigvals, expm, logm, lu, nullspace, qr, rank, rref, sqrtm, svd, tolerance,
+    },
+    options::AngleType,
+    parse::{is_func, Function},
+    poly::{polyder, polydiv, polyfit, polyint, polymul, polyroots, polyval},
+};
+use rug::{
+    float::{Constant::Pi, Special},
+    ops::Pow,
+    Complex, Float,
+};
+use std::{
+    cell::Cell,
+    ops::{Shl, Shr},
+    thread,
+};
+pub fn do_math(
+    func: Vec<NumStr>,
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+) -> Result<NumStr, ()>
+{
+    LIMIT.with(|l| l.set(false));
+    evaluate(func, deg, prec, funcs, 0)
+}
+thread_local! {
+    static LIMIT: Cell<bool> = const { Cell::new(false) };
+}
+// whether the last do_math on this thread gave up for going too many calls deep
+pub fn recursion_limit() -> bool
+{
+    LIMIT.with(Cell::get)
+}
+// swaps _, $n and ans(n) for the answers they name, the first is 1 and ans(-1) is the last
+pub fn recall(
+    mut func: Vec<NumStr>,
+    answers: &[(String, NumStr)],
+    prec: u32,
+) -> Result<Vec<NumStr>, ()>
+{
+    // an answer that isn't there is a message rather than an invalid input, so a run goes on
+    let pick = |n: i64| {
+        let i = if n < 0
+        {
+            answers.len() as i64 + n
+        }
+        else
+        {
+            n - 1
+        };
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| answers.get(i))
+            .map(|(_, v)| v.clone())
+            .ok_or(n)
+    };
+    let missing = |n: i64| Ok(vec![Str(format!("no answer {}", n))]);
+    let mut i = 0;
+    while i < func.len()
+    {
+        if let Str(s) = func[i].clone()
+        {
+            if s == "_"
+            {
+                match pick(-1)
+                {
+                    Ok(v) => func[i] = v,
+                    Err(_) => return Ok(vec![Str("no answers yet".to_string())]),
+                }
+            }
+            else if let Some(n) = s.strip_prefix('$')
+            {
+                match pick(n.parse().map_err(|_| ())?)
+                {
+                    Ok(v) => func[i] = v,
+                    Err(n) => return missing(n),
+                }
+            }
+            else if s == "ans" && func.get(i + 1).is_some_and(|n| n.str_is("("))
+            {
+                let mut count = 0;
+                let mut j = i + 1;
+                while j < func.len()
+                {
+                    if func[j].str_is("(")
+                    {
+                        count += 1;
+                    }
+                    else if func[j].str_is(")")
+                    {
+                        count -= 1;
+                        if count == 0
+                        {
+                            break;
+                        }
+                    }
+                    j += 1;
+                }
+                let n = do_math(
+                    recall(func[i + 2..j.min(func.len())].to_vec(), answers, prec)?,
+                    AngleType::Radians,
+                    prec,
+                    &[],
+                )?
+                .num()?;
+                if n.imag() != &0.0 || n.real().clone().fract() != 0.0
+                {
+                    return Err(());
+                }
+                match pick(n.real().to_f64() as i64)
+                {
+                    Ok(v) =>
+                    {
+                        func.splice(i..(j + 1).min(func.len()), [v]);
+                    }
+                    Err(n) => return missing(n),
+                }
+            }
+        }
+        i += 1;
+    }
+    Ok(func)
+}
+// depth counts the user function calls being evaluated so endless recursion gives up
+fn evaluate(
+    func: Vec<NumStr>,
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+    depth: usize,
+) -> Result<NumStr, ()>
 {
     if func.len() == 1
     {
@@ -60,7 +190,7 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                     {
                         if s == "," && count == 0
                         {
-                            let z = do_math(v[single..f].to_vec(), deg, prec)?;
+                            let z = evaluate(v[single..f].to_vec(), deg, prec, funcs, depth)?;
                             match z
                             {
                                 Num(n) => vec.push(n),
@@ -73,11 +203,11 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                             }
                             single = f + 1;
                         }
-                        else if s == "{"
+                        else if s == "{" || s == "("
                         {
                             count += 1;
                         }
-                        else if s == "}"
+                        else if s == "}" || s == ")"
                         {
                             count -= 1;
                         }
@@ -85,7 +215,7 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                 }
                 if single != v.len()
                 {
-                    let z = do_math(v[single..].to_vec(), deg, prec)?;
+                    let z = evaluate(v[single..].to_vec(), deg, prec, funcs, depth)?;
                     match z
                     {
                         Num(n) => vec.push(n),
@@ -129,7 +259,9 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                     }
                     j += 1;
                 }
-                if i + 1 == j - 1
+                // only a user function can be called with nothing, k() takes all its defaults
+                let user = i != 0 && matches!(&function[i - 1], Str(k) if k.starts_with('@'));
+                if i + 1 == j - 1 && !user
                 {
                     return Err(());
                 }
@@ -138,7 +270,70 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                 {
                     if let Str(k) = &function[i - 1]
                     {
-                        if k == "log"
+                        if let Some(name) = k.strip_prefix('@')
+                        {
+                            let args = if v.is_empty()
+                            {
+                                Vec::new()
+                            }
+                            else
+                            {
+                                split_args(&v)
+                                    .into_iter()
+                                    .map(|a| evaluate(a, deg, prec, funcs, depth))
+                                    .collect::<Result<Vec<NumStr>, ()>>()?
+                            };
+                            function[i - 1] = call(name, args, deg, prec, funcs, depth)?;
+                            function.drain(i..j);
+                            continue 'outer;
+                        }
+                        else if k == "if" || k == "piecewise"
+                        {
+                            // only the branch that's taken is evaluated, so recursion can stop
+                            let eval = |f: &[NumStr]| evaluate(f.to_vec(), deg, prec, funcs, depth);
+                            let pieces = if k == "if"
+                            {
+                                let mut args = split_args(&v);
+                                match args.len()
+                                {
+                                    2 => (),
+                                    3 => args.insert(2, Vec::new()),
+                                    _ => return Err(()),
+                                }
+                                args
+                            }
+                            else
+                            {
+                                pieces(&v)?
+                            };
+                            let mut value = None;
+                            for p in pieces.chunks(2)
+                            {
+                                if p[0].is_empty() || !eval(&p[0])?.num()?.eq0()
+                                {
+                                    value = Some(eval(&p[1])?);
+                                    break;
+                                }
+                            }
+                            // nothing applies, which graphs leave a gap for
+                            function[i - 1] = value.unwrap_or(Num(Complex::with_val(
+                                prec,
+                                Float::with_val(prec, Special::Nan),
+                            )));
+                            function.drain(i..j);
+                            continue 'outer;
+                        }
+                        else if matches!(
+                            k.as_str(),
+                            "map" | "filter" | "reduce" | "fold" | "zipwith" | "table"
+                        )
+                        {
+                            function[i - 1] =
+                                higher_order(k, &split_args(&v), deg, prec, funcs, depth)?;
+                            function.drain(i..j);
+                            continue 'outer;
+                        }
+                        else if k == "log"
                             || k == "root"
                             || k == "atan"
                             || k == "arctan"
@@ -153,8 +348,15 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                             || k == "min"
                             || k == "proj"
                             || k == "project"
+                            || k == "polyval"
+                            || k == "polymul"
+                            || k == "polydiv"
+                            || k == "polyint"
+                            || k == "polyfit"
+                            || k == "linsolve"
                         {
                             count = 0;
+                            place.clear();
                             for (f, n) in v.iter().enumerate()
                             {
                                 if let Str(s) = n
@@ -176,13 +378,23 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                             if !place.is_empty()
                             {
                                 function.drain(i..j);
-                                function.insert(i, do_math(v[..place[0]].to_vec(), deg, prec)?);
+                                function.insert(
+                                    i,
+                                    evaluate(v[..place[0]].to_vec(), deg, prec, funcs, depth)?,
+                                );
                                 for (k, l) in place.iter().enumerate()
                                 {
                                     function.insert(i + k + 1, Str(",".to_string()));
                                     function.insert(
                                         i + k + 2,
-                                        do_math(v[l + 1..].to_vec(), deg, prec)?,
+                                        evaluate(
+                                            v[l + 1..*place.get(k + 1).unwrap_or(&v.len())]
+                                                .to_vec(),
+                                            deg,
+                                            prec,
+                                            funcs,
+                                            depth,
+                                        )?,
                                     );
                                     i += 1;
                                 }
@@ -196,7 +408,7 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                         }
                     }
                 }
-                function[i] = do_math(v, deg, prec)?;
+                function[i] = evaluate(v, deg, prec, funcs, depth)?;
                 function.drain(i + 1..j);
             }
         }
@@ -255,17 +467,29 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                             function[i] = sum(
                                 function[i + 2..place[0]].to_vec(),
                                 l,
-                                do_math(function[place[1] + 1..place[2]].to_vec(), deg, prec)?
-                                    .num()?
-                                    .real()
-                                    .to_f64() as i64,
-                                do_math(function[place[2] + 1..place[3]].to_vec(), deg, prec)?
-                                    .num()?
-                                    .real()
-                                    .to_f64() as i64,
+                                evaluate(
+                                    function[place[1] + 1..place[2]].to_vec(),
+                                    deg,
+                                    prec,
+                                    funcs,
+                                    depth,
+                                )?
+                                .num()?
+                                .real()
+                                .to_f64() as i64,
+                                evaluate(
+                                    function[place[2] + 1..place[3]].to_vec(),
+                                    deg,
+                                    prec,
+                                    funcs,
+                                    depth,
+                                )?
+                                .num()?
+                                .real()
+                                .to_f64() as i64,
                                 !(s == "sum" || s == "summation"),
-                                deg,
                                 prec,
+                                &|f| evaluate(f, deg, prec, funcs, depth),
                             )?;
                             function.drain(i + 1..=place[3]);
                         }
@@ -316,7 +540,38 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                                 return Err(());
                             }
                         }
-                        "inverse" | "inv" => Matrix(inverse(a)?),
+                        "inverse" | "inv" if a.len() == a[0].len() => match inverse(a)
+                        {
+                            Ok(m) => Matrix(m),
+                            Err(_) => Str("singular matrix".to_string()),
+                        },
+                        "linsolve" if function.len() > i + 3 && function[i + 2].str_is(",") =>
+                        {
+                            let b = function[i + 3].clone();
+                            function.drain(i + 2..i + 4);
+                            if a.len() != a[0].len()
+                            {
+                                return Err(());
+                            }
+                            match b
+                            {
+                                Vector(b) if b.len() == a.len() => match solve(a, b)
+                                {
+                                    Ok(x) => Vector(x),
+                                    Err(_) => Str("singular matrix".to_string()),
+                                },
+                                Matrix(b) if b.len() == a.len() => match (0..b[0].len())
+                                    .map(|j| {
+                                        solve(a.clone(), b.iter().map(|r| r[j].clone()).collect())
+                                    })
+                                    .collect::<Result<Vec<Vec<Complex>>, ()>>()
+                                {
+                                    Ok(x) => Matrix(transpose(x)),
+                                    Err(_) => Str("singular matrix".to_string()),
+                                },
+                                _ => return Err(()),
+                            }
+                        }
                         "transpose" | "trans" => Matrix(transpose(a)),
                         "len" | "length" => Num(Complex::with_val(prec, a.len())),
                         "wid" | "width" => Num(Complex::with_val(prec, a[0].len())),
@@ -401,6 +656,33 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                                 .map(|a| a.iter().map(|a| a.clone().abs()).collect())
                                 .collect(),
                         ),
+                        "eigvals" => Vector(eigvals(&a)?),
+                        "eig" =>
+                        {
+                            let (values, vectors) = eig(&a)?;
+                            Tuple(vec![Vector(values), Matrix(vectors)])
+                        }
+                        "lu" => Tuple(lu(&a)?.into_iter().map(Matrix).collect()),
+                        "qr" => Tuple(qr(&a).into_iter().map(Matrix).collect()),
+                        "svd" => Tuple(svd(&a)?.into_iter().map(Matrix).collect()),
+                        "cholesky" | "chol" => Matrix(cholesky(&a)?),
+                        "rank" => Num(Complex::with_val(prec, rank(&a))),
+                        "nullspace" | "null" =>
+                        {
+                            let v = nullspace(&a);
+                            if v.is_empty()
+                            {
+                                Vector(Vec::new())
+                            }
+                            else
+                            {
+                                Matrix(v)
+                            }
+                        }
+                        "rref" => Matrix(rref(&a)),
+                        "expm" | "exp" if a.len() == a[0].len() => Matrix(expm(&a)?),
+                        "logm" | "ln" if a.len() == a[0].len() => Matrix(logm(&a)?),
+                        "sqrtm" | "sqrt" if a.len() == a[0].len() => Matrix(sqrtm(&a)?),
                         _ => do_functions(
                             function[i + 1].clone(),
                             deg,
@@ -602,6 +884,80 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                                 return Err(());
                             }
                         }
+                        "polyval" =>
+                        {
+                            if function.len() > i + 3 && function[i + 2].str_is(",")
+                            {
+                                let b = function[i + 3].clone();
+                                function.drain(i + 2..i + 4);
+                                match b
+                                {
+                                    Num(x) => Num(polyval(&a, &x)),
+                                    Vector(x) => Vector(x.iter().map(|x| polyval(&a, x)).collect()),
+                                    _ => return Err(()),
+                                }
+                            }
+                            else
+                            {
+                                return Err(());
+                            }
+                        }
+                        "polymul" | "polydiv" =>
+                        {
+                            if function.len() > i + 3 && function[i + 2].str_is(",")
+                            {
+                                let b = function[i + 3].vec()?;
+                                function.drain(i + 2..i + 4);
+                                if s == "polymul"
+                                {
+                                    Vector(polymul(&a, &b)?)
+                                }
+                                else
+                                {
+                                    Matrix(polydiv(&a, &b)?)
+                                }
+                            }
+                            else
+                            {
+                                return Err(());
+                            }
+                        }
+                        "polyfit" =>
+                        {
+                            if function.len() > i + 5
+                                && function[i + 2].str_is(",")
+                                && function[i + 4].str_is(",")
+                            {
+                                let b = function[i + 3].vec()?;
+                                let n = function[i + 5].num()?;
+                                function.drain(i + 2..i + 6);
+                                if n.real() < &0.0 || !n.imag().is_zero() || !n.real().is_integer()
+                                {
+                                    return Err(());
+                                }
+                                Vector(polyfit(&a, &b, n.real().to_f64() as usize)?)
+                            }
+                            else
+                            {
+                                return Err(());
+                            }
+                        }
+                        "polyint" =>
+                        {
+                            let c = if function.len() > i + 3 && function[i + 2].str_is(",")
+                            {
+                                let c = function[i + 3].num()?;
+                                function.drain(i + 2..i + 4);
+                                c
+                            }
+                            else
+                            {
+                                Complex::new(prec)
+                            };
+                            Vector(polyint(&a, c))
+                        }
+                        "polyder" => Vector(polyder(&a)?),
+                        "polyroots" => Vector(polyroots(&a)?),
                         _ => do_functions(
                             function[i + 1].clone(),
                             deg,
@@ -613,6 +969,29 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                     };
                     function.remove(i + 1);
                 }
+                else if let Tuple(a) = function[i + 1].clone()
+                {
+                    function[i] = match s.as_str()
+                    {
+                        "len" | "length" => Num(Complex::with_val(prec, a.len())),
+                        "part" if function.len() > i + 3 && function[i + 2].str_is(",") =>
+                        {
+                            let b = function[i + 3].num()?;
+                            function.drain(i + 2..i + 4);
+                            let n = b.clone().real().to_f64() as usize;
+                            if n <= a.len() && n != 0
+                            {
+                                a[n - 1].clone()
+                            }
+                            else
+                            {
+                                return Err(());
+                            }
+                        }
+                        _ => return Err(()),
+                    };
+                    function.remove(i + 1);
+                }
                 else
                 {
                     function[i] = if s == "rotate"
@@ -638,12 +1017,19 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
         i = function.len() - 2;
         while i != 0
         {
-            if !function[i].str_is("^")
+            if function[i].str_is("^")
+            {
+                function[i] = function[i - 1].pow(&function[i + 1])?;
+            }
+            else if function[i].str_is(".^")
+            {
+                function[i] = function[i - 1].elem_pow(&function[i + 1])?;
+            }
+            else
             {
                 i -= 1;
                 continue;
             }
-            function[i] = function[i - 1].pow(&function[i + 1])?;
             function.remove(i + 1);
             function.remove(i - 1);
             i -= 1;
@@ -722,35 +1108,23 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                 }
                 "<" =>
                 {
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (function[i - 1].num()?.abs().real() < function[i + 1].num()?.abs().real())
-                            as i32,
-                    ))
+                    let (l, r) = compared(function[i - 1].num()?, function[i + 1].num()?);
+                    function[i] = Num(Complex::with_val(prec, (l < r) as i32))
                 }
                 ">" =>
                 {
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (function[i - 1].num()?.abs().real() > function[i + 1].num()?.abs().real())
-                            as i32,
-                    ))
+                    let (l, r) = compared(function[i - 1].num()?, function[i + 1].num()?);
+                    function[i] = Num(Complex::with_val(prec, (l > r) as i32))
                 }
                 ">=" =>
                 {
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (function[i - 1].num()?.abs().real() >= function[i + 1].num()?.abs().real())
-                            as i32,
-                    ))
+                    let (l, r) = compared(function[i - 1].num()?, function[i + 1].num()?);
+                    function[i] = Num(Complex::with_val(prec, (l >= r) as i32))
                 }
                 "<=" =>
                 {
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (function[i - 1].num()?.abs().real() <= function[i + 1].num()?.abs().real())
-                            as i32,
-                    ))
+                    let (l, r) = compared(function[i - 1].num()?, function[i + 1].num()?);
+                    function[i] = Num(Complex::with_val(prec, (l <= r) as i32))
                 }
                 "==" =>
                 {
@@ -817,26 +1191,14 @@ pub fn do_math(func: Vec<NumStr>, deg: AngleType, prec: u32) -> Result<NumStr, (
                 "&&" =>
                 {
                     a = function[i - 1].num()?;
-                    b = function[i - 1].num()?;
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (a.imag() == &0.0
-                            && b.imag() == &0.0
-                            && a.real() == &1.0
-                            && b.real() == &1.0) as i32,
-                    ))
+                    b = function[i + 1].num()?;
+                    function[i] = Num(Complex::with_val(prec, (!a.eq0() && !b.eq0()) as i32))
                 }
                 "||" =>
                 {
                     a = function[i - 1].num()?;
-                    b = function[i - 1].num()?;
-                    function[i] = Num(Complex::with_val(
-                        prec,
-                        (a.imag() == &0.0
-                            && b.imag() == &0.0
-                            && (a.real() == &1.0 || b.real() == &1.0))
-                            as i32,
-                    ))
+                    b = function[i + 1].num()?;
+                    function[i] = Num(Complex::with_val(prec, (!a.eq0() || !b.eq0()) as i32))
                 }
                 _ =>
                 {
@@ -1133,14 +1495,338 @@ fn subfact(a: f64) -> f64
     }
     curr
 }
+// the pieces of an argument list between its top level commas
+fn split_args(v: &[NumStr]) -> Vec<Vec<NumStr>>
+{
+    let mut args = vec![Vec::new()];
+    let mut count = 0;
+    for n in v
+    {
+        if n.str_is(",") && count == 0
+        {
+            args.push(Vec::new());
+            continue;
+        }
+        else if n.str_is("(") || n.str_is("{")
+        {
+            count += 1;
+        }
+        else if n.str_is(")") || n.str_is("}")
+        {
+            count -= 1;
+        }
+        args.last_mut().unwrap().push(n.clone());
+    }
+    args
+}
+// the conditions and values of piecewise one after the other, a last argument that isn't a
+// {cond,value} pair is the otherwise and goes with an empty condition
+fn pieces(v: &[NumStr]) -> Result<Vec<Vec<NumStr>>, ()>
+{
+    let args = split_args(v);
+    let last = args.len() - 1;
+    let mut pieces = Vec::new();
+    for (k, a) in args.into_iter().enumerate()
+    {
+        let pair = if a.first().is_some_and(|n| n.str_is("{"))
+            && a.last().is_some_and(|n| n.str_is("}"))
+        {
+            match split_args(&a[1..a.len() - 1]).as_slice()
+            {
+                [c, v] if !c.is_empty() && !v.is_empty() => Some([c.clone(), v.clone()]),
+                _ => None,
+            }
+        }
+        else
+        {
+            None
+        };
+        match pair
+        {
+            Some(pair) => pieces.extend(pair),
+            None if k == last && !a.is_empty() => pieces.extend([Vec::new(), a]),
+            None => return Err(()),
+        }
+    }
+    Ok(pieces)
+}
+// map, filter, reduce, fold and zipwith go through the entries of a vector or rows of a matrix
+// with a function, and table steps a variable through a range
+fn higher_order(
+    name: &str,
+    args: &[Vec<NumStr>],
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+    depth: usize,
+) -> Result<NumStr, ()>
+{
+    let eval = |f: &[NumStr]| evaluate(f.to_vec(), deg, prec, funcs, depth);
+    let apply = |a: Vec<NumStr>| applied(&args[0], a, deg, prec, funcs, depth);
+    let entries = |f: &[NumStr]| match eval(f)?
+    {
+        Vector(v) => Ok(v.into_iter().map(Num).collect::<Vec<NumStr>>()),
+        Matrix(m) => Ok(m.into_iter().map(Vector).collect()),
+        _ => Err(()),
+    };
+    match (name, args.len())
+    {
+        ("map", 2) => gathered(
+            entries(&args[1])?
+                .into_iter()
+                .map(|n| apply(vec![n]))
+                .collect::<Result<Vec<NumStr>, ()>>()?,
+        ),
+        ("filter", 2) =>
+        {
+            let mut kept = Vec::new();
+            for n in entries(&args[1])?
+            {
+                if !apply(vec![n.clone()])?.num()?.eq0()
+                {
+                    kept.push(n);
+                }
+            }
+            gathered(kept)
+        }
+        ("reduce", 2) =>
+        {
+            let mut entries = entries(&args[1])?.into_iter();
+            let first = entries.next().ok_or(())?;
+            entries.try_fold(first, |a, n| apply(vec![a, n]))
+        }
+        ("fold", 3) => entries(&args[2])?
+            .into_iter()
+            .try_fold(eval(&args[1])?, |a, n| apply(vec![a, n])),
+        ("zipwith", 3) => gathered(
+            entries(&args[1])?
+                .into_iter()
+                .zip(entries(&args[2])?)
+                .map(|(a, b)| apply(vec![a, b]))
+                .collect::<Result<Vec<NumStr>, ()>>()?,
+        ),
+        ("table", 4 | 5) =>
+        {
+            let [Str(var)] = args[1].as_slice()
+            else
+            {
+                return Err(());
+            };
+            let start = eval(&args[2])?.num()?;
+            let step = match args.get(4)
+            {
+                Some(s) => eval(s)?.num()?,
+                None => Complex::with_val(prec, 1),
+            };
+            if step.eq0()
+            {
+                return Err(());
+            }
+            // a step like 0.1 isn't exact, so the count is rounded a little to still reach the end
+            let count = ((eval(&args[3])?.num()? - &start) / &step).real().clone()
+                + (Float::with_val(prec, 1) >> (prec / 2));
+            let mut values = Vec::new();
+            if count < 0
+            {
+                return gathered(values);
+            }
+            for k in 0..=count.floor().to_u32_saturating().ok_or(())?
+            {
+                let n = Num(start.clone() + step.clone() * k);
+                values.push(eval(
+                    &args[0]
+                        .iter()
+                        .map(|t| {
+                            if t.str_is(var)
+                            {
+                                n.clone()
+                            }
+                            else
+                            {
+                                t.clone()
+                            }
+                        })
+                        .collect::<Vec<NumStr>>(),
+                )?);
+            }
+            gathered(values)
+        }
+        _ => Err(()),
+    }
+}
+// a function given to map and the others, a lambda like x->x^2 or (a,b)->a+b, or the name of a
+// user function or one that takes a single argument
+fn applied(
+    f: &[NumStr],
+    args: Vec<NumStr>,
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+    depth: usize,
+) -> Result<NumStr, ()>
+{
+    if let [Str(name)] = f
+    {
+        if funcs.iter().any(|g| &g.name == name)
+        {
+            return call(name, args, deg, prec, funcs, depth);
+        }
+        else if args.len() == 1 && is_func(name)
+        {
+            let mut func = vec![Str(name.clone()), Str("(".to_string())];
+            func.extend(args);
+            func.push(Str(")".to_string()));
+            return evaluate(func, deg, prec, funcs, depth);
+        }
+    }
+    let arrow = f.iter().position(|n| n.str_is("->")).ok_or(())?;
+    let params = match &f[..arrow]
+    {
+        [Str(p)] => vec![p],
+        [Str(open), params @ .., Str(close)] if open == "(" && close == ")" => params
+            .iter()
+            .step_by(2)
+            .map(|n| match n
+            {
+                Str(p) => Ok(p),
+                _ => Err(()),
+            })
+            .collect::<Result<Vec<&String>, ()>>()?,
+        _ => return Err(()),
+    };
+    if params.len() != args.len()
+    {
+        return Err(());
+    }
+    let body = f[arrow + 1..]
+        .iter()
+        .map(|n| match n
+        {
+            Str(s) => params
+                .iter()
+                .position(|p| *p == s)
+                .map_or_else(|| n.clone(), |k| args[k].clone()),
+            _ => n.clone(),
+        })
+        .collect();
+    deeper(body, deg, prec, funcs, depth)
+}
+// the results of map and the others as a vector, or a matrix when they're all rows of one length
+fn gathered(values: Vec<NumStr>) -> Result<NumStr, ()>
+{
+    if values.iter().all(|n| matches!(n, Num(_)))
+    {
+        return Ok(Vector(
+            values
+                .iter()
+                .map(NumStr::num)
+                .collect::<Result<Vec<Complex>, ()>>()?,
+        ));
+    }
+    let rows = values
+        .iter()
+        .map(NumStr::vec)
+        .collect::<Result<Vec<Vec<Complex>>, ()>>()?;
+    if rows.iter().any(|r| r.len() != rows[0].len())
+    {
+        return Err(());
+    }
+    Ok(Matrix(rows))
+}
+// reals compare by value, anything complex by its magnitude
+fn compared(a: Complex, b: Complex) -> (Float, Float)
+{
+    if a.imag().is_zero() && b.imag().is_zero()
+    {
+        (a.real().clone(), b.real().clone())
+    }
+    else
+    {
+        (a.abs().real().clone(), b.abs().real().clone())
+    }
+}
+// binds the arguments to a user function's parameters, missing ones from the end take their
+// defaults, which can use the parameters before them, and evaluates its body with them
+fn call(
+    name: &str,
+    mut args: Vec<NumStr>,
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+    depth: usize,
+) -> Result<NumStr, ()>
+{
+    let f = funcs
+        .iter()
+        .filter(|f| f.name == name)
+        .find(|f| {
+            f.params.len() == args.len()
+                || (f.params.len() > args.len()
+                    && f.defaults[args.len()..].iter().all(Option::is_some))
+        })
+        .ok_or(())?;
+    let bind = |func: &[NumStr], args: &[NumStr]| -> Vec<NumStr> {
+        func.iter()
+            .map(|n| match n
+            {
+                Str(s) => f.params[..args.len()]
+                    .iter()
+                    .position(|p| p == s)
+                    .map_or_else(|| n.clone(), |k| args[k].clone()),
+                _ => n.clone(),
+            })
+            .collect()
+    };
+    for d in &f.defaults[args.len()..]
+    {
+        let d = bind(d.as_ref().ok_or(())?, &args);
+        args.push(evaluate(d, deg, prec, funcs, depth)?);
+    }
+    deeper(bind(&f.body, &args), deg, prec, funcs, depth)
+}
+// a function's body evaluated one call deeper, recursion gives up past 256 calls and every few
+// calls the rest goes on to a new thread, as one stack can't hold the frames of them all
+fn deeper(
+    body: Vec<NumStr>,
+    deg: AngleType,
+    prec: u32,
+    funcs: &[Function],
+    depth: usize,
+) -> Result<NumStr, ()>
+{
+    if depth >= 256
+    {
+        LIMIT.with(|l| l.set(true));
+        return Err(());
+    }
+    if depth % 8 != 7
+    {
+        return evaluate(body, deg, prec, funcs, depth + 1);
+    }
+    let (value, limit) = thread::scope(|s| {
+        thread::Builder::new()
+            .stack_size(16 << 20)
+            .spawn_scoped(s, || {
+                (
+                    evaluate(body, deg, prec, funcs, depth + 1),
+                    recursion_limit(),
+                )
+            })
+            .map_err(|_| ())?
+            .join()
+            .map_err(|_| ())
+    })?;
+    LIMIT.with(|l| l.set(limit));
+    value
+}
 fn sum(
     function: Vec<NumStr>,
     var: &str,
     start: i64,
     end: i64,
     product: bool,
-    deg: AngleType,
     prec: u32,
+    eval: &dyn Fn(Vec<NumStr>) -> Result<NumStr, ()>,
 ) -> Result<NumStr, ()>
 {
     let mut func = function.clone();
@@ -1152,7 +1838,7 @@ fn sum(
             *k = Num(Complex::with_val(prec, start));
         }
     }
-    let mut value = do_math(func, deg, prec)?;
+    let mut value = eval(func)?;
     for z in start + 1..=end
     {
         func = function.clone();
@@ -1163,7 +1849,7 @@ fn sum(
                 *k = Num(Complex::with_val(prec, z));
             }
         }
-        math = do_math(func, deg, prec)?;
+        math = eval(func)?;
         if !product
         {
             value = value.add(&math)?;
@@ -1191,36 +1877,76 @@ fn submatrix(a: Vec<Vec<Complex>>, row: usize, col: usize) -> Vec<Vec<Complex>>
 }
 fn determinant(a: Vec<Vec<Complex>>) -> Complex
 {
-    if a.len() == 1
+    let n = a.len();
+    let prec = a[0][0].prec();
+    let mut m = a;
+    let mut det = Complex::with_val(prec, 1);
+    // integer matrices use fraction free bareiss elimination so the result stays exact
+    if m.iter()
+        .flatten()
+        .all(|z| z.real().is_integer() && z.imag().is_integer())
     {
-        a[0][0].clone()
-    }
-    else if a.len() == 2
-    {
-        a[0][0].clone() * a[1][1].clone() - a[1][0].clone() * a[0][1].clone()
-    }
-    else if a.len() == 3
-    {
-        a[0][0].clone() * (a[1][1].clone() * a[2][2].clone() - a[1][2].clone() * a[2][1].clone())
-            + a[0][1].clone()
-                * (a[1][2].clone() * a[2][0].clone() - a[1][0].clone() * a[2][2].clone())
-            + a[0][2].clone()
-                * (a[1][0].clone() * a[2][1].clone() - a[1][1].clone() * a[2][0].clone())
+        let mut prev = Complex::with_val(prec, 1);
+        for k in 0..n - 1
+        {
+            if m[k][k].eq0()
+            {
+                match (k + 1..n).find(|i| !m[*i][k].eq0())
+                {
+                    Some(i) =>
+                    {
+                        m.swap(k, i);
+                        det = -det;
+                    }
+                    None => return Complex::new(prec),
+                }
+            }
+            let (top, bottom) = m.split_at_mut(k + 1);
+            for row in bottom
+            {
+                let f = row[k].clone();
+                for (z, p) in row.iter_mut().zip(top[k].iter()).skip(k + 1)
+                {
+                    *z = (z.clone() * &top[k][k] - f.clone() * p) / &prev;
+                }
+            }
+            prev = m[k][k].clone();
+        }
+        return det * &m[n - 1][n - 1];
     }
-    else
+    for k in 0..n
     {
-        let mut det = Complex::new(a[0][0].prec());
-        for (i, x) in a[0].iter().enumerate()
+        let p = (k..n)
+            .max_by(|x, y| {
+                m[*x][k]
+                    .clone()
+                    .abs()
+                    .real()
+                    .partial_cmp(m[*y][k].clone().abs().real())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        if m[p][k].eq0()
+        {
+            return Complex::new(prec);
+        }
+        if p != k
         {
-            let mut sub_matrix = a[1..].to_vec();
-            for row in &mut sub_matrix
+            m.swap(p, k);
+            det = -det;
+        }
+        det *= &m[k][k];
+        let (top, bottom) = m.split_at_mut(k + 1);
+        for row in bottom
+        {
+            let f = row[k].clone() / &top[k][k];
+            for (z, p) in row.iter_mut().zip(top[k].iter()).skip(k + 1)
             {
-                row.remove(i);
+                *z -= f.clone() * p;
             }
-            det += x * determinant(sub_matrix) * if i % 2 == 0 { 1.0 } else { -1.0 };
         }
-        det
     }
+    det
 }
 fn transpose(a: Vec<Vec<Complex>>) -> Vec<Vec<Complex>>
 {
@@ -1265,18 +1991,116 @@ fn cofactor(a: Vec<Vec<Complex>>) -> Vec<Vec<Complex>>
     }
     result
 }
+// gauss-jordan elimination, pivots this small relative to the largest entry count as singular
 pub fn inverse(a: Vec<Vec<Complex>>) -> Result<Vec<Vec<Complex>>, ()>
 {
-    if a.len() == a[0].len() && a.len() > 1
+    let n = a.len();
+    if n == 0 || a.iter().any(|r| r.len() != n)
     {
-        Matrix(transpose(cofactor(a.clone())))
-            .div(&Num(determinant(a)))?
-            .mat()
+        return Err(());
     }
-    else
+    let prec = a[0][0].prec();
+    let tol = tolerance(&a, prec.0 / 2);
+    let mut m: Vec<Vec<Complex>> = a
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut r)| {
+            r.extend((0..n).map(|j| Complex::with_val(prec, (i == j) as u8)));
+            r
+        })
+        .collect();
+    for k in 0..n
     {
-        Err(())
+        let p = (k..n)
+            .max_by(|x, y| {
+                m[*x][k]
+                    .clone()
+                    .abs()
+                    .real()
+                    .partial_cmp(m[*y][k].clone().abs().real())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        if m[p][k].clone().abs().real() <= &tol
+        {
+            return Err(());
+        }
+        m.swap(k, p);
+        let d = m[k][k].clone();
+        for z in m[k].iter_mut()
+        {
+            *z /= &d;
+        }
+        let pivot = m[k].clone();
+        for (i, row) in m.iter_mut().enumerate()
+        {
+            if i != k && !row[k].eq0()
+            {
+                let f = row[k].clone();
+                for (z, p) in row.iter_mut().zip(pivot.iter())
+                {
+                    *z -= f.clone() * p;
+                }
+            }
+        }
+    }
+    Ok(m.into_iter().map(|r| r[n..].to_vec()).collect())
+}
+// gaussian elimination with partial pivoting
+pub fn solve(a: Vec<Vec<Complex>>, b: Vec<Complex>) -> Result<Vec<Complex>, ()>
+{
+    let n = a.len();
+    if n == 0 || b.len() != n || a.iter().any(|r| r.len() != n)
+    {
+        return Err(());
+    }
+    let tol = tolerance(&a, a[0][0].prec().0 / 2);
+    let mut m: Vec<Vec<Complex>> = a
+        .into_iter()
+        .zip(b)
+        .map(|(mut r, b)| {
+            r.push(b);
+            r
+        })
+        .collect();
+    for i in 0..n
+    {
+        let p = (i..n)
+            .max_by(|x, y| {
+                m[*x][i]
+                    .clone()
+                    .abs()
+                    .real()
+                    .partial_cmp(m[*y][i].clone().abs().real())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+        if m[p][i].clone().abs().real() <= &tol
+        {
+            return Err(());
+        }
+        m.swap(i, p);
+        let (top, bottom) = m.split_at_mut(i + 1);
+        for row in bottom
+        {
+            let f = row[i].clone() / &top[i][i];
+            for (r, p) in row.iter_mut().zip(top[i].iter()).skip(i)
+            {
+                *r -= f.clone() * p;
+            }
+        }
+    }
+    let mut x = vec![Complex::new(m[0][0].prec()); n];
+    for i in (0..n).rev()
+    {
+        let mut t = m[i][n].clone();
+        for (j, v) in x.iter().enumerate().skip(i + 1)
+        {
+            t -= m[i][j].clone() * v;
+        }
+        x[i] = t / &m[i][i];
     }
+    Ok(x)
 }
 fn functions(
     a: Complex,
@@ -1,320 +1,649 @@
 use crate::{help, Options};
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
-pub fn arg_opts(options: &mut Options, args: &mut Vec<String>) -> bool
+use std::{fs::read_to_string, str::FromStr};
+#[derive(Copy, Clone, PartialEq)]
+pub enum Kind
 {
-    let mut err = false;
-    args.remove(0);
-    let (mut split, mut l);
-    let mut i = 0;
-    while i < args.len()
-    {
-        if args[i].starts_with("--") && (args[i].contains('=') || args[i].contains(','))
-        {
-            l = args[i].clone();
-            split = l.split(|c| c == '=' || c == ',');
-            args[i] = split.next().unwrap().to_string();
-            args.insert(i + 1, split.next().unwrap().to_string());
-            if split.clone().count() > 0
-            {
-                args.insert(i + 2, split.next().unwrap().to_string());
-            }
-        }
-        match args[i].as_str()
-        {
-            "--debug" => options.debug = !options.debug,
-            "--tau" => options.tau = !options.tau,
-            "--rad" => options.deg = AngleType::Radians,
-            "--deg" => options.deg = AngleType::Degrees,
-            "--grad" => options.deg = AngleType::Gradians,
-            "--prompt" => options.prompt = !options.prompt,
-            "--color" => options.color = !options.color,
-            "--line" => options.lines = !options.lines,
-            "--rt" => options.real_time_output = !options.real_time_output,
-            "--polar" => options.polar = !options.polar,
-            "--frac" => options.frac = !options.frac,
-            "--multi" => options.multi = !options.multi,
-            "--tabbed" => options.tabbed = !options.tabbed,
-            "--prec" | "--precision" =>
-            {
-                if args.len() > 1
-                {
-                    options.prec = match args[i + 1].parse::<u32>()
-                    {
-                        Ok(x) =>
-                        {
-                            if x == 0
-                            {
-                                println!("Invalid precision");
-                                err = true;
-                                args.remove(i);
-                                continue;
-                            }
-                            else
-                            {
-                                x
-                            }
-                        }
-                        Err(_) =>
-                        {
-                            println!("Invalid precision");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                }
-            }
-            "--decimal" | "--deci" | "--decimals" =>
+    // flipped when named on its own
+    Toggle,
+    // turned on when named on its own, the name is also a constant or function so name=value
+    // keeps its usual meaning and set has to be used instead
+    Switch,
+    Value,
+    // min,max
+    Range,
+}
+// one entry per option, the flags, the config file, the repl and help are all read off this
+pub struct Opt
+{
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub kind: Kind,
+    arg: &'static str,
+    help: &'static str,
+    expect: &'static str,
+    get: fn(&Options) -> String,
+    set: fn(&mut Options, &str) -> Result<(), ()>,
+}
+pub static OPTIONS: [Opt; 35] = [
+    Opt {
+        name: "tau",
+        aliases: &[],
+        kind: Kind::Switch,
+        arg: "",
+        help: "fractions are shown in tau instead of pi",
+        expect: "true or false",
+        get: |o| o.tau.to_string(),
+        set: |o, s| {
+            o.tau = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "pi",
+        aliases: &[],
+        kind: Kind::Switch,
+        arg: "",
+        help: "fractions are shown in pi (default)",
+        expect: "true or false",
+        get: |o| (!o.tau).to_string(),
+        set: |o, s| {
+            o.tau = !boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "deg",
+        aliases: &[],
+        kind: Kind::Switch,
+        arg: "",
+        help: "compute in degrees",
+        expect: "true or false",
+        get: |o| (o.deg == AngleType::Degrees).to_string(),
+        set: |o, s| angle(o, s, AngleType::Degrees),
+    },
+    Opt {
+        name: "rad",
+        aliases: &[],
+        kind: Kind::Switch,
+        arg: "",
+        help: "compute in radians (default)",
+        expect: "true or false",
+        get: |o| (o.deg == AngleType::Radians).to_string(),
+        set: |o, s| angle(o, s, AngleType::Radians),
+    },
+    Opt {
+        name: "grad",
+        aliases: &[],
+        kind: Kind::Switch,
+        arg: "",
+        help: "compute in gradians",
+        expect: "true or false",
+        get: |o| (o.deg == AngleType::Gradians).to_string(),
+        set: |o, s| angle(o, s, AngleType::Gradians),
+    },
+    Opt {
+        name: "2d",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "number of points to graph in 2D",
+        expect: "a number above 0",
+        get: |o| o.samples_2d.to_string(),
+        set: |o, s| {
+            o.samples_2d = positive(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "3d",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "number of points to graph in 3D",
+        expect: "a number above 0",
+        get: |o| o.samples_3d.to_string(),
+        set: |o, s| {
+            o.samples_3d = positive(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "xr",
+        aliases: &[],
+        kind: Kind::Range,
+        arg: "=[min],[max]",
+        help: "x range for graphing",
+        expect: "min,max",
+        get: |o| format!("{},{}", o.xr[0], o.xr[1]),
+        set: |o, s| {
+            o.xr = range(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "yr",
+        aliases: &[],
+        kind: Kind::Range,
+        arg: "=[min],[max]",
+        help: "y range for graphing",
+        expect: "min,max",
+        get: |o| format!("{},{}", o.yr[0], o.yr[1]),
+        set: |o, s| {
+            o.yr = range(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "zr",
+        aliases: &[],
+        kind: Kind::Range,
+        arg: "=[min],[max]",
+        help: "z range for graphing",
+        expect: "min,max",
+        get: |o| format!("{},{}", o.zr[0], o.zr[1]),
+        set: |o, s| {
+            o.zr = range(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "point",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[char]",
+        help: "point style for graphing",
+        expect: "one of . + x * s S o O t T d D r R",
+        get: |o| o.point_style.to_string(),
+        set: |o, s| {
+            o.point_style = match s.chars().collect::<Vec<char>>()[..]
             {
-                if args.len() > 1
-                {
-                    if args[i + 1] == "-1"
-                    {
-                        options.decimal_places = usize::MAX - 1;
-                    }
-                    else if args[i + 1] == "-2"
-                    {
-                        options.decimal_places = usize::MAX;
-                    }
-                    else
-                    {
-                        options.decimal_places = match args[i + 1].parse::<usize>()
-                        {
-                            Ok(x) => x,
-                            Err(_) =>
-                            {
-                                println!("Invalid decimal");
-                                err = true;
-                                args.remove(i);
-                                continue;
-                            }
-                        };
-                    }
-                    args.remove(i);
-                }
-            }
-            "--frac_iter" =>
+                [c] if ".+x*sSoOtTdDrR".contains(c) => c,
+                _ => return Err(()),
+            };
+            Ok(())
+        },
+    },
+    Opt {
+        name: "sci",
+        aliases: &["scientific"],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles scientific notation",
+        expect: "true or false",
+        get: |o| (o.notation == Notation::Sci).to_string(),
+        set: |o, s| {
+            if boolean(s)?
             {
-                if args.len() > 1
-                {
-                    options.frac_iter = match args[i + 1].parse::<usize>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid frac iter");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                }
+                o.notation = Notation::Sci;
             }
-            "--2d" =>
+            else if o.notation == Notation::Sci
             {
-                if args.len() > 1
-                {
-                    options.samples_2d = match args[i + 1].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid 2d sample size");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                }
+                o.notation = Notation::Auto;
             }
-            "--3d" =>
+            Ok(())
+        },
+    },
+    Opt {
+        name: "notation",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[auto|sci|eng|sig]",
+        help: "sets how numbers are written, eng uses si prefixes like 4.7k and sig rounds to significant figures",
+        expect: "auto, sci, eng or sig",
+        get: |o| {
+            match o.notation
             {
-                if args.len() > 1
-                {
-                    options.samples_3d = match args[i + 1].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid 3d sample size");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                }
+                Notation::Auto => "auto",
+                Notation::Sci => "sci",
+                Notation::Eng => "eng",
+                Notation::Sig => "sig",
             }
-            "--yr" =>
+            .to_string()
+        },
+        set: |o, s| {
+            o.notation = match s
             {
-                if args.len() > 2
-                {
-                    options.yr[0] = match args[i + 1].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid y range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    options.yr[1] = match args[i + 2].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid y range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                    args.remove(i);
-                }
-            }
-            "--xr" =>
+                "auto" => Notation::Auto,
+                "sci" => Notation::Sci,
+                "eng" => Notation::Eng,
+                "sig" => Notation::Sig,
+                _ => return Err(()),
+            };
+            Ok(())
+        },
+    },
+    Opt {
+        name: "sig",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "sets how many significant figures eng and sig notation show",
+        expect: "a whole number above 0",
+        get: |o| o.sig.to_string(),
+        set: |o, s| {
+            o.sig = number(s).and_then(|n| if n == 0 { Err(()) } else { Ok(n) })?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "base",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "sets the number base (2 to 36)",
+        expect: "a whole number from 2 to 36",
+        get: |o| o.base.to_string(),
+        set: |o, s| {
+            o.base = number(s).and_then(|n| {
+                if (2..=36).contains(&n)
+                {
+                    Ok(n)
+                }
+                else
+                {
+                    Err(())
+                }
+            })?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "prompt",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles the prompt",
+        expect: "true or false",
+        get: |o| o.prompt.to_string(),
+        set: |o, s| {
+            o.prompt = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "color",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles color",
+        expect: "true or false",
+        get: |o| o.color.to_string(),
+        set: |o, s| {
+            o.color = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "comma",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles comma seperation",
+        expect: "true or false",
+        get: |o| o.comma.to_string(),
+        set: |o, s| {
+            o.comma = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "vars",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles default variables",
+        expect: "true or false",
+        get: |o| o.allow_vars.to_string(),
+        set: |o, s| {
+            o.allow_vars = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "line",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles line graphing",
+        expect: "true or false",
+        get: |o| o.lines.to_string(),
+        set: |o, s| {
+            o.lines = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "rt",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles real time printing",
+        expect: "true or false",
+        get: |o| o.real_time_output.to_string(),
+        set: |o, s| {
+            o.real_time_output = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "polar",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles displaying polar vectors",
+        expect: "true or false",
+        get: |o| o.polar.to_string(),
+        set: |o, s| {
+            o.polar = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "frac",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles fraction display",
+        expect: "true or false",
+        get: |o| o.frac.to_string(),
+        set: |o, s| {
+            o.frac = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "exact",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles exact symbolic display like 2√2 or π/6",
+        expect: "true or false",
+        get: |o| o.exact.to_string(),
+        set: |o, s| {
+            o.exact = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "frac_iter",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "how many iterations to check for fractions",
+        expect: "a whole number",
+        get: |o| o.frac_iter.to_string(),
+        set: |o, s| {
+            o.frac_iter = number(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "history_size",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "how many inputs the history keeps",
+        expect: "a whole number",
+        get: |o| o.history_size.to_string(),
+        set: |o, s| {
+            o.history_size = number(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "session",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles keeping variables, options and answers in kalc.session between runs",
+        expect: "true or false",
+        get: |o| o.session.to_string(),
+        set: |o, s| {
+            o.session = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "prec",
+        aliases: &["precision"],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "sets the precision",
+        expect: "a whole number above 0",
+        get: |o| o.prec.to_string(),
+        set: |o, s| {
+            o.prec = number(s).and_then(|n| if n == 0 { Err(()) } else { Ok(n) })?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "deci",
+        aliases: &["decimal", "decimals"],
+        kind: Kind::Value,
+        arg: "=[num]",
+        help: "sets how many decimals to display, -1 for length of terminal, -2 for maximum decimal places, may need to up precision for more decimals",
+        expect: "a whole number, -1 or -2",
+        get: |o| match o.decimal_places
+        {
+            usize::MAX => "-2".to_string(),
+            n if n == usize::MAX - 1 => "-1".to_string(),
+            n => n.to_string(),
+        },
+        set: |o, s| {
+            o.decimal_places = match s
             {
-                if args.len() > 2
-                {
-                    options.xr[0] = match args[i + 1].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid x range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    options.xr[1] = match args[i + 2].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid x range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                    args.remove(i);
-                }
-            }
-            "--zr" =>
+                "-1" => usize::MAX - 1,
+                "-2" => usize::MAX,
+                _ => number(s)?,
+            };
+            Ok(())
+        },
+    },
+    Opt {
+        name: "multi",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles multi line display for matrixes",
+        expect: "true or false",
+        get: |o| o.multi.to_string(),
+        set: |o, s| {
+            o.multi = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "tabbed",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles tabbed display for matrixes",
+        expect: "true or false",
+        get: |o| o.tabbed.to_string(),
+        set: |o, s| {
+            o.tabbed = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "pretty",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles drawing matrixes in brackets with their columns lined up and stacking fractions",
+        expect: "true or false",
+        get: |o| o.pretty.to_string(),
+        set: |o, s| {
+            o.pretty = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "format",
+        aliases: &[],
+        kind: Kind::Value,
+        arg: "=[text|latex|mathml]",
+        help: "prints each input with its answer as latex or mathml instead of text",
+        expect: "text, latex or mathml",
+        get: |o| {
+            match o.format
             {
-                if args.len() > 2
-                {
-                    options.zr[0] = match args[i + 1].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid z range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    options.zr[1] = match args[i + 2].parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid z range");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                    args.remove(i);
-                }
+                Format::Text => "text",
+                Format::Latex => "latex",
+                Format::MathML => "mathml",
             }
-            "--base" =>
+            .to_string()
+        },
+        set: |o, s| {
+            o.format = match s
             {
-                if args.len() > 1
-                {
-                    options.base = match args[i + 1].parse::<usize>()
-                    {
-                        Ok(x) =>
-                        {
-                            if !(2..=36).contains(&x)
-                            {
-                                println!("Invalid base");
-                                err = true;
-                                args.remove(i);
-                                continue;
-                            }
-                            else
-                            {
-                                x
-                            }
-                        }
-                        Err(_) =>
-                        {
-                            println!("Invalid base");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    };
-                    args.remove(i);
-                }
-            }
-            "--comma" => options.comma = !options.comma,
-            "--sci" | "--scientific" => options.sci = !options.sci,
-            "--point" =>
+                "text" => Format::Text,
+                "latex" => Format::Latex,
+                "mathml" => Format::MathML,
+                _ => return Err(()),
+            };
+            Ok(())
+        },
+    },
+    Opt {
+        name: "json",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "toggles printing each answer or error as a json object, for other programs to read",
+        expect: "true or false",
+        get: |o| o.json.to_string(),
+        set: |o, s| {
+            o.json = boolean(s)?;
+            Ok(())
+        },
+    },
+    Opt {
+        name: "debug",
+        aliases: &[],
+        kind: Kind::Toggle,
+        arg: "",
+        help: "displays computation time in nanoseconds",
+        expect: "true or false",
+        get: |o| o.debug.to_string(),
+        set: |o, s| {
+            o.debug = boolean(s)?;
+            Ok(())
+        },
+    },
+];
+fn boolean(s: &str) -> Result<bool, ()>
+{
+    s.parse().map_err(|_| ())
+}
+fn number<T: FromStr>(s: &str) -> Result<T, ()>
+{
+    s.parse().map_err(|_| ())
+}
+fn positive(s: &str) -> Result<f64, ()>
+{
+    number(s).and_then(|n: f64| {
+        if n > 0.0
+        {
+            Ok(n)
+        }
+        else
+        {
+            Err(())
+        }
+    })
+}
+fn range(s: &str) -> Result<[f64; 2], ()>
+{
+    let (min, max) = s.split_once(',').ok_or(())?;
+    Ok([number(min.trim())?, number(max.trim())?])
+}
+// turning an angle off goes back to radians
+fn angle(options: &mut Options, s: &str, angle: AngleType) -> Result<(), ()>
+{
+    if boolean(s)?
+    {
+        options.deg = angle;
+    }
+    else if options.deg == angle
+    {
+        options.deg = AngleType::Radians;
+    }
+    Ok(())
+}
+impl Opt
+{
+    pub fn get(&self, options: &Options) -> String
+    {
+        (self.get)(options)
+    }
+    pub fn set(&self, options: &mut Options, value: &str) -> Result<(), String>
+    {
+        (self.set)(options, value.trim())
+            .map_err(|_| format!("Invalid {}, expected {}", self.name, self.expect))
+    }
+    pub fn toggle(&self, options: &mut Options)
+    {
+        let on = self.kind == Kind::Switch || self.get(options) != "true";
+        (self.set)(options, &on.to_string()).unwrap();
+    }
+    pub fn reset(&self, options: &mut Options)
+    {
+        (self.set)(options, &self.get(&Options::default())).unwrap();
+    }
+    pub fn flag(&self) -> String
+    {
+        format!("--{}{} {}", self.name, self.arg, self.help)
+    }
+}
+pub fn option(name: &str) -> Option<&'static Opt>
+{
+    OPTIONS
+        .iter()
+        .find(|o| o.name == name || o.aliases.contains(&name))
+}
+// the options that can be typed as name=value
+pub fn setting(name: &str) -> Option<&'static Opt>
+{
+    option(name).filter(|o| o.kind != Kind::Switch)
+}
+// get, set and reset from the repl or a script
+pub fn set_option(command: &str, arg: &str, options: &mut Options) -> String
+{
+    let (name, value) = arg.split_once('=').unwrap_or((arg, ""));
+    let opt = match option(name.trim())
+    {
+        Some(o) => o,
+        None => return format!("Unknown option {}", name.trim()),
+    };
+    match command
+    {
+        "get" => return opt.get(options),
+        "set" =>
+        {
+            if let Err(e) = opt.set(options, value)
             {
-                options.point_style = match args[i + 1].chars().next()
-                {
-                    Some(x) =>
-                    {
-                        if x == '.'
-                            || x == '+'
-                            || x == 'x'
-                            || x == '*'
-                            || x == 's'
-                            || x == 'S'
-                            || x == 'o'
-                            || x == 'O'
-                            || x == 't'
-                            || x == 'T'
-                            || x == 'd'
-                            || x == 'D'
-                            || x == 'x'
-                            || x == 'R'
-                        {
-                            x
-                        }
-                        else
-                        {
-                            println!("Invalid point char");
-                            err = true;
-                            args.remove(i);
-                            continue;
-                        }
-                    }
-                    None =>
-                    {
-                        println!("Invalid point char");
-                        err = true;
-                        args.remove(i);
-                        continue;
-                    }
-                };
-                args.remove(i);
+                return e;
             }
+        }
+        _ => opt.reset(options),
+    }
+    format!("{}={}", opt.name, opt.get(options))
+}
+pub fn arg_opts(options: &mut Options, args: &mut Vec<String>, config: &Config) -> bool
+{
+    let mut err = false;
+    args.remove(0);
+    let mut i = 0;
+    while i < args.len()
+    {
+        let (name, value) = match args[i].split_once('=')
+        {
+            Some((n, v)) => (n.to_string(), Some(v.to_string())),
+            None => (args[i].clone(), None),
+        };
+        match name.as_str()
+        {
             "--help" | "-h" =>
             {
                 help();
@@ -325,458 +654,269 @@ pub fn arg_opts(options: &mut Options, args: &mut Vec<String>) -> bool
                 println!("{} v{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
                 std::process::exit(0);
             }
-            "--vars" => options.allow_vars = !options.allow_vars,
             "--default" | "--def" =>
             {
                 *options = Options::default();
+                args.remove(i);
+                continue;
             }
-            _ =>
+            "--profile" =>
             {
-                i += 1;
+                args.remove(i);
+                let name = match value
+                {
+                    Some(v) => v,
+                    None if i < args.len() => args.remove(i),
+                    None => String::new(),
+                };
+                if config.profile(&name, options).is_none()
+                {
+                    println!("Unknown profile {}", name);
+                    err = true;
+                }
                 continue;
             }
+            _ => (),
         }
+        let opt = match name.strip_prefix("--").and_then(option)
+        {
+            Some(o) => o,
+            None =>
+            {
+                i += 1;
+                continue;
+            }
+        };
         args.remove(i);
+        // a value can also be the next argument, or the next two for a range
+        let value = match value
+        {
+            Some(v) => v,
+            None if opt.kind == Kind::Toggle || opt.kind == Kind::Switch =>
+            {
+                opt.toggle(options);
+                continue;
+            }
+            None if i < args.len() =>
+            {
+                let v = args.remove(i);
+                if opt.kind == Kind::Range && !v.contains(',') && i < args.len()
+                {
+                    format!("{},{}", v, args.remove(i))
+                }
+                else
+                {
+                    v
+                }
+            }
+            None => String::new(),
+        };
+        if let Err(e) = opt.set(options, &value)
+        {
+            println!("{}", e);
+            err = true;
+        }
     }
     err
 }
-pub fn file_opts(options: &mut Options, file_path: &String) -> bool
+// kalc.config is toml, the options are top level keys and each [profile.name] table is laid over
+// them when picked, a key keeps its line so a problem can point at it
+#[derive(Default)]
+pub struct Config
 {
-    let mut err = false;
-    if File::open(file_path).is_ok()
+    tables: Vec<Table>,
+}
+struct Table
+{
+    name: String,
+    keys: Vec<(usize, &'static Opt, Option<String>)>,
+}
+impl Config
+{
+    pub fn parse(text: &str) -> (Config, Vec<(usize, String)>)
     {
-        let file = File::open(file_path).unwrap();
-        let reader = BufReader::new(file);
-        let mut split;
-        for line in reader.lines().map(|l| l.unwrap())
+        let mut errors = Vec::new();
+        let mut tables = vec![Table {
+            name: String::new(),
+            keys: Vec::new(),
+        }];
+        // the keys of a table that isn't a profile are left out, its header was already reported
+        let mut skip = false;
+        for (i, line) in text.lines().enumerate()
         {
-            split = line.split('=');
-            match split.next().unwrap()
+            let line = strip_comment(line).trim();
+            if line.is_empty()
             {
-                "frac_iter" =>
-                {
-                    options.frac_iter = match split.next().unwrap().parse::<usize>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid frac iter");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "2d" =>
-                {
-                    options.samples_2d = match split.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid 2d sample size");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "3d" =>
-                {
-                    options.samples_3d = match split.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid 3d sample size");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "xr" =>
-                {
-                    let mut xr = split.next().unwrap().split(',');
-                    if xr.clone().count() != 2
-                    {
-                        println!("Invalid x range");
-                        err = true;
-                        continue;
-                    }
-                    options.xr[0] = match xr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid x range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                    options.xr[1] = match xr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid x range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "yr" =>
-                {
-                    let mut yr = split.next().unwrap().split(',');
-                    if yr.clone().count() != 2
-                    {
-                        println!("Invalid y range");
-                        err = true;
-                        continue;
-                    }
-                    options.yr[0] = match yr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid y range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                    options.yr[1] = match yr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid y range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "zr" =>
-                {
-                    let mut zr = split.next().unwrap().split(',');
-                    if zr.clone().count() != 2
-                    {
-                        println!("Invalid z range");
-                        err = true;
-                        continue;
-                    }
-                    options.zr[0] = match zr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid z range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                    options.zr[1] = match zr.next().unwrap().parse::<f64>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid z range");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "prec" | "precision" =>
-                {
-                    options.prec = match split.next().unwrap().parse::<u32>()
-                    {
-                        Ok(x) =>
-                        {
-                            if x == 0
-                            {
-                                println!("Invalid precision");
-                                err = true;
-                                continue;
-                            }
-                            else
-                            {
-                                x
-                            }
-                        }
-                        Err(_) =>
-                        {
-                            println!("Invalid precision");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "decimal" | "deci" | "decimals" =>
-                {
-                    let r = split.next().unwrap();
-                    if r == "-1"
-                    {
-                        options.decimal_places = usize::MAX - 1;
-                    }
-                    else if r == "-2"
-                    {
-                        options.decimal_places = usize::MAX;
-                    }
-                    else
-                    {
-                        options.decimal_places = match r.parse::<usize>()
-                        {
-                            Ok(x) => x,
-                            Err(_) =>
-                            {
-                                println!("Invalid decimal places");
-                                err = true;
-                                continue;
-                            }
-                        };
-                    }
-                }
-                "multi" =>
-                {
-                    options.multi = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid multi bool");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "tabbed" =>
-                {
-                    options.tabbed = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid tabbed bool");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "rt" =>
-                {
-                    options.real_time_output = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid real time bool");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "line" =>
-                {
-                    options.lines = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid line bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "polar" =>
-                {
-                    options.polar = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid polar bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "frac" =>
-                {
-                    options.polar = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid frac bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "prompt" =>
-                {
-                    options.prompt = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid prompt bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "comma" =>
-                {
-                    options.comma = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid comma bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "color" =>
-                {
-                    options.color = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid color bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "point" =>
-                {
-                    options.point_style = match split.next().unwrap().chars().next()
-                    {
-                        Some(x) =>
-                        {
-                            if x == '.'
-                                || x == '+'
-                                || x == 'x'
-                                || x == '*'
-                                || x == 's'
-                                || x == 'S'
-                                || x == 'o'
-                                || x == 'O'
-                                || x == 't'
-                                || x == 'T'
-                                || x == 'd'
-                                || x == 'D'
-                                || x == 'x'
-                                || x == 'R'
-                            {
-                                x
-                            }
-                            else
-                            {
-                                println!("Invalid point char");
-                                err = true;
-                                continue;
-                            }
-                        }
-                        None =>
-                        {
-                            println!("Invalid point char");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "sci" | "scientific" =>
-                {
-                    options.sci = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid scientific bool");
-                            err = true;
-                            continue;
-                        }
-                    }
-                }
-                "base" =>
-                {
-                    options.base = match split.next().unwrap().parse::<usize>()
-                    {
-                        Ok(x) =>
-                        {
-                            if !(2..=36).contains(&x)
-                            {
-                                println!("Invalid base");
-                                err = true;
-                                continue;
-                            }
-                            else
-                            {
-                                x
-                            }
-                        }
-                        Err(_) =>
-                        {
-                            println!("Invalid base");
-                            err = true;
-                            continue;
-                        }
-                    };
-                }
-                "debug" =>
-                {
-                    options.debug = match split.next().unwrap().parse::<bool>()
-                    {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid debug bool");
-                            err = true;
-                            continue;
-                        }
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[')
+            {
+                match header
+                    .strip_suffix(']')
+                    .and_then(|h| h.trim().strip_prefix("profile."))
+                    .map(|n| unquote(n.trim()))
+                {
+                    Some(name) if !name.is_empty() =>
+                    {
+                        tables.push(Table {
+                            name: name.to_string(),
+                            keys: Vec::new(),
+                        });
+                        skip = false;
                     }
-                }
-                "deg" => options.deg = AngleType::Radians,
-                "rad" => options.deg = AngleType::Degrees,
-                "grad" => options.deg = AngleType::Gradians,
-                "tau" =>
-                {
-                    options.tau = match split.next().unwrap().parse::<bool>()
+                    _ =>
                     {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid tau bool");
-                            err = true;
-                            continue;
-                        }
+                        errors.push((i + 1, format!("Unknown table {}", line)));
+                        skip = true;
                     }
                 }
-                "vars" =>
+                continue;
+            }
+            if skip
+            {
+                continue;
+            }
+            // a line without = is a toggle like the old flat files had
+            let (key, value) = match line.split_once('=')
+            {
+                Some((k, v)) => (unquote(k.trim()), Some(toml_value(v.trim()))),
+                None => (line, None),
+            };
+            match option(key)
+            {
+                Some(opt) => tables.last_mut().unwrap().keys.push((i + 1, opt, value)),
+                None => errors.push((i + 1, format!("Unknown option {}", key))),
+            }
+        }
+        let config = Config { tables };
+        // every table is tried on the defaults so a profile that isn't picked still gets checked
+        for table in &config.tables
+        {
+            errors.extend(config.apply(&table.name, &mut Options::default()).unwrap());
+        }
+        errors.sort_by_key(|(line, _)| *line);
+        (config, errors)
+    }
+    fn apply(&self, table: &str, options: &mut Options) -> Option<Vec<(usize, String)>>
+    {
+        let table = self.tables.iter().find(|t| t.name == table)?;
+        let mut errors = Vec::new();
+        for (line, opt, value) in &table.keys
+        {
+            match value
+            {
+                Some(v) =>
                 {
-                    options.allow_vars = match split.next().unwrap().parse::<bool>()
+                    if let Err(e) = opt.set(options, v)
                     {
-                        Ok(x) => x,
-                        Err(_) =>
-                        {
-                            println!("Invalid vars bool");
-                            err = true;
-                            continue;
-                        }
+                        errors.push((*line, e));
                     }
                 }
-                _ =>
-                {}
+                None if opt.kind == Kind::Toggle || opt.kind == Kind::Switch => opt.toggle(options),
+                None => errors.push((*line, format!("{} needs a value", opt.name))),
             }
         }
+        Some(errors)
+    }
+    // lays a profile over the options and lists what it set, its mistakes were shown on reading
+    pub fn profile(&self, name: &str, options: &mut Options) -> Option<String>
+    {
+        if name.is_empty()
+        {
+            return None;
+        }
+        self.apply(name, options)?;
+        let table = self.tables.iter().find(|t| t.name == name)?;
+        Some(
+            table
+                .keys
+                .iter()
+                .map(|(_, opt, _)| format!("{}={}", opt.name, opt.get(options)))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+    pub fn profiles(&self) -> Vec<&str>
+    {
+        self.tables
+            .iter()
+            .skip(1)
+            .map(|t| t.name.as_str())
+            .collect()
     }
-    err
+}
+// a # starts a comment unless it's quoted
+fn strip_comment(line: &str) -> &str
+{
+    let mut quote = None;
+    for (i, c) in line.char_indices()
+    {
+        match quote
+        {
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => (),
+        }
+    }
+    line
+}
+fn unquote(s: &str) -> &str
+{
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+        .unwrap_or(s)
+}
+// strings lose their quotes and arrays become min,max, anything else like the old xr=-5,5 is
+// passed on as written
+fn toml_value(v: &str) -> String
+{
+    match v.strip_prefix('[').and_then(|v| v.strip_suffix(']'))
+    {
+        Some(items) => items
+            .split(',')
+            .map(|i| unquote(i.trim()))
+            .collect::<Vec<&str>>()
+            .join(","),
+        None => unquote(v).to_string(),
+    }
+}
+// reads kalc.config, what it can't use is reported by line and left out
+pub fn file_opts(options: &mut Options, file_path: &String) -> Config
+{
+    let (config, errors) = match read_to_string(file_path)
+    {
+        Ok(s) => Config::parse(&s),
+        Err(_) => return Config::default(),
+    };
+    for (line, e) in errors
+    {
+        println!("{} line {}: {}", file_path, line, e);
+    }
+    config.apply("", options);
+    config
 }
 
 #[derive(Copy, Clone, PartialEq)]
-pub enum AngleType{
+pub enum Notation
+{
+    Auto,
+    Sci,
+    Eng,
+    Sig,
+}
+#[derive(Copy, Clone, PartialEq)]
+pub enum Format
+{
+    Text,
+    Latex,
+    MathML,
+}
+#[derive(Copy, Clone, PartialEq)]
+pub enum AngleType
+{
     Radians,
     Degrees,
     Gradians,
@@ -5,10 +5,11 @@ use crate::{
     },
     parse,
 };
-use rug::{float::Constant::Pi, Complex, Float};
+use rug::{float::Constant::Pi, ops::Pow, Complex, Float, Integer};
 use std::{
     collections::HashSet,
     io::{stdin, IsTerminal},
+    ops::Range,
 };
 pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
 {
@@ -32,11 +33,8 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
             if !word.is_empty()
             {
                 find_word = false;
-                if is_func(&word)
-                {
-                    place_multiplier(&mut func, &find_word);
-                    func.push(Str(word.clone()));
-                }
+                place_multiplier(&mut func, &find_word);
+                func.push(Str(word.clone()));
                 word.clear();
             }
             else if i != 0
@@ -52,36 +50,44 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
             if !word.is_empty() && word != "0."
             {
                 find_word = false;
-                if is_func(&word)
-                {
-                    place_multiplier(&mut func, &find_word);
-                    func.push(Str(word.clone()));
-                }
+                place_multiplier(&mut func, &find_word);
+                func.push(Str(word.clone()));
                 word.clear();
             }
             place_multiplier(&mut func, &find_word);
-            deci = false;
-            for c in chars[i..].iter()
+            let mut num = if let Some(literal) =
+                radix_literal(&chars, i).filter(|_| word.is_empty())
             {
-                match c
+                i = literal.2;
+                radix_value(&chars, literal, prec)?
+            }
+            else
+            {
+                deci = false;
+                for c in chars[i..].iter()
                 {
-                    '0'..='9' =>
-                    {
-                        word.push(*c);
-                    }
-                    '.' =>
+                    match c
                     {
-                        if deci
+                        '0'..='9' =>
                         {
-                            return Err(());
+                            word.push(*c);
                         }
-                        deci = true;
-                        word.push(*c);
+                        '.' if chars.len() > i + 1 && chars[i + 1] == '^' => break,
+                        '.' =>
+                        {
+                            if deci
+                            {
+                                return Err(());
+                            }
+                            deci = true;
+                            word.push(*c);
+                        }
+                        _ => break,
                     }
-                    _ => break,
+                    i += 1;
                 }
-                i += 1;
-            }
+                Complex::with_val(prec, Complex::parse(word.as_bytes()).unwrap())
+            };
             if neg
             {
                 if chars.len() > i && chars[i] == '^'
@@ -91,14 +97,12 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                 }
                 else
                 {
-                    word.insert(0, '-');
+                    // only the real part, a -0i would flip the branch of sqrt, arg and the like
+                    *num.mut_real() *= -1;
                 }
                 neg = false;
             }
-            func.push(Num(Complex::with_val(
-                prec,
-                Complex::parse(word.as_bytes()).unwrap(),
-            )));
+            func.push(Num(num));
             word.clear();
             if !open
             {
@@ -109,12 +113,11 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
         }
         else if c.is_ascii_alphabetic()
         {
-            if find_word
-                && (!(c == 'x' || c == 'y')
-                    || (chars.len() - 1 != i && chars[i + 1] == 'p' && word == "e")
-                    || word == "ma")
+            if (find_word && !(c == 'x' || c == 'y' || c == 'z'))
+                || continues_func(&word, &chars[i..])
             {
                 word.push(c);
+                find_word = true;
             }
             else
             {
@@ -143,18 +146,16 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                             count += 1;
                         }
                     }
-                    'x' | 'y' =>
+                    'x' | 'y' | 'z' =>
                     {
                         if !word.is_empty()
                         {
                             find_word = false;
-                            if is_func(&word)
-                            {
-                                place_multiplier(&mut func, &find_word);
-                                func.push(Str(word.clone()));
-                            }
+                            place_multiplier(&mut func, &find_word);
+                            func.push(Str(word.clone()));
                             word.clear();
                         }
+                        place_multiplier(&mut func, &find_word);
                         func.push(Str(c.to_string()));
                         if !open
                         {
@@ -188,7 +189,8 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
             if !word.is_empty()
             {
                 find_word = false;
-                if i + 4 < chars.len()
+                if is_func(&word)
+                    && i + 4 < chars.len()
                     && chars[i] == '^'
                     && chars[i + 1] == '('
                     && chars[i + 2] == '-'
@@ -202,7 +204,8 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                     i += 5;
                     continue;
                 }
-                if i + 2 < chars.len()
+                if is_func(&word)
+                    && i + 2 < chars.len()
                     && chars[i] == '^'
                     && chars[i + 1] == '-'
                     && chars[i + 2] == '1'
@@ -214,7 +217,8 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                     i += 3;
                     continue;
                 }
-                if i + 1 < chars.len()
+                if is_func(&word)
+                    && i + 1 < chars.len()
                     && chars[i] == '^'
                     && (chars[i + 1].is_ascii_digit() || chars[i + 1] == '-')
                 {
@@ -234,12 +238,9 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                     i += pos.unwrap() + 1;
                     continue;
                 }
-                if is_func(&word)
-                {
-                    place_multiplier(&mut func, &find_word);
-                    func.push(Str(word.clone()));
-                    word.clear();
-                }
+                place_multiplier(&mut func, &find_word);
+                func.push(Str(word.clone()));
+                word.clear();
             }
             if !exp.is_empty() && c != '(' && c != ')'
             {
@@ -256,6 +257,11 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
             }
             match c
             {
+                '.' if i + 1 < chars.len() && chars[i + 1] == '^' =>
+                {
+                    func.push(Str(".^".to_string()));
+                    i += 1;
+                }
                 '.' => word.push_str("0."),
                 '&' if i != 0 && i + 1 < chars.len() && chars[i + 1] == '&' =>
                 {
@@ -311,6 +317,7 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                     && i + 1 != chars.len()
                     && (chars[i - 1].is_ascii_alphanumeric()
                         || (!func.is_empty() && func.last().unwrap().str_is(")"))
+                        || chars[i - 1] == '_'
                         || chars[i - 1] == '}'
                         || chars[i - 1] == ']')
                     && chars[i - 1] != 'E' =>
@@ -341,6 +348,11 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                         func.push(Str('>'.to_string()));
                     }
                 }
+                '-' if i + 1 < chars.len() && chars[i + 1] == '>' =>
+                {
+                    func.push(Str("->".to_string()));
+                    i += 1;
+                }
                 '-' =>
                 {
                     if i != 0 && chars[i - 1] == '^'
@@ -353,6 +365,7 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                         || !(chars[i - 1] != 'E'
                             && (chars[i - 1].is_ascii_alphanumeric()
                                 || func.last().unwrap().str_is(")")
+                                || chars[i - 1] == '_'
                                 || chars[i - 1] == '}'
                                 || chars[i - 1] == ']'))
                     {
@@ -484,6 +497,56 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
                         count += 1;
                     }
                 }
+                // a call to a user function, input_var marks them so the name isn't read as variables
+                '@' =>
+                {
+                    place_multiplier(&mut func, &find_word);
+                    if neg
+                    {
+                        func.push(Num(n1.clone()));
+                        func.push(Str("*".to_string()));
+                        neg = false;
+                    }
+                    let name: String = chars[i + 1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphanumeric())
+                        .collect();
+                    i += name.len();
+                    func.push(Str(format!("@{}", name)));
+                }
+                // an underscore inside a name is part of it, not the last answer
+                '_' if i != 0
+                    && chars[i - 1].is_ascii_alphanumeric()
+                    && chars.get(i + 1).is_some_and(|c| c.is_ascii_alphanumeric()) =>
+                {}
+                // an earlier answer, $n by number and _ for the last one, swapped for its value by recall
+                '$' | '_' =>
+                {
+                    place_multiplier(&mut func, &find_word);
+                    if neg
+                    {
+                        func.push(Num(n1.clone()));
+                        func.push(Str("*".to_string()));
+                        neg = false;
+                    }
+                    let n: String = if c == '$'
+                    {
+                        chars[i + 1..]
+                            .iter()
+                            .take_while(|c| c.is_ascii_digit())
+                            .collect()
+                    }
+                    else
+                    {
+                        String::new()
+                    };
+                    if c == '$' && n.is_empty()
+                    {
+                        return Err(());
+                    }
+                    i += n.len();
+                    func.push(Str(format!("{}{}", c, n)));
+                }
                 ',' if i != 0 && i + 1 != chars.len() => func.push(Str(','.to_string())),
                 '%' if i != 0 && i + 1 != chars.len() => func.push(Str('%'.to_string())),
                 _ => (),
@@ -491,6 +554,11 @@ pub fn get_func(input: &str, prec: u32) -> Result<Vec<NumStr>, ()>
         }
         i += 1;
     }
+    if !word.is_empty() && !is_func(&word)
+    {
+        place_multiplier(&mut func, &false);
+        func.push(Str(word));
+    }
     func.extend(vec![Str(")".to_string()); count as usize]);
     if !exp.is_empty()
     {
@@ -532,7 +600,7 @@ fn place_multiplier(func: &mut Vec<NumStr>, find_word: &bool)
 {
     if let Some(Str(s)) = func.last()
     {
-        if !find_word && (s == ")" || s == "x" || s == "y" || s == "]" || s == "}")
+        if !find_word && (s == ")" || s == "]" || s == "}" || is_var(s))
         {
             func.push(Str('*'.to_string()))
         }
@@ -546,6 +614,155 @@ fn place_multiplier(func: &mut Vec<NumStr>, find_word: &bool)
         func.push(Str('*'.to_string()))
     }
 }
+// a 0x, 0b, 0o or base#digits literal starting at i, its base and the range of its digits,
+// base#digits is only read where a new operand starts so x^2#1 is still two graphs
+pub fn radix_literal(chars: &[char], i: usize) -> Option<(u32, usize, usize)>
+{
+    if i > 0 && (chars[i - 1].is_alphanumeric() || matches!(chars[i - 1], '.' | '_'))
+    {
+        return None;
+    }
+    let (radix, start) = match (chars.get(i), chars.get(i + 1))
+    {
+        (Some('0'), Some('x')) => (16, i + 2),
+        (Some('0'), Some('b')) => (2, i + 2),
+        (Some('0'), Some('o')) => (8, i + 2),
+        _ if i > 0 && !matches!(chars[i - 1], ' ' | '(' | '{' | ',' | '=') => return None,
+        _ =>
+        {
+            let n = chars
+                .get(i..)?
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .count();
+            let radix = chars[i..i + n]
+                .iter()
+                .collect::<String>()
+                .parse::<u32>()
+                .ok()?;
+            if chars.get(i + n) != Some(&'#') || !(2..=36).contains(&radix)
+            {
+                return None;
+            }
+            (radix, i + n + 1)
+        }
+    };
+    if !chars.get(start).is_some_and(|c| c.is_digit(radix))
+    {
+        return None;
+    }
+    // letters and digits run to the end of the literal, radix_value rejects any the base lacks
+    let digits = |j: usize| {
+        chars[j.min(chars.len())..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric() && !(radix == 16 && **c == 'p'))
+            .count()
+    };
+    let mut end = start + digits(start);
+    if chars.get(end) == Some(&'.') && digits(end + 1) != 0
+    {
+        end += 1 + digits(end + 1);
+    }
+    // hex can scale by a power of 2, 0x1.8p3 is 12
+    if radix == 16 && chars.get(end) == Some(&'p')
+    {
+        let sign = matches!(chars.get(end + 1), Some('+' | '-')) as usize;
+        end += 1 + sign;
+        end += chars[end.min(chars.len())..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric())
+            .count();
+    }
+    Some((radix, start, end))
+}
+fn radix_value(
+    chars: &[char],
+    (radix, start, end): (u32, usize, usize),
+    prec: u32,
+) -> Result<Complex, ()>
+{
+    let mut mantissa = Integer::new();
+    let mut places = 0;
+    let mut point = false;
+    let mut exp = 0;
+    for (j, c) in chars[start..end].iter().enumerate()
+    {
+        match c
+        {
+            '.' => point = true,
+            'p' =>
+            {
+                exp = chars[start + j + 1..end]
+                    .iter()
+                    .collect::<String>()
+                    .parse::<i32>()
+                    .map_err(|_| ())?;
+                break;
+            }
+            _ =>
+            {
+                mantissa = mantissa * radix + c.to_digit(radix).ok_or(())?;
+                if point
+                {
+                    places += 1;
+                }
+            }
+        }
+    }
+    Ok(Complex::with_val(
+        prec,
+        Float::with_val(prec, mantissa) / Float::with_val(prec, radix).pow(places)
+            * Float::with_val(prec, 2).pow(exp),
+    ))
+}
+// "x to hex" shows x in another base, the part before "to" and that base or why it isn't one
+pub fn conversion(input: &str) -> Option<Result<(&str, usize), String>>
+{
+    let (expr, target) = input.rsplit_once(" to ")?;
+    let target = target.trim();
+    let base = match target
+    {
+        "bin" | "binary" => Some(2),
+        "oct" | "octal" => Some(8),
+        "dec" | "decimal" => Some(10),
+        "hex" | "hexadecimal" => Some(16),
+        b => b.strip_prefix("base ").and_then(|n| n.trim().parse().ok()),
+    };
+    Some(match base.filter(|b| (2..=36).contains(b))
+    {
+        Some(b) => Ok((expr.trim(), b)),
+        None => Err(format!(
+            "unknown base {}, use bin, oct, dec, hex or base 2 to 36",
+            target
+        )),
+    })
+}
+// graphs are split on #, except where it is part of a base#digits literal
+pub fn graphs(input: &str) -> Vec<String>
+{
+    let chars = input.chars().collect::<Vec<char>>();
+    let mut graphs = vec![String::new()];
+    let mut i = 0;
+    while i < chars.len()
+    {
+        if let Some((_, _, end)) = radix_literal(&chars, i)
+        {
+            graphs.last_mut().unwrap().extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        if chars[i] == '#'
+        {
+            graphs.push(String::new());
+        }
+        else
+        {
+            graphs.last_mut().unwrap().push(chars[i]);
+        }
+        i += 1;
+    }
+    graphs
+}
 pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> String
 {
     let chars = input
@@ -554,20 +771,8 @@ pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> St
         .chars()
         .collect::<Vec<char>>();
     let mut output = String::new();
-    let (
-        mut not_pushed,
-        mut start,
-        mut c,
-        mut k,
-        mut j,
-        mut v,
-        mut temp,
-        mut split,
-        mut value,
-        mut o,
-    );
+    let (mut not_pushed, mut c, mut j);
     let mut i = 0;
-    let mut commas: Vec<usize>;
     let mut stack_end = Vec::new();
     let mut stack_start = Vec::new();
     for c in &chars
@@ -612,7 +817,8 @@ pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> St
         input.push(top);
     }
     let chars = input.chars().collect::<Vec<char>>();
-    let mut count;
+    let mut scopes = lambda_scopes(&chars);
+    scopes.extend(index_scopes(&chars));
     while i < chars.len()
     {
         c = chars[i];
@@ -622,6 +828,13 @@ pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> St
             i += 1;
             continue;
         }
+        // letters that are digits of a literal are not variables
+        if let Some((_, _, end)) = radix_literal(&chars, i)
+        {
+            output.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
         if !c.is_alphabetic()
         {
             output.push(c);
@@ -631,151 +844,30 @@ pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> St
         for var in vars
         {
             j = i;
-            if var[0].contains('(')
-                && input.contains('(')
-                && i + var[0].len() - 1 <= input.len()
-                && input[i..i + var[0].len() - 1].split('(').next() == var[0].split('(').next()
+            if let Some((name, _)) = var[0].split_once('(')
             {
-                o = i;
-                count = 0;
-                for (f, c) in chars[i..].iter().enumerate()
+                // the call is left for do_math to bind its arguments, only marked as a user function
+                let len = name.chars().count();
+                if chars
+                    .get(i..i + len)
+                    .is_some_and(|w| w.iter().copied().eq(name.chars()))
+                    && chars.get(i + len) == Some(&'(')
+                    && (i == 0 || !is_name(chars[i - 1]))
                 {
-                    if *c == '('
-                    {
-                        count += 1;
-                    }
-                    else if *c == ')'
-                    {
-                        count -= 1;
-                        if count == 0
-                        {
-                            i += f;
-                            break;
-                        }
-                    }
-                }
-                if i == j
-                {
-                    i = input.len() - 1
-                }
-                if input[j..i + 1] == var[0]
-                {
-                    if let Some(n) = dont_do
-                    {
-                        if n == var[0]
-                        {
-                            return String::new();
-                        }
-                    }
+                    output.push('@');
+                    output.push_str(name);
+                    i += len - 1;
                     not_pushed = false;
-                    output.push('(');
-                    output.push_str(&input_var(&var[1], vars, Some(&var[0])));
-                    output.push(')');
-                }
-                else if j == 0 || !chars[j - 1].is_ascii_alphabetic()
-                {
-                    k = 0;
-                    for (f, c) in chars[j + 2..].iter().enumerate()
-                    {
-                        if *c == ')'
-                        {
-                            k = f + j + 3;
-                            break;
-                        }
-                        else if f + j + 3 == chars.len()
-                        {
-                            k = f + j + 4;
-                            break;
-                        }
-                    }
-                    if k == 0
-                    {
-                        continue;
-                    }
-                    v = var[0].chars().collect::<Vec<char>>();
-                    if let Some(n) = dont_do
-                    {
-                        if n == var[0]
-                        {
-                            return String::new();
-                        }
-                    }
-                    if input.contains(',') && var[0].contains(',') && chars.len() > 4
-                    {
-                        not_pushed = false;
-                        output.push('(');
-                        temp = &input[j + var[0].split('(').next().unwrap().len() + 1..i + 1];
-                        if temp.ends_with(')')
-                        {
-                            temp = &temp[..temp.len() - 1];
-                        }
-                        commas = Vec::new();
-                        count = 0;
-                        for (f, c) in temp.chars().enumerate()
-                        {
-                            if c == '(' || c == '{' || c == '['
-                            {
-                                count += 1;
-                            }
-                            else if c == ')' || c == '}' || c == ']'
-                            {
-                                count -= 1;
-                            }
-                            else if c == ',' && count == 0
-                            {
-                                commas.push(f);
-                            }
-                        }
-                        if commas.len() == var[0].matches(',').count()
-                        {
-                            start = 0;
-                            split = Vec::new();
-                            for end in commas
-                            {
-                                split.push(&temp[start..end]);
-                                start = end + 1;
-                            }
-                            split.push(&temp[start..]);
-                            value = input_var(&var[1], vars, Some(&var[0])).clone();
-                            for i in 0..split.len()
-                            {
-                                value = value.replace(
-                                    v[v.len()
-                                        - 2 * (i as i32 - split.len() as i32).unsigned_abs()
-                                            as usize],
-                                    &format!("({})", input_var(split[i], vars, Some(&var[0]))),
-                                );
-                            }
-                            output.push_str(&value);
-                            output.push(')');
-                        }
-                    }
-                    else
-                    {
-                        not_pushed = false;
-                        output.push('(');
-                        temp = &input[j + var[0].split('(').next().unwrap().len() + 1..i + 1];
-                        if temp.ends_with(')')
-                        {
-                            temp = &temp[..temp.len() - 1];
-                        }
-                        output.push_str(&input_var(&var[1], vars, Some(&var[0])).replace(
-                            v[v.len() - 2],
-                            &format!("({})", input_var(temp, vars, Some(&var[0]))),
-                        ));
-                        output.push(')');
-                    }
-                }
-                else
-                {
-                    i = o;
+                    break;
                 }
             }
             else if !(i + var[0].len() > input.len() || input[i..i + var[0].len()] != var[0])
                 && (i + 1 == chars.len() || chars[i + 1] != '(')
-                && (j == 0 || !chars[j - 1].is_ascii_alphabetic())
-                && (var[0].len() - 1 + i == chars.len() - 1
-                    || !chars[i + 1 + var[0].len() - 1].is_ascii_alphabetic())
+                && (j == 0 || !is_name(chars[j - 1]))
+                && (var[0].len() - 1 + i == chars.len() - 1 || !is_name(chars[i + var[0].len()]))
+                && !scopes
+                    .iter()
+                    .any(|(scope, params)| scope.contains(&i) && params.contains(&var[0]))
             {
                 if let Some(n) = dont_do
                 {
@@ -806,10 +898,247 @@ pub fn input_var(input: &str, vars: &[[String; 2]], dont_do: Option<&str>) -> St
         output
     }
 }
-fn is_func(word: &str) -> bool
+// where each lambda's parameters are in scope, from the parameters to the end of the argument
+// the lambda is given as, so they aren't replaced by variables of the same name
+fn lambda_scopes(chars: &[char]) -> Vec<(Range<usize>, Vec<String>)>
+{
+    let mut scopes = Vec::new();
+    for arrow in (1..chars.len()).filter(|&k| chars[k - 1] == '-' && chars[k] == '>')
+    {
+        let mut start = arrow - 1;
+        while start > 0 && chars[start - 1] == ' '
+        {
+            start -= 1;
+        }
+        let params: String = if start > 0 && chars[start - 1] == ')'
+        {
+            let Some(open) = chars[..start - 1].iter().rposition(|c| *c == '(')
+            else
+            {
+                continue;
+            };
+            let params = chars[open + 1..start - 1].iter().collect();
+            start = open;
+            params
+        }
+        else
+        {
+            let word = chars[..start]
+                .iter()
+                .rev()
+                .take_while(|c| c.is_ascii_alphanumeric())
+                .count();
+            start -= word;
+            chars[start..start + word].iter().collect()
+        };
+        let mut count = 0;
+        let end = arrow
+            + chars[arrow..]
+                .iter()
+                .position(|c| {
+                    match c
+                    {
+                        '(' | '{' | '[' => count += 1,
+                        ')' | '}' | ']' => count -= 1,
+                        _ => (),
+                    }
+                    count < 0 || (count == 0 && *c == ',')
+                })
+                .unwrap_or(chars.len() - arrow);
+        scopes.push((
+            start..end,
+            params.split(',').map(|p| p.trim().to_string()).collect(),
+        ));
+    }
+    scopes
+}
+// the index variable of sum, product and table is bound for the whole call like in free_vars
+fn index_scopes(chars: &[char]) -> Vec<(Range<usize>, Vec<String>)>
+{
+    let mut scopes = Vec::new();
+    for open in (1..chars.len()).filter(|&k| chars[k] == '(')
+    {
+        let len = chars[..open]
+            .iter()
+            .rev()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .count();
+        let name: String = chars[open - len..open].iter().collect();
+        if !matches!(
+            name.as_str(),
+            "sum" | "summation" | "product" | "prod" | "table"
+        )
+        {
+            continue;
+        }
+        let mut count = 0;
+        let mut commas = Vec::new();
+        let mut end = chars.len();
+        for (k, c) in chars.iter().enumerate().skip(open)
+        {
+            match c
+            {
+                '(' | '{' | '[' => count += 1,
+                ')' | '}' | ']' => count -= 1,
+                ',' if count == 1 => commas.push(k),
+                _ => (),
+            }
+            if count == 0
+            {
+                end = k;
+                break;
+            }
+        }
+        if commas.len() > 1
+        {
+            let var: String = chars[commas[0] + 1..commas[1]].iter().collect();
+            scopes.push((open..end, vec![var.trim().to_string()]));
+        }
+    }
+    scopes
+}
+// a letter or an underscore, either can sit next to a variable's name in a longer one
+fn is_name(c: char) -> bool
+{
+    c.is_ascii_alphabetic() || c == '_'
+}
+fn is_var(word: &str) -> bool
+{
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic()) && !is_func(word)
+}
+// whether the letters from here on spell out a function, so the x in exp or the z in zeta stay in the word
+fn continues_func(word: &str, rest: &[char]) -> bool
+{
+    let run: String = word
+        .chars()
+        .chain(rest.iter().take_while(|c| c.is_ascii_alphabetic()).copied())
+        .collect();
+    functions()
+        .iter()
+        .any(|f| f.len() > word.len() && run.starts_with(f))
+}
+// identifiers left unbound in a parsed input, the index variable of sum and product is bound inside it
+pub fn free_vars(func: &[NumStr]) -> Vec<String>
+{
+    let mut bound = Vec::new();
+    for (i, n) in func.iter().enumerate()
+    {
+        if let Str(s) = n
+        {
+            if s == "sum" || s == "summation" || s == "product" || s == "prod" || s == "table"
+            {
+                let mut count = 0;
+                let mut var = None;
+                for (j, t) in func.iter().enumerate().skip(i + 1)
+                {
+                    if t.str_is("(") || t.str_is("{")
+                    {
+                        count += 1;
+                    }
+                    else if t.str_is(")") || t.str_is("}")
+                    {
+                        count -= 1;
+                        if count == 0
+                        {
+                            if let Some(Str(v)) = var.and_then(|v| func.get(v))
+                            {
+                                bound.push((v.clone(), i, j));
+                            }
+                            break;
+                        }
+                    }
+                    else if t.str_is(",") && count == 1 && var.is_none()
+                    {
+                        var = Some(j + 1);
+                    }
+                }
+            }
+        }
+    }
+    // a lambda's parameters are bound until the end of the argument it's in
+    for (k, n) in func.iter().enumerate()
+    {
+        if n.str_is("->")
+        {
+            let mut count = 0;
+            let end = k + func[k..]
+                .iter()
+                .position(|t| {
+                    if t.str_is("(") || t.str_is("{")
+                    {
+                        count += 1;
+                    }
+                    else if t.str_is(")") || t.str_is("}")
+                    {
+                        count -= 1;
+                    }
+                    count < 0 || (count == 0 && t.str_is(","))
+                })
+                .unwrap_or(func.len() - k);
+            let start = if k > 0 && func[k - 1].str_is(")")
+            {
+                func[..k - 1]
+                    .iter()
+                    .rposition(|t| t.str_is("("))
+                    .unwrap_or(0)
+            }
+            else
+            {
+                k.saturating_sub(1)
+            };
+            for t in &func[start..k]
+            {
+                if let Str(v) = t
+                {
+                    bound.push((v.clone(), start, end));
+                }
+            }
+        }
+    }
+    let mut vars: Vec<String> = Vec::new();
+    for (i, n) in func.iter().enumerate()
+    {
+        if let Str(s) = n
+        {
+            if is_var(s)
+                && !vars.contains(s)
+                && !bound
+                    .iter()
+                    .any(|(v, start, end)| v == s && (*start..=*end).contains(&i))
+            {
+                vars.push(s.clone());
+            }
+        }
+    }
+    vars
+}
+// only x, y and z left unbound means the input is graphed rather than evaluated
+pub fn is_graph(func: &[NumStr]) -> bool
+{
+    let vars = free_vars(func);
+    !vars.is_empty() && vars.iter().all(|v| v == "x" || v == "y" || v == "z")
+}
+pub fn is_equation(input: &str) -> bool
 {
-    let functions: HashSet<_> = [
+    split_equation(input).is_some()
+}
+pub fn is_func(word: &str) -> bool
+{
+    functions().contains(word)
+}
+pub fn functions() -> HashSet<&'static str>
+{
+    [
         "sum",
+        "ans",
+        "if",
+        "piecewise",
+        "map",
+        "filter",
+        "reduce",
+        "fold",
+        "zipwith",
+        "table",
         "product",
         "prod",
         "summation",
@@ -832,6 +1161,21 @@ fn is_func(word: &str) -> bool
         "trace",
         "det",
         "determinant",
+        "linsolve",
+        "eig",
+        "eigvals",
+        "lu",
+        "qr",
+        "svd",
+        "cholesky",
+        "chol",
+        "rank",
+        "nullspace",
+        "null",
+        "rref",
+        "expm",
+        "logm",
+        "sqrtm",
         "part",
         "norm",
         "abs",
@@ -934,13 +1278,18 @@ fn is_func(word: &str) -> bool
         "erfc",
         "ai",
         "digamma",
-        "zeta
-",
+        "zeta",
+        "polyval",
+        "polyroots",
+        "polyfit",
+        "polymul",
+        "polydiv",
+        "polyder",
+        "polyint",
     ]
     .iter()
     .cloned()
-    .collect();
-    functions.contains(word)
+    .collect()
 }
 pub fn get_vars(prec: u32) -> Vec<[String; 2]>
 {
@@ -966,4 +1315,110 @@ pub fn get_vars(prec: u32) -> Vec<[String; 2]>
         ["pi".to_string(), pi.to_string()],
         ["tau".to_string(), tau.to_string()],
     ]
+}
+// a user function kept parsed, its parameters are bound when it's called instead of being pasted
+// into its text, so they can't pick up a variable of the same name
+#[derive(Clone)]
+pub struct Function
+{
+    pub name: String,
+    pub params: Vec<String>,
+    pub defaults: Vec<Option<Vec<NumStr>>>,
+    pub body: Vec<NumStr>,
+}
+pub fn user_functions(vars: &[[String; 2]], prec: u32) -> Vec<Function>
+{
+    let mut funcs = Vec::new();
+    for v in vars
+    {
+        let Some((name, params)) = v[0].split_once('(')
+        else
+        {
+            continue;
+        };
+        let params = params.strip_suffix(')').unwrap_or(params);
+        let (names, defaults): (Vec<String>, Vec<Option<&str>>) = params
+            .split(',')
+            .map(|p| match p.split_once('=')
+            {
+                Some((p, d)) => (p.trim().to_string(), Some(d)),
+                None => (p.trim().to_string(), None),
+            })
+            .unzip();
+        // the parameters shadow any variable with their name, in the body and in the defaults
+        let globals: Vec<[String; 2]> = vars
+            .iter()
+            .filter(|g| !names.contains(&g[0]))
+            .cloned()
+            .collect();
+        let defaults = defaults
+            .into_iter()
+            .map(|d| d.and_then(|d| get_func(&input_var(d, &globals, None), prec).ok()))
+            .collect();
+        if let Ok(body) = get_func(&input_var(&v[1], &globals, None), prec)
+        {
+            funcs.push(Function {
+                name: name.to_string(),
+                params: names,
+                defaults,
+                body,
+            });
+        }
+    }
+    funcs
+}
+// a default that uses its own parameter or one after it, which has no value yet when the default
+// is worked out, as the parameter and the one it uses
+pub fn forward_default(l: &str, prec: u32) -> Option<(String, String)>
+{
+    let params = l.split_once('(')?.1;
+    let params = params.strip_suffix(')').unwrap_or(params);
+    let (names, defaults): (Vec<&str>, Vec<Option<&str>>) = params
+        .split(',')
+        .map(|p| match p.split_once('=')
+        {
+            Some((p, d)) => (p.trim(), Some(d)),
+            None => (p.trim(), None),
+        })
+        .unzip();
+    for (k, d) in defaults.into_iter().enumerate()
+    {
+        let Some(func) = d.and_then(|d| get_func(&input_var(d, &[], None), prec).ok())
+        else
+        {
+            continue;
+        };
+        if let Some(v) = free_vars(&func)
+            .into_iter()
+            .find(|v| names[k..].contains(&v.as_str()))
+        {
+            return Some((names[k].to_string(), v));
+        }
+    }
+    None
+}
+// the = that separates the sides, not one inside f(x,n=2) or part of == <= >= !=
+pub fn split_equation(input: &str) -> Option<(&str, &str)>
+{
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut count = 0;
+    for (k, &(i, c)) in chars.iter().enumerate()
+    {
+        match c
+        {
+            '(' | '{' | '[' => count += 1,
+            ')' | '}' | ']' => count -= 1,
+            '=' if count == 0 =>
+            {
+                let prev = k.checked_sub(1).map(|k| chars[k].1);
+                let next = chars.get(k + 1).map(|c| c.1);
+                if !matches!(prev, Some('=' | '<' | '>' | '!')) && next != Some('=')
+                {
+                    return Some((&input[..i], &input[i + 1..]));
+                }
+            }
+            _ => (),
+        }
+    }
+    None
 }
\ No newline at end of file
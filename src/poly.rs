@@ -0,0 +1,898 @@
+use crate::{
+    complex::{
+        NumStr,
+        NumStr::{Matrix, Num, Str, Vector},
+    },
+    math::{do_math, solve},
+    parse::{free_vars, get_func, input_var, user_functions},
+    print::get_output,
+    Options,
+};
+use rug::{float::Constant::Pi, ops::Pow, Assign, Complex, Float, Integer, Rational};
+use std::cmp::Ordering;
+// coefficients are stored highest degree first, so {1,0,-1} is x^2-1
+fn trim(a: &[Complex]) -> Vec<Complex>
+{
+    match a.iter().position(|n| !n.eq0())
+    {
+        Some(n) => a[n..].to_vec(),
+        None => vec![Complex::new(a[0].prec())],
+    }
+}
+pub fn polyval(a: &[Complex], x: &Complex) -> Complex
+{
+    let mut n = Complex::new(x.prec());
+    for c in a
+    {
+        n = n * x + c;
+    }
+    n
+}
+pub fn polymul(a: &[Complex], b: &[Complex]) -> Result<Vec<Complex>, ()>
+{
+    if a.is_empty() || b.is_empty()
+    {
+        return Err(());
+    }
+    let mut c = vec![Complex::new(a[0].prec()); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate()
+    {
+        for (j, y) in b.iter().enumerate()
+        {
+            c[i + j] += x.clone() * y;
+        }
+    }
+    Ok(c)
+}
+// returns the quotient and remainder padded to the same length
+pub fn polydiv(a: &[Complex], b: &[Complex]) -> Result<Vec<Vec<Complex>>, ()>
+{
+    if a.is_empty() || b.is_empty()
+    {
+        return Err(());
+    }
+    let b = trim(b);
+    if b[0].eq0()
+    {
+        return Err(());
+    }
+    let mut r = trim(a);
+    if r.len() < b.len()
+    {
+        let q = vec![Complex::new(r[0].prec()); r.len()];
+        return Ok(vec![q, r]);
+    }
+    let mut q = Vec::new();
+    for i in 0..=r.len() - b.len()
+    {
+        let n = r[i].clone() / &b[0];
+        for (j, c) in b.iter().enumerate()
+        {
+            r[i + j] -= n.clone() * c;
+        }
+        q.push(n);
+    }
+    let mut r = r[r.len() - b.len() + 1..].to_vec();
+    if r.is_empty()
+    {
+        r.push(Complex::new(q[0].prec()));
+    }
+    let len = q.len().max(r.len());
+    for p in [&mut q, &mut r]
+    {
+        while p.len() < len
+        {
+            p.insert(0, Complex::new(p[0].prec()));
+        }
+    }
+    Ok(vec![q, r])
+}
+pub fn polyder(a: &[Complex]) -> Result<Vec<Complex>, ()>
+{
+    match a.len()
+    {
+        0 => Err(()),
+        1 => Ok(vec![Complex::new(a[0].prec())]),
+        n => Ok(a[..n - 1]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| c.clone() * (n - 1 - i) as u64)
+            .collect()),
+    }
+}
+pub fn polyint(a: &[Complex], c: Complex) -> Vec<Complex>
+{
+    let mut b: Vec<Complex> = a
+        .iter()
+        .enumerate()
+        .map(|(i, n)| n.clone() / (a.len() - i) as u64)
+        .collect();
+    b.push(c);
+    b
+}
+// least squares fit via the normal equations
+pub fn polyfit(x: &[Complex], y: &[Complex], deg: usize) -> Result<Vec<Complex>, ()>
+{
+    if x.len() != y.len() || x.len() <= deg
+    {
+        return Err(());
+    }
+    let prec = x[0].prec();
+    let rows: Vec<Vec<Complex>> = x
+        .iter()
+        .map(|x| (0..=deg).rev().map(|p| x.clone().pow(p as u32)).collect())
+        .collect();
+    let mut a = vec![vec![Complex::new(prec); deg + 1]; deg + 1];
+    let mut b = vec![Complex::new(prec); deg + 1];
+    for (row, y) in rows.iter().zip(y)
+    {
+        for i in 0..=deg
+        {
+            for j in 0..=deg
+            {
+                a[i][j] += row[i].clone() * &row[j];
+            }
+            b[i] += row[i].clone() * y;
+        }
+    }
+    solve(a, b)
+}
+// aberth iteration started on a circle enclosing every root
+pub fn polyroots(a: &[Complex]) -> Result<Vec<Complex>, ()>
+{
+    if a.is_empty()
+        || a.iter()
+            .any(|c| !c.real().is_finite() || !c.imag().is_finite())
+    {
+        return Err(());
+    }
+    let a = trim(a);
+    let n = a.len() - 1;
+    if n == 0
+    {
+        return Err(());
+    }
+    let prec = a[0].prec();
+    let tol = Float::with_val(prec.0, 1) >> (prec.0 / 2);
+    let mut radius = Float::new(prec.0);
+    for c in &a[1..]
+    {
+        let r = Float::with_val(prec.0, (c.clone() / &a[0]).abs().real());
+        if r > radius
+        {
+            radius = r;
+        }
+    }
+    radius += 1;
+    let der = polyder(&a)?;
+    let mut z: Vec<Complex> = (0..n)
+        .map(|k| {
+            let t: Float = Float::with_val(prec.0, Pi) * 2 * k as u64 / n as u64 + 0.4;
+            Complex::with_val(prec, (t.clone().cos(), t.sin())) * &radius
+        })
+        .collect();
+    for _ in 0..500 + prec.0
+    {
+        let mut done = true;
+        for k in 0..n
+        {
+            let w = polyval(&a, &z[k]) / polyval(&der, &z[k]);
+            let mut s = Complex::new(prec);
+            for (j, zj) in z.iter().enumerate()
+            {
+                if j != k
+                {
+                    s += (z[k].clone() - zj).recip();
+                }
+            }
+            let step: Complex = w.clone() / (1 - w * s);
+            if !step.real().is_finite() || !step.imag().is_finite()
+            {
+                continue;
+            }
+            let size = Float::with_val(prec.0, z[k].abs_ref()) + 1;
+            if Float::with_val(prec.0, step.abs_ref()) > tol.clone() * size
+            {
+                done = false;
+            }
+            z[k] -= step;
+        }
+        if done
+        {
+            break;
+        }
+    }
+    for r in z.iter_mut()
+    {
+        let size = tol.clone() * (Float::with_val(prec.0, r.abs_ref()) + 1);
+        if r.imag().clone().abs() < size
+        {
+            r.mut_imag().assign(0);
+        }
+        if r.real().clone().abs() < size
+        {
+            r.mut_real().assign(0);
+        }
+    }
+    z.sort_by(|a, b| {
+        a.real()
+            .partial_cmp(b.real())
+            .unwrap_or(Ordering::Equal)
+            .then(a.imag().partial_cmp(b.imag()).unwrap_or(Ordering::Equal))
+    });
+    Ok(z)
+}
+// coefficient strings are passed without their sign
+fn notation(coefs: Vec<(bool, String)>) -> String
+{
+    let deg = coefs.len() - 1;
+    let mut out = String::new();
+    for (k, (neg, c)) in coefs.into_iter().enumerate()
+    {
+        if c == "0"
+        {
+            continue;
+        }
+        let p = deg - k;
+        if neg
+        {
+            out.push('-');
+        }
+        else if !out.is_empty()
+        {
+            out.push('+');
+        }
+        if c != "1" || p == 0
+        {
+            out.push_str(&c);
+        }
+        match p
+        {
+            0 => (),
+            1 => out.push('x'),
+            _ => out.push_str(&format!("x^{}", p)),
+        }
+    }
+    if out.is_empty()
+    {
+        "0".to_string()
+    }
+    else
+    {
+        out
+    }
+}
+pub fn poly_string(a: &[Complex], options: Options) -> String
+{
+    notation(
+        trim(a)
+            .iter()
+            .map(|c| {
+                let out = get_output(&options, c);
+                let color = if options.color { "\x1b[0m" } else { "" };
+                if out.1.is_empty()
+                {
+                    match out.0.strip_prefix('-')
+                    {
+                        Some(s) => (true, s.to_string()),
+                        None => (false, out.0),
+                    }
+                }
+                else if out.0.is_empty()
+                {
+                    (false, format!("({}{})", out.1, color))
+                }
+                else
+                {
+                    (false, format!("({}{}{})", out.0, out.1, color))
+                }
+            })
+            .collect(),
+    )
+}
+fn rational_string(a: &[Rational]) -> String
+{
+    notation(
+        a.iter()
+            .map(|c| {
+                (
+                    *c < 0,
+                    if c.is_integer()
+                    {
+                        c.numer().clone().abs().to_string()
+                    }
+                    else
+                    {
+                        format!("({})", c.clone().abs())
+                    },
+                )
+            })
+            .collect(),
+    )
+}
+// recovers the coefficients of an expression in x from its values at 0,1,2...
+// via newton's forward differences, only works if those values are integers
+fn coefficients(input: &str, vars: &[[String; 2]], options: Options) -> Option<Vec<Rational>>
+{
+    let func = get_func(&input_var(input, vars, None), options.prec).ok()?;
+    let funcs = user_functions(vars, options.prec);
+    let points = 24;
+    let mut diff = Vec::new();
+    for x in 0..points
+    {
+        let n = do_math(
+            func.iter()
+                .map(|i| match i
+                {
+                    Str(s) if s == "x" => Num(Complex::with_val(options.prec, x)),
+                    _ => i.clone(),
+                })
+                .collect(),
+            options.deg,
+            options.prec,
+            &funcs,
+        )
+        .ok()?
+        .num()
+        .ok()?;
+        let tol = Float::with_val(options.prec, 1) >> (options.prec / 2);
+        let int = n.real().clone().round();
+        if n.imag().clone().abs() > tol
+            || (n.real().clone() - &int).abs() > tol * (1 + int.clone().abs())
+        {
+            return None;
+        }
+        diff.push(int.to_integer()?);
+    }
+    // forward differences at 0
+    let mut d = Vec::new();
+    while !diff.is_empty()
+    {
+        d.push(diff[0].clone());
+        diff = diff
+            .windows(2)
+            .map(|w| Integer::from(&w[1] - &w[0]))
+            .collect();
+    }
+    let deg = d.iter().rposition(|n| *n != 0).unwrap_or(0);
+    if deg + 3 >= points
+    {
+        return None;
+    }
+    // sum of d_k*x(x-1)...(x-k+1)/k!, lowest degree first
+    let mut coefs = vec![Rational::new(); deg + 1];
+    let mut basis = vec![Rational::from(1)];
+    let mut fact = Integer::from(1);
+    for (k, n) in d.iter().take(deg + 1).enumerate()
+    {
+        if k != 0
+        {
+            fact *= k;
+            let mut next = vec![Rational::new(); basis.len() + 1];
+            for (i, b) in basis.iter().enumerate()
+            {
+                next[i + 1] += b;
+                next[i] -= Rational::from(b * (k - 1) as u32);
+            }
+            basis = next;
+        }
+        for (i, b) in basis.iter().enumerate()
+        {
+            coefs[i] += Rational::from(b * n) / &fact;
+        }
+    }
+    coefs.reverse();
+    Some(coefs)
+}
+pub fn expand(input: &str, vars: &[[String; 2]], options: Options) -> Option<String>
+{
+    Some(rational_string(&coefficients(input, vars, options)?))
+}
+pub fn factor(input: &str, vars: &[[String; 2]], options: Options) -> Option<String>
+{
+    let coefs = coefficients(input, vars, options)?;
+    if coefs.iter().any(|c| !c.is_integer())
+    {
+        return None;
+    }
+    let mut a: Vec<Integer> = coefs.iter().map(|c| c.numer().clone()).collect();
+    let mut content = Integer::new();
+    for c in &a
+    {
+        content.gcd_mut(c);
+    }
+    if content == 0
+    {
+        return Some("0".to_string());
+    }
+    if a[0] < 0
+    {
+        content = -content;
+    }
+    for c in a.iter_mut()
+    {
+        *c /= &content;
+    }
+    let mut factors: Vec<(Vec<Integer>, usize)> = Vec::new();
+    let mut zeros = 0;
+    while a.len() > 1 && a[a.len() - 1] == 0
+    {
+        a.pop();
+        zeros += 1;
+    }
+    if zeros != 0
+    {
+        factors.push((vec![Integer::from(1), Integer::new()], zeros));
+    }
+    // rational root theorem, a root p/q gives the factor qx-p
+    'outer: while a.len() > 1
+    {
+        for q in divisors(&a[0])?
+        {
+            for p in divisors(&a[a.len() - 1])?
+            {
+                for p in [p.clone(), -p]
+                {
+                    if Integer::from(p.gcd_ref(&q)) != 1
+                    {
+                        continue;
+                    }
+                    let mut b = Vec::new();
+                    let mut carry = Integer::new();
+                    for c in &a[..a.len() - 1]
+                    {
+                        let n = Integer::from(c + &carry);
+                        if !n.is_divisible(&q)
+                        {
+                            break;
+                        }
+                        let n = n / &q;
+                        carry = Integer::from(&n * &p);
+                        b.push(n);
+                    }
+                    if b.len() != a.len() - 1 || Integer::from(&a[a.len() - 1] + &carry) != 0
+                    {
+                        continue;
+                    }
+                    a = b;
+                    let f = vec![q.clone(), -p];
+                    match factors.iter_mut().find(|(g, _)| *g == f)
+                    {
+                        Some((_, n)) => *n += 1,
+                        None => factors.push((f, 1)),
+                    }
+                    continue 'outer;
+                }
+            }
+        }
+        break;
+    }
+    // what is left has no linear factors, kronecker's method looks for the others
+    let whole = match split(&a)
+    {
+        Some(rest) =>
+        {
+            for f in rest.into_iter().filter(|f| f.len() > 1)
+            {
+                match factors.iter_mut().find(|(g, _)| *g == f)
+                {
+                    Some((_, n)) => *n += 1,
+                    None => factors.push((f, 1)),
+                }
+            }
+            true
+        }
+        None =>
+        {
+            factors.push((a, 1));
+            false
+        }
+    };
+    let mut out = match content.to_i32()
+    {
+        Some(1) => String::new(),
+        Some(-1) => "-".to_string(),
+        _ => content.to_string(),
+    };
+    let single = out.is_empty() && factors.len() == 1 && factors[0].1 == 1;
+    for (f, n) in factors
+    {
+        let s = rational_string(&f.into_iter().map(Rational::from).collect::<Vec<Rational>>());
+        if single
+        {
+            out.push_str(&s);
+        }
+        else if s == "x"
+        {
+            out.push('x');
+        }
+        else
+        {
+            out.push_str(&format!("({})", s));
+        }
+        if n > 1
+        {
+            out.push_str(&format!("^{}", n));
+        }
+    }
+    if out.is_empty() || out == "-"
+    {
+        out.push('1');
+    }
+    if !whole
+    {
+        out.push_str(" (could not fully factor)");
+    }
+    Some(out)
+}
+// the factors of a primitive polynomial over the integers, trying each degree up to half of its
+// own, none if that would mean trying too many candidates
+fn split(a: &[Integer]) -> Option<Vec<Vec<Integer>>>
+{
+    let n = a.len() - 1;
+    for d in 2..=n / 2
+    {
+        // a factor's value at x divides a's there, the points with the fewest divisors
+        // give the fewest candidates
+        let mut points: Vec<(i64, Vec<Integer>)> = (-8i64..=8)
+            .filter_map(|x| {
+                let v = value(a, x);
+                (v != 0).then(|| divisors(&v).map(|ds| (x, ds)))?
+            })
+            .collect();
+        points.sort_by_key(|(_, ds)| ds.len());
+        points.truncate(d + 1);
+        if points.len() != d + 1
+            || points
+                .iter()
+                .skip(1)
+                .try_fold(points[0].1.len(), |n, (_, ds)| n.checked_mul(2 * ds.len()))
+                .is_none_or(|n| n > 100_000)
+        {
+            return None;
+        }
+        let xs: Vec<i64> = points.iter().map(|(x, _)| *x).collect();
+        // each point picks a divisor and all but the first a sign, g and -g are the same factor
+        let mut choice = vec![0; d + 1];
+        loop
+        {
+            let values: Vec<Integer> = points
+                .iter()
+                .zip(&choice)
+                .map(|((_, ds), c)| {
+                    let v = ds[c / 2].clone();
+                    if c % 2 == 1
+                    {
+                        -v
+                    }
+                    else
+                    {
+                        v
+                    }
+                })
+                .collect();
+            if let Some(mut g) = interpolate(&xs, &values).filter(|g| g[0] != 0)
+            {
+                if g[0] < 0
+                {
+                    g.iter_mut().for_each(|c| *c = -c.clone());
+                }
+                if let Some(q) = divide(a, &g)
+                {
+                    // there is nothing of lower degree left, so g doesn't split further
+                    let mut factors = vec![g];
+                    factors.extend(split(&q)?);
+                    return Some(factors);
+                }
+            }
+            let mut i = 0;
+            loop
+            {
+                if i > d
+                {
+                    break;
+                }
+                choice[i] += if i == 0 { 2 } else { 1 };
+                if choice[i] < 2 * points[i].1.len()
+                {
+                    break;
+                }
+                choice[i] = 0;
+                i += 1;
+            }
+            if i > d
+            {
+                break;
+            }
+        }
+    }
+    Some(vec![a.to_vec()])
+}
+fn value(a: &[Integer], x: i64) -> Integer
+{
+    let mut n = Integer::new();
+    for c in a
+    {
+        n = n * x + c;
+    }
+    n
+}
+// the polynomial through (x,y) for each pair, if its coefficients are integers
+fn interpolate(x: &[i64], y: &[Integer]) -> Option<Vec<Integer>>
+{
+    // newton's divided differences, then the nested form multiplied out
+    let mut diff: Vec<Rational> = y.iter().map(Rational::from).collect();
+    for k in 1..x.len()
+    {
+        for i in (k..x.len()).rev()
+        {
+            diff[i] = Rational::from(&diff[i] - &diff[i - 1]) / (x[i] - x[i - k]);
+        }
+    }
+    let mut coefs = vec![diff[x.len() - 1].clone()];
+    for i in (0..x.len() - 1).rev()
+    {
+        // coefs*(x-x_i)+diff_i
+        let mut next = vec![Rational::new(); coefs.len() + 1];
+        for (j, c) in coefs.iter().enumerate()
+        {
+            next[j] += c;
+            next[j + 1] -= Rational::from(c * x[i]);
+        }
+        next[coefs.len()] += &diff[i];
+        coefs = next;
+    }
+    coefs
+        .into_iter()
+        .map(|c| c.is_integer().then(|| c.numer().clone()))
+        .collect()
+}
+// a/b when b divides it exactly
+fn divide(a: &[Integer], b: &[Integer]) -> Option<Vec<Integer>>
+{
+    let mut r = a.to_vec();
+    let mut q = Vec::new();
+    for i in 0..=a.len() - b.len()
+    {
+        if !r[i].is_divisible(&b[0])
+        {
+            return None;
+        }
+        let c = Integer::from(&r[i] / &b[0]);
+        for (j, d) in b.iter().enumerate()
+        {
+            r[i + j] -= Integer::from(&c * d);
+        }
+        q.push(c);
+    }
+    r.iter().all(|c| *c == 0).then_some(q)
+}
+fn divisors(n: &Integer) -> Option<Vec<Integer>>
+{
+    let n = n.clone().abs();
+    if n > 1_000_000_000_000u64
+    {
+        return None;
+    }
+    let n = n.to_u64()?;
+    let mut d = Vec::new();
+    let mut i = 1;
+    while i * i <= n
+    {
+        if n % i == 0
+        {
+            d.push(Integer::from(i));
+            if i * i != n
+            {
+                d.push(Integer::from(n / i));
+            }
+        }
+        i += 1;
+    }
+    d.sort();
+    Some(d)
+}
+// polynomial notation for results of the poly functions, polydiv gives its quotient and remainder
+pub fn poly_output(input: &str, num: &NumStr, options: Options) -> Option<String>
+{
+    if !["polymul", "polydiv", "polyder", "polyint", "polyfit"]
+        .iter()
+        .any(|p| input.trim_start().starts_with(p))
+    {
+        return None;
+    }
+    match num
+    {
+        Vector(v) => Some(poly_string(v, options)),
+        Matrix(m) if m.len() == 2 => Some(format!(
+            "{} remainder {}",
+            poly_string(&m[0], options),
+            poly_string(&m[1], options)
+        )),
+        _ => None,
+    }
+}
+// roots of l-r in its one unknown, exactly through polyroots when it is a polynomial
+// and otherwise by newton's method from a spread of starting points
+pub fn solve_equation(l: &str, r: &str, vars: &[[String; 2]], options: Options) -> Option<String>
+{
+    let prec = options.prec;
+    let func = get_func(&input_var(&format!("({})-({})", l, r), vars, None), prec).ok()?;
+    let funcs = user_functions(vars, prec);
+    let var = match free_vars(&func).as_slice()
+    {
+        [v] => v.clone(),
+        _ => return None,
+    };
+    let f = |x: &Complex| -> Option<Complex> {
+        let n = do_math(
+            func.iter()
+                .map(|i| match i
+                {
+                    Str(s) if *s == var => Num(x.clone()),
+                    _ => i.clone(),
+                })
+                .collect(),
+            options.deg,
+            prec,
+            &funcs,
+        )
+        .ok()?
+        .num()
+        .ok()?;
+        if n.real().is_finite() && n.imag().is_finite()
+        {
+            Some(n)
+        }
+        else
+        {
+            None
+        }
+    };
+    let tol = Float::with_val(prec, 1) >> (prec / 2);
+    let close = Float::with_val(prec, 1) >> (prec / 8);
+    let mut roots = Vec::new();
+    match polynomial(&f, prec)
+    {
+        Some(a) if a.len() == 1 =>
+        {
+            return Some(
+                if a[0].eq0()
+                {
+                    format!("any {}", var)
+                }
+                else
+                {
+                    "no solution".to_string()
+                },
+            )
+        }
+        Some(a) => roots = polyroots(&a).ok()?,
+        None =>
+        {
+            for s in [-100, -10, -3, -1, 0, 1, 3, 10, 100]
+            {
+                let mut x: Complex = Complex::with_val(prec, s) + 0.25;
+                for _ in 0..200
+                {
+                    let h = (Float::with_val(prec, x.abs_ref()) + 1) >> (prec / 4);
+                    let (Some(y), Some(a), Some(b)) =
+                        (f(&x), f(&(x.clone() + &h)), f(&(x.clone() - &h)))
+                    else
+                    {
+                        break;
+                    };
+                    let step: Complex = y * 2 * &h / (a - b);
+                    if !step.real().is_finite() || !step.imag().is_finite()
+                    {
+                        break;
+                    }
+                    x -= &step;
+                    if Float::with_val(prec, step.abs_ref())
+                        < tol.clone() * (Float::with_val(prec, x.abs_ref()) + 1)
+                    {
+                        break;
+                    }
+                }
+                let size: Float = Float::with_val(prec, x.abs_ref()) + 1;
+                // a small f alone isn't a root, exp(x) is tiny far to the left, the step newton
+                // would still take from there has to be as well
+                let h = size.clone() >> (prec / 4);
+                let root = match (f(&x), f(&(x.clone() + &h)), f(&(x.clone() - &h)))
+                {
+                    (Some(y), _, _) if y.eq0() => true,
+                    (Some(y), Some(a), Some(b)) =>
+                    {
+                        let step: Complex = y.clone() * 2 * &h / (a - b);
+                        Float::with_val(prec, y.abs_ref()) < tol
+                            && step.real().is_finite()
+                            && step.imag().is_finite()
+                            && Float::with_val(prec, step.abs_ref()) < close.clone() * &size
+                    }
+                    _ => false,
+                };
+                if root
+                {
+                    if x.imag().clone().abs() < tol.clone() * &size
+                    {
+                        x.mut_imag().assign(0);
+                    }
+                    roots.push(x);
+                }
+            }
+            if roots.is_empty()
+            {
+                return Some("no solution found".to_string());
+            }
+            roots.sort_by(|a, b| {
+                a.real()
+                    .partial_cmp(b.real())
+                    .unwrap()
+                    .then(a.imag().partial_cmp(b.imag()).unwrap())
+            });
+        }
+    }
+    roots.dedup_by(|a, b| {
+        Float::with_val(prec, (a.clone() - &*b).abs().real())
+            < close.clone() * (Float::with_val(prec, b.abs_ref()) + 1)
+    });
+    if roots.is_empty()
+    {
+        return None;
+    }
+    Some(
+        roots
+            .iter()
+            .map(|x| {
+                let a = get_output(&options, x);
+                format!(
+                    "{}={}{}{}",
+                    var,
+                    a.0,
+                    a.1,
+                    if options.color { "\x1b[0m" } else { "" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+    )
+}
+// coefficients of f if its forward differences at 0,1,2... vanish and the fit holds off the integers
+fn polynomial(f: &dyn Fn(&Complex) -> Option<Complex>, prec: u32) -> Option<Vec<Complex>>
+{
+    let points = 16;
+    let x: Vec<Complex> = (0..points).map(|n| Complex::with_val(prec, n)).collect();
+    let y = x.iter().map(f).collect::<Option<Vec<Complex>>>()?;
+    let mut scale = Float::with_val(prec, 1);
+    for n in &y
+    {
+        let a = Float::with_val(prec, n.abs_ref());
+        if a > scale
+        {
+            scale = a;
+        }
+    }
+    let tol = scale >> (prec / 2);
+    let mut diff = y.clone();
+    let mut deg = None;
+    for k in 0..points - 3usize
+    {
+        if diff
+            .iter()
+            .all(|n| Float::with_val(prec, n.abs_ref()) < tol.clone() << k)
+        {
+            deg = Some(k.saturating_sub(1));
+            break;
+        }
+        diff = diff.windows(2).map(|w| w[1].clone() - &w[0]).collect();
+    }
+    let deg = deg?;
+    let a = trim(&polyfit(&x[..=deg], &y[..=deg], deg).ok()?);
+    let p = Complex::with_val(prec, (0.3, 0.7));
+    let check = Float::with_val(prec, (polyval(&a, &p) - f(&p)?).abs().real());
+    if check > Float::with_val(prec, 1) >> (prec / 4)
+    {
+        return None;
+    }
+    Some(a)
+}
@@ -1,51 +1,101 @@
 use crate::{
+    assignable,
     complex::{
         NumStr,
-        NumStr::{Matrix, Num, Str, Vector},
+        NumStr::{Matrix, Num, Str, Tuple, Vector},
     },
     fraction::fraction,
     get_terminal_width,
-    math::{do_math, to_polar},
-    parse::get_func,
-    Options,
-    AngleType,
+    markup::markup,
+    math::{do_math, recall, recursion_limit, to_polar},
+    options::{option, Format, Notation},
+    parse::{
+        conversion, free_vars, functions, get_func, graphs, input_var, is_equation, is_func,
+        is_graph, split_equation, user_functions, Function,
+    },
+    poly::poly_output,
+    prompt,
+    symbolic::exact,
+    AngleType, Options, COMMANDS,
+};
+use rug::{
+    float::Constant::Pi,
+    ops::{CompleteRound, Pow},
+    Complex, Float, Integer,
 };
-use rug::{float::Constant::Pi, ops::CompleteRound, Complex, Float, Integer};
 use std::{cmp::Ordering, str::FromStr};
-pub fn print_answer(input: &str, func: Vec<NumStr>, options: Options)
+// prints an input's answer and hands it back so it can be kept
+pub fn print_answer(
+    input: &str,
+    func: Vec<NumStr>,
+    options: Options,
+    vars: &[[String; 2]],
+) -> Option<NumStr>
 {
-    if input.contains('#')
-        || input
-            .replace("exp", "")
-            .replace("max", "")
-            .replace("}x{", "")
-            .replace("]x[", "")
-            .contains('x')
-        || input.contains('y')
-        || input
-            .replace("zeta", "")
-            .replace("normalize", "")
-            .contains('z')
-        || input
-            .replace("==", "")
-            .replace("!=", "")
-            .replace(">=", "")
-            .replace("<=", "")
-            .contains('=')
+    if graphs(input).len() > 1 || is_graph(&func) || is_equation(input)
     {
-        return;
+        return None;
     }
-    let num = match do_math(func, options.deg, options.prec)
+    let funcs = user_functions(vars, options.prec);
+    let num = match do_math(func.clone(), options.deg, options.prec, &funcs)
     {
         Ok(num) => num,
+        Err(_) if options.json =>
+        {
+            print!(
+                "{}",
+                json_error(
+                    input,
+                    &diagnose(input, &func, options, &funcs)
+                        .unwrap_or("could not evaluate".to_string())
+                )
+            );
+            return None;
+        }
+        Err(_) if recursion_limit() =>
+        {
+            println!("recursion limit reached");
+            return None;
+        }
         Err(_) =>
         {
             println!("0");
-            return;
+            return None;
         }
     };
+    print_value(input, num.clone(), options, vars);
+    Some(num)
+}
+fn print_value(input: &str, num: NumStr, options: Options, vars: &[[String; 2]])
+{
+    if options.json
+    {
+        print!("{}", json_answer(input, &num));
+        return;
+    }
+    if options.format != Format::Text
+    {
+        if let Some(s) = markup(input, Some(&num), options, vars)
+        {
+            print!("{}", s);
+            return;
+        }
+    }
+    if let Some(s) = poly_output(input, &num, options).or_else(|| tuple_output(&num, options))
+    {
+        print!("{}{}", s, if options.color { "\x1b[0m" } else { "" });
+        return;
+    }
     if let Num(n) = num
     {
+        if options.exact
+        {
+            if let Some(s) = exact(input, vars, options)
+            {
+                print!("{}", s);
+                return;
+            }
+        }
         let a = get_output(&options, &n);
         print!(
             "{}{}{}",
@@ -94,10 +144,34 @@ pub fn print_answer(input: &str, func: Vec<NumStr>, options: Options)
                 output += ",";
             }
         }
+        if v.is_empty()
+        {
+            output += if options.polar { "]" } else { "}" };
+        }
         print!("{}{}", output, if options.color { "\x1b[0m" } else { "" });
     }
     else if let Matrix(v) = num
     {
+        if options.pretty
+        {
+            print!(
+                "{}",
+                pretty_matrix(
+                    &v.iter()
+                        .map(|r| {
+                            r.iter()
+                                .map(|n| {
+                                    let out = get_output(&options, n);
+                                    out.0 + &out.1
+                                })
+                                .collect()
+                        })
+                        .collect::<Vec<Vec<String>>>()
+                )
+                .join("\n")
+            );
+            return;
+        }
         let mut output = if options.multi
         {
             String::new()
@@ -156,36 +230,94 @@ pub fn print_answer(input: &str, func: Vec<NumStr>, options: Options)
         }
         print!("{}{}", output, if options.color { "\x1b[0m" } else { "" });
     }
+    else if let Str(s) = num
+    {
+        print!("{}", s);
+    }
 }
 pub fn print_concurrent(
     unmodified_input: &str,
     input: &str,
     options: Options,
-    start: usize,
-    end: usize,
+    vars: &[[String; 2]],
+    answers: &[(String, NumStr)],
+    line: &str,
 ) -> usize
 {
-    if input.contains('#')
-        || input
-            .replace("exp", "")
-            .replace("max", "")
-            .replace("}x{", "")
-            .replace("]x[", "")
-            .contains('x')
-        || input.contains('y')
-        || input
-            .replace("zeta", "")
-            .replace("normalize", "")
-            .contains('z')
-        || input
-            .replace("==", "")
-            .replace("!=", "")
-            .replace(">=", "")
-            .replace("<=", "")
-            .contains('=')
+    // commands only run on Enter, get can already show the value
+    if let Some((command, arg)) = unmodified_input.split_once(' ')
+    {
+        if COMMANDS.contains(&command)
+        {
+            return hint(
+                option(arg.trim())
+                    .filter(|_| command == "get")
+                    .map(|o| o.get(&options)),
+                line,
+                options,
+            );
+        }
+    }
+    if let Some(c) = conversion(unmodified_input)
+    {
+        let (expr, base) = match c
+        {
+            Ok(c) => c,
+            Err(e) => return hint(Some(e), line, options),
+        };
+        return print_concurrent(
+            expr,
+            &input_var(expr, vars, None),
+            Options { base, ..options },
+            vars,
+            answers,
+            line,
+        );
+    }
+    let func = match get_func(input, options.prec).and_then(|f| recall(f, answers, options.prec))
+    {
+        Ok(f) if !(graphs(input).len() > 1 || is_graph(&f) || is_equation(input)) => f,
+        _ if is_equation(input) =>
+        {
+            return hint(
+                equation_hint(unmodified_input, vars, options),
+                line,
+                options,
+            )
+        }
+        Ok(_) => return hint(graph_hint(input, options), line, options),
+        Err(_) if graphs(input).len() > 1 =>
+        {
+            return hint(graph_hint(input, options), line, options)
+        }
+        Err(_) => return hint(None, line, options),
+    };
+    let mut frac = 0;
+    let funcs = user_functions(vars, options.prec);
+    let mut num = match do_math(func.clone(), options.deg, options.prec, &funcs)
+    {
+        Ok(n) => n,
+        Err(_) => return hint(diagnose(input, &func, options, &funcs), line, options),
+    };
+    if let Str(_) = num
     {
+        num = Num(Complex::new(options.prec));
+    }
+    if let Some(s) =
+        poly_output(unmodified_input, &num, options).or_else(|| tuple_output(&num, options))
+    {
+        let num = s
+            .replace("\x1b[0m", "")
+            .replace("\x1b[93m", "")
+            .replace("\x1b[92m", "")
+            .chars()
+            .count()
+            / get_terminal_width();
         print!(
-            "\x1B[2K\x1B[1G\x1B[0J{}{}{}",
+            "\x1B[0J\n\x1B[2K\x1B[1G{}{}{}\x1B[2K\x1B[1G{}{}{}",
+            s,
+            if options.color { "\x1b[0m" } else { "" },
+            "\x1b[A".repeat(num + 1),
             if options.prompt
             {
                 if options.color
@@ -205,49 +337,10 @@ pub fn print_concurrent(
             {
                 ""
             },
-            &unmodified_input[start..end],
+            line,
             if options.color { "\x1b[0m" } else { "" }
         );
-        return 0;
-    }
-    let func = match get_func(input, options.prec)
-    {
-        Ok(f) => f,
-        Err(_) =>
-        {
-            print!(
-                "\x1B[2K\x1B[1G\x1B[0J{}{}{}",
-                if options.prompt
-                {
-                    if options.color
-                    {
-                        "\x1b[94m> \x1b[96m"
-                    }
-                    else
-                    {
-                        "> "
-                    }
-                }
-                else if options.color
-                {
-                    "\x1b[96m"
-                }
-                else
-                {
-                    ""
-                },
-                &unmodified_input[start..end],
-                if options.color { "\x1b[0m" } else { "" }
-            );
-            return 0;
-        }
-    };
-    let mut frac = 0;
-    let mut num =
-        do_math(func, options.deg, options.prec).unwrap_or(Num(Complex::new(options.prec)));
-    if let Str(_) = num
-    {
-        num = Num(Complex::new(options.prec));
+        return num;
     }
     if let Num(n) = num
     {
@@ -260,7 +353,21 @@ pub fn print_concurrent(
             ""
         }
         .to_owned();
-        let (frac_a, frac_b) = if options.frac || options.frac_iter == 0
+        // integers are already exact so only show forms like 2√2 or π/6
+        let exact = if options.exact
+        {
+            exact(unmodified_input, vars, options).filter(|s| s.parse::<Integer>().is_err())
+        }
+        else
+        {
+            None
+        };
+        let (frac_a, frac_b) = if let Some(s) = exact.clone()
+        {
+            frac = 1;
+            (s, String::new())
+        }
+        else if options.frac || options.frac_iter == 0
         {
             let fa = fraction(n.real().clone(), options);
             let fb = fraction(n.imag().clone(), options);
@@ -369,7 +476,7 @@ pub fn print_concurrent(
             .replace("\x1b[93m", "")
             .replace("\x1b[92m", "")
             .len();
-        if (frac == 1 && !options.frac)
+        if (frac == 1 && !options.frac && exact.is_none())
             || (frac_a.len() + frac_b.len()
                 - if options.color && !frac_b.is_empty()
                 {
@@ -428,11 +535,17 @@ pub fn print_concurrent(
                 {
                     ""
                 },
-                &unmodified_input[start..end],
+                line,
                 if options.color { "\x1b[0m" } else { "" },
             );
             frac += num + if len1 != 0 && len2 != 0 { 1 } else { 0 };
         }
+        else if options.pretty && frac == 1
+        {
+            let mut lines = stack(&(frac_a + &frac_b));
+            lines.push(output.0 + &output.1);
+            return block(&lines, line, options);
+        }
         else
         {
             print!(
@@ -467,7 +580,7 @@ pub fn print_concurrent(
                 {
                     ""
                 },
-                &unmodified_input[start..end],
+                line,
                 if options.color { "\x1b[0m" } else { "" }
             );
         }
@@ -564,6 +677,11 @@ pub fn print_concurrent(
                 frac_out += ",";
             }
         }
+        if v.is_empty()
+        {
+            output += if options.polar { "]" } else { "}" };
+            frac_out += if options.polar { "]" } else { "}" };
+        }
         let terlen = get_terminal_width();
         let len = output
             .replace("\x1b[0m", "")
@@ -586,6 +704,12 @@ pub fn print_concurrent(
         {
             frac = 0;
         }
+        if options.pretty && frac == 1
+        {
+            let mut lines = stack(&frac_out);
+            lines.push(output);
+            return block(&lines, line, options);
+        }
         let num = (len as f64 / terlen as f64).floor() as usize;
         print!(
             "\x1B[0J{}\n\x1B[2K\x1B[1G{}{}\x1b[A{}\x1B[2K\x1B[1G{}{}{}",
@@ -619,13 +743,37 @@ pub fn print_concurrent(
             {
                 ""
             },
-            &unmodified_input[start..end],
+            line,
             if options.color { "\x1b[0m" } else { "" }
         );
         frac += num;
     }
     else if let Matrix(v) = num
     {
+        if options.pretty
+        {
+            let cells = |f: &dyn Fn(&Complex) -> String| {
+                v.iter()
+                    .map(|r| r.iter().map(f).collect())
+                    .collect::<Vec<Vec<String>>>()
+            };
+            let decimals = cells(&|n| {
+                let out = get_output(&options, n);
+                out.0 + &out.1
+            });
+            let fractions = cells(&|n| fraction_entry(n, options));
+            let mut lines = Vec::new();
+            if options.frac && fractions != decimals
+            {
+                lines = pretty_matrix(&fractions);
+            }
+            lines.extend(pretty_matrix(&decimals));
+            // too wide for the terminal is left to the usual layout, which wraps
+            if lines.iter().all(|l| width(l) <= get_terminal_width())
+            {
+                return block(&lines, line, options);
+            }
+        }
         let mut output = if !options.multi { "{" } else { "" }.to_string();
         let mut frac_out = if !options.multi { "{" } else { "" }.to_string();
         let mut out;
@@ -815,13 +963,618 @@ pub fn print_concurrent(
             {
                 ""
             },
-            &unmodified_input[start..end],
+            line,
             if options.color { "\x1b[0m" } else { "" }
         );
         frac += num;
     }
     frac
 }
+// decompositions like lu or qr are shown as (a,b,...) with each part on one line
+// the lines of a preview drawn under the input, the count given back is how many more
+// lines than the usual one it took
+fn block(lines: &[String], line: &str, options: Options) -> usize
+{
+    print!(
+        "\x1B[0J{}{}\x1B[2K\x1B[1G{}{}{}",
+        lines
+            .iter()
+            .map(|l| format!(
+                "\n\x1B[2K\x1B[1G{}{}",
+                l,
+                if options.color { "\x1b[0m" } else { "" }
+            ))
+            .collect::<String>(),
+        "\x1b[A".repeat(lines.len()),
+        prompt(&options),
+        line,
+        if options.color { "\x1b[0m" } else { "" }
+    );
+    lines.len().saturating_sub(1)
+}
+// how many columns a string takes up once its colors are left out
+fn width(s: &str) -> usize
+{
+    let mut n = 0;
+    let mut escape = false;
+    for c in s.chars()
+    {
+        if c == '\x1b'
+        {
+            escape = true;
+        }
+        else if escape
+        {
+            escape = !c.is_ascii_alphabetic();
+        }
+        else
+        {
+            n += 1;
+        }
+    }
+    n
+}
+fn center(s: &str, n: usize) -> String
+{
+    let pad = n.saturating_sub(width(s));
+    format!("{}{}{}", " ".repeat(pad / 2), s, " ".repeat(pad - pad / 2))
+}
+// fractions like 3π/4 drawn over three lines with a bar between the two halves, anything
+// between them such as signs, commas and brackets sits on the bar's line
+pub fn stack(s: &str) -> Vec<String>
+{
+    let mut parts: Vec<String> = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    let mut escape = false;
+    for c in s.chars()
+    {
+        if c == '\x1b' || escape
+        {
+            escape = c != 'm';
+            continue;
+        }
+        match c
+        {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 && matches!(c, '+' | '-' | ',' | '{' | '}' | '[' | ']')
+        {
+            parts.push(current.clone());
+            parts.push(c.to_string());
+            current.clear();
+        }
+        else
+        {
+            current.push(c);
+        }
+    }
+    parts.push(current);
+    let mut lines = vec![String::new(); 3];
+    for part in parts.iter().filter(|p| !p.is_empty())
+    {
+        let (part, i) = match part.strip_suffix('i')
+        {
+            Some(p) if p.contains('/') => (p, "i"),
+            _ => (part.as_str(), ""),
+        };
+        match part.rsplit_once('/')
+        {
+            Some((n, d)) =>
+            {
+                let w = width(n).max(width(d));
+                lines[0] += &center(n, w);
+                lines[1] += &"─".repeat(w);
+                lines[2] += &center(d, w);
+            }
+            None =>
+            {
+                lines[0] += &" ".repeat(width(part));
+                lines[1] += part;
+                lines[2] += &" ".repeat(width(part));
+            }
+        }
+        lines[0] += &" ".repeat(i.len());
+        lines[1] += i;
+        lines[2] += &" ".repeat(i.len());
+    }
+    if s.contains('/')
+    {
+        lines
+    }
+    else
+    {
+        vec![lines.swap_remove(1)]
+    }
+}
+// a matrix between ⎡⎤⎣⎦ with each column lined up on its decimal points, fractions are stacked
+pub fn pretty_matrix(cells: &[Vec<String>]) -> Vec<String>
+{
+    let blocks: Vec<Vec<Vec<String>>> = cells
+        .iter()
+        .map(|r| r.iter().map(|c| stack(c)).collect())
+        .collect();
+    // where a number is split to line up, its decimal point or else the end of its real part
+    let split = |s: &str| {
+        let chars: Vec<char> = s.chars().collect();
+        let mut escape = false;
+        for (i, c) in chars.iter().enumerate()
+        {
+            if *c == '\x1b' || escape
+            {
+                escape = *c != 'm';
+                continue;
+            }
+            if *c == '.' || (i != 0 && (*c == '+' || *c == '-') && chars[i - 1] != 'E')
+            {
+                return width(&chars[..i].iter().collect::<String>());
+            }
+        }
+        width(s)
+    };
+    let columns = cells.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut left = vec![0; columns];
+    let mut right = vec![0; columns];
+    let mut wide = vec![0; columns];
+    for row in &blocks
+    {
+        for (k, b) in row.iter().enumerate()
+        {
+            if b.len() == 1
+            {
+                left[k] = left[k].max(split(&b[0]));
+                right[k] = right[k].max(width(&b[0]) - split(&b[0]));
+            }
+            else
+            {
+                wide[k] = wide[k].max(width(&b[1]));
+            }
+        }
+    }
+    let mut lines = Vec::new();
+    for row in &blocks
+    {
+        let height = row.iter().map(|b| b.len()).max().unwrap_or(1);
+        for h in 0..height
+        {
+            let mut text = Vec::new();
+            for (k, b) in row.iter().enumerate()
+            {
+                let w = wide[k].max(left[k] + right[k]);
+                text.push(
+                    if b.len() == 1
+                    {
+                        if h == height / 2
+                        {
+                            let l = split(&b[0]);
+                            let cell = format!(
+                                "{}{}{}",
+                                " ".repeat(left[k] - l),
+                                b[0],
+                                " ".repeat(right[k] - (width(&b[0]) - l))
+                            );
+                            center(&cell, w)
+                        }
+                        else
+                        {
+                            " ".repeat(w)
+                        }
+                    }
+                    else
+                    {
+                        center(&b[h + b.len() / 2 - height / 2], w)
+                    },
+                );
+            }
+            lines.push(text.join("  "));
+        }
+    }
+    let n = lines.len();
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let (a, b) = match i
+            {
+                _ if n == 1 => ("[", "]"),
+                0 => ("⎡", "⎤"),
+                i if i == n - 1 => ("⎣", "⎦"),
+                _ => ("⎢", "⎥"),
+            };
+            format!("{} {} {}", a, l, b)
+        })
+        .collect()
+}
+// an entry as its fraction when it has one, otherwise as it is usually shown
+fn fraction_entry(n: &Complex, options: Options) -> String
+{
+    let out = get_output(&options, n);
+    let (a, b) = (
+        fraction(n.real().clone(), options),
+        fraction(n.imag().clone(), options),
+    );
+    format!(
+        "{}{}",
+        if a.is_empty() { out.0 } else { a },
+        if b.is_empty() || n.imag().is_zero()
+        {
+            out.1
+        }
+        else
+        {
+            format!(
+                "{}{}i",
+                if !n.real().is_zero() && n.imag().is_sign_positive()
+                {
+                    "+"
+                }
+                else
+                {
+                    ""
+                },
+                b
+            )
+        }
+    )
+}
+// a grey line under the input saying why it isn't evaluated or what enter will do with it
+fn hint(text: Option<String>, line: &str, options: Options) -> usize
+{
+    print!(
+        "\x1B[2K\x1B[1G\x1B[0J{}{}{}{}",
+        text.map_or(String::new(), |t| format!(
+            "\n\x1B[2K\x1B[1G{}{}{}\x1b[A\x1B[2K\x1B[1G",
+            if options.color { "\x1b[90m" } else { "" },
+            t.chars().take(get_terminal_width()).collect::<String>(),
+            if options.color { "\x1b[0m" } else { "" }
+        )),
+        prompt(&options),
+        line,
+        if options.color { "\x1b[0m" } else { "" }
+    );
+    0
+}
+fn graph_hint(input: &str, options: Options) -> Option<String>
+{
+    let count = graphs(input).iter().filter(|s| !s.is_empty()).count();
+    let vars = free_vars(&get_func(&graphs(input)[0], options.prec).ok()?);
+    let (args, space) = if vars.iter().any(|v| v == "z")
+    {
+        ("z", "over the complex plane")
+    }
+    else if vars.iter().any(|v| v == "y")
+    {
+        ("x,y", "in 3D")
+    }
+    else
+    {
+        ("x", "in 2D")
+    };
+    Some(
+        if count > 1
+        {
+            format!(
+                "press Enter to plot {} functions of {} {}",
+                count, args, space
+            )
+        }
+        else
+        {
+            format!("press Enter to plot f({}) {}", args, space)
+        },
+    )
+}
+fn equation_hint(input: &str, vars: &[[String; 2]], options: Options) -> Option<String>
+{
+    let (l, r) = split_equation(input)?;
+    if l.is_empty() || assignable(l)
+    {
+        return None;
+    }
+    let unknowns = free_vars(
+        &get_func(
+            &input_var(&format!("({})-({})", l, r), vars, None),
+            options.prec,
+        )
+        .ok()?,
+    );
+    match unknowns.len()
+    {
+        0 => None,
+        1 => Some(format!("press Enter to solve for {}", unknowns[0])),
+        _ => Some(format!("more than one unknown: {}", unknowns.join(", "))),
+    }
+}
+// why an input that parses doesn't evaluate, a name nothing defines or operands that don't fit together
+pub fn diagnose(
+    input: &str,
+    func: &[NumStr],
+    options: Options,
+    funcs: &[Function],
+) -> Option<String>
+{
+    // this is about the do_math that just failed, before anything here runs another
+    if recursion_limit()
+    {
+        return Some("recursion limit reached".to_string());
+    }
+    // the e of ex is only there until exp is typed out
+    let partial = |v: &str| {
+        input.match_indices(v).any(|(i, _)| {
+            let word: String = input[i..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphabetic())
+                .collect();
+            word.len() > v.len() && functions().iter().any(|f| f.starts_with(&word))
+        })
+    };
+    if let Some(v) = free_vars(func)
+        .iter()
+        .find(|v| !matches!(v.as_str(), "x" | "y" | "z"))
+    {
+        return if partial(v)
+        {
+            None
+        }
+        else if input.contains(&format!("{}(", v))
+        {
+            Some(format!("unknown function {}", v))
+        }
+        else
+        {
+            Some(format!("unknown variable {}", v))
+        };
+    }
+    mismatch(func, options, funcs)
+}
+// each operand is evaluated on its own, one that fails is looked into and otherwise the
+// first operator whose operands won't combine is the problem
+fn mismatch(func: &[NumStr], options: Options, funcs: &[Function]) -> Option<String>
+{
+    let mut last: Option<NumStr> = None;
+    for (op, piece) in operands(func)
+    {
+        let value = match do_math(piece.clone(), options.deg, options.prec, funcs)
+        {
+            Ok(Str(_)) =>
+            {
+                last = None;
+                continue;
+            }
+            Ok(n) => n,
+            Err(_) if piece.is_empty() =>
+            {
+                last = None;
+                continue;
+            }
+            Err(_) =>
+            {
+                let open = piece.iter().position(|n| n.str_is("(") || n.str_is("{"))?;
+                let mut count = 0;
+                let close = open
+                    + piece[open..].iter().position(|n| {
+                        if n.str_is("(") || n.str_is("{")
+                        {
+                            count += 1;
+                        }
+                        else if n.str_is(")") || n.str_is("}")
+                        {
+                            count -= 1;
+                        }
+                        count == 0
+                    })?;
+                let inner = &piece[open + 1..close];
+                if let Some(s) = mismatch(inner, options, funcs)
+                {
+                    return Some(s);
+                }
+                // the arguments are fine by themselves, so the function doesn't take them
+                if let Some(Str(f)) = open.checked_sub(1).map(|i| &piece[i])
+                {
+                    if is_func(f) && !inner.iter().any(|n| n.str_is("->"))
+                    {
+                        let args = operands(inner)
+                            .into_iter()
+                            .map(|(_, a)| {
+                                do_math(a, options.deg, options.prec, funcs).map(|a| shape(&a))
+                            })
+                            .collect::<Result<Vec<String>, ()>>()
+                            .ok()?;
+                        return Some(format!("{} can't take {}", f, args.join(", ")));
+                    }
+                }
+                return None;
+            }
+        };
+        if let Some(a) = last.take()
+        {
+            if op != ","
+                && do_math(
+                    vec![a.clone(), Str(op.clone()), value.clone()],
+                    options.deg,
+                    options.prec,
+                    funcs,
+                )
+                .is_err()
+            {
+                return Some(format!(
+                    "dimension mismatch: {} {} {}",
+                    shape(&a),
+                    op,
+                    shape(&value)
+                ));
+            }
+        }
+        last = Some(value);
+    }
+    None
+}
+// splits at the top level operators and commas, each piece comes with what came before it
+fn operands(func: &[NumStr]) -> Vec<(String, Vec<NumStr>)>
+{
+    let mut pieces = vec![(String::new(), Vec::new())];
+    let mut count = 0;
+    for n in func
+    {
+        if let Str(s) = n
+        {
+            match s.as_str()
+            {
+                "(" | "{" => count += 1,
+                ")" | "}" => count -= 1,
+                "+" | "-" | "*" | "/" | "^" | "," if count == 0 =>
+                {
+                    pieces.push((s.clone(), Vec::new()));
+                    continue;
+                }
+                _ => (),
+            }
+        }
+        pieces.last_mut().unwrap().1.push(n.clone());
+    }
+    pieces
+}
+fn shape(n: &NumStr) -> String
+{
+    match n
+    {
+        Num(_) => "number".to_string(),
+        Vector(v) => format!("{}-vector", v.len()),
+        Matrix(m) => format!("{}x{} matrix", m.len(), m.first().map_or(0, |r| r.len())),
+        Tuple(_) => "tuple".to_string(),
+        Str(s) => s.clone(),
+    }
+}
+pub fn tuple_output(num: &NumStr, options: Options) -> Option<String>
+{
+    let t = match num
+    {
+        Tuple(t) => t,
+        _ => return None,
+    };
+    let color = if options.color { "\x1b[0m" } else { "" };
+    let part = |z: &Complex| {
+        let out = get_output(&options, z);
+        format!("{}{}{}", out.0, out.1, color)
+    };
+    let mut output = Vec::new();
+    for n in t
+    {
+        output.push(match n
+        {
+            Num(n) => part(n),
+            Vector(v) => format!(
+                "{{{}}}",
+                v.iter().map(part).collect::<Vec<String>>().join(",")
+            ),
+            Matrix(m) => format!(
+                "{{{}}}",
+                m.iter()
+                    .map(|v| format!(
+                        "{{{}}}",
+                        v.iter().map(part).collect::<Vec<String>>().join(",")
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Str(s) => s.clone(),
+            Tuple(_) => tuple_output(n, options)?,
+        });
+    }
+    Some(format!("({})", output.join(",")))
+}
+// an answer as json, its parts as decimals to the full precision so nothing is lost to rounding
+fn json(num: &NumStr) -> String
+{
+    let complex = |z: &Complex| {
+        format!(
+            "\"real\":\"{}\",\"imag\":\"{}\"",
+            decimal(z.real()),
+            decimal(z.imag())
+        )
+    };
+    let list = |v: &[Complex]| {
+        v.iter()
+            .map(|z| format!("{{{}}}", complex(z)))
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    match num
+    {
+        Num(n) => format!("{{\"type\":\"number\",{}}}", complex(n)),
+        Vector(v) => format!("{{\"type\":\"vector\",\"value\":[{}]}}", list(v)),
+        Matrix(m) => format!(
+            "{{\"type\":\"matrix\",\"value\":[{}]}}",
+            m.iter()
+                .map(|v| format!("[{}]", list(v)))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        Tuple(t) => format!(
+            "{{\"type\":\"tuple\",\"value\":[{}]}}",
+            t.iter().map(json).collect::<Vec<String>>().join(",")
+        ),
+        Str(s) => format!("{{\"type\":\"string\",\"value\":{}}}", json_string(s)),
+    }
+}
+pub fn json_answer(input: &str, num: &NumStr) -> String
+{
+    format!("{{\"input\":{},{}", json_string(input), &json(num)[1..])
+}
+pub fn json_error(input: &str, message: &str) -> String
+{
+    format!(
+        "{{\"input\":{},\"type\":\"error\",\"message\":{}}}",
+        json_string(input),
+        json_string(message)
+    )
+}
+fn json_string(s: &str) -> String
+{
+    let mut output = "\"".to_string();
+    for c in s.chars()
+    {
+        match c
+        {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+// every digit rug gives for the precision, without the zeros it pads the end with
+pub fn decimal(num: &Float) -> String
+{
+    if num.is_zero()
+    {
+        return "0".to_string();
+    }
+    let s = num.to_string_radix(10, None);
+    let (mantissa, exp) = s.split_once('e').unwrap_or((&s, ""));
+    let mantissa = if mantissa.contains('.')
+    {
+        mantissa.trim_end_matches('0').trim_end_matches('.')
+    }
+    else
+    {
+        mantissa
+    };
+    if exp.is_empty() || exp == "0"
+    {
+        mantissa.to_string()
+    }
+    else
+    {
+        format!("{}e{}", mantissa, exp)
+    }
+}
 pub fn get_output(options: &Options, num: &Complex) -> (String, String)
 {
     let sign = if num.real() != &0.0 && num.imag().is_sign_positive()
@@ -833,7 +1586,7 @@ pub fn get_output(options: &Options, num: &Complex) -> (String, String)
         ""
     }
     .to_owned();
-    let mut n;
+    let n;
     let dec = if options.decimal_places == 0
     {
         1
@@ -847,15 +1600,7 @@ pub fn get_output(options: &Options, num: &Complex) -> (String, String)
         (
             if num.real() != &0.0
             {
-                n = num.real().to_string_radix(options.base as i32, None);
-                if n.contains('e')
-                {
-                    n
-                }
-                else
-                {
-                    n.trim_end_matches('0').trim_end_matches('.').to_owned()
-                }
+                radix(num.real(), options)
             }
             else if num.imag() == &0.0
             {
@@ -867,15 +1612,7 @@ pub fn get_output(options: &Options, num: &Complex) -> (String, String)
             },
             if num.imag() != &0.0
             {
-                n = num.imag().to_string_radix(options.base as i32, None);
-                sign + &if n.contains('e')
-                {
-                    n
-                }
-                else
-                {
-                    n.trim_end_matches('0').trim_end_matches('.').to_owned()
-                } + if options.color { "\x1b[93mi" } else { "i" }
+                sign + &radix(num.imag(), options) + if options.color { "\x1b[93mi" } else { "i" }
             }
             else
             {
@@ -883,7 +1620,32 @@ pub fn get_output(options: &Options, num: &Complex) -> (String, String)
             },
         )
     }
-    else if options.sci
+    else if options.notation == Notation::Eng || options.notation == Notation::Sig
+    {
+        (
+            if num.real() != &0.0
+            {
+                figures(num.real(), options)
+            }
+            else if num.imag() == &0.0
+            {
+                "0".to_owned()
+            }
+            else
+            {
+                "".to_owned()
+            },
+            if num.imag() != &0.0
+            {
+                sign + &figures(num.imag(), options) + if options.color { "\x1b[93mi" } else { "i" }
+            }
+            else
+            {
+                "".to_owned()
+            },
+        )
+    }
+    else if options.notation == Notation::Sci
     {
         (
             if num.real() != &0.0
@@ -959,6 +1721,132 @@ pub fn get_output(options: &Options, num: &Complex) -> (String, String)
         )
     }
 }
+// a part in another base rounded to the decimal places like the decimal output, with any other
+// notation than auto as digits and E with the power of the base in decimal
+fn radix(num: &Float, options: &Options) -> String
+{
+    if !num.is_finite()
+    {
+        return num.to_string();
+    }
+    let base = options.base as u32;
+    // places past what the precision holds would only be noise
+    let most = (num.prec() as f64 / (base as f64).log2()) as usize;
+    let places = options.decimal_places.min(most);
+    let mut n = num.clone().abs();
+    let mut exp = 0;
+    if options.notation != Notation::Auto
+    {
+        let size: Float = n.clone().log2() / Float::with_val(n.prec(), base).log2();
+        exp = size.floor().to_f64() as i32;
+        n /= Float::with_val(n.prec(), base).pow(exp);
+    }
+    let scale = Integer::from(base).pow(places as u32);
+    let mut digits = (n * &scale).round().to_integer().unwrap_or_default();
+    // 0.ff.. rounding up to a whole power of the base moves the point along one
+    if exp != 0 && digits >= scale.clone() * base
+    {
+        digits /= base;
+        exp += 1;
+    }
+    let mut s = digits.to_string_radix(base as i32);
+    if s.len() <= places
+    {
+        s.insert_str(0, &"0".repeat(places + 1 - s.len()));
+    }
+    s.insert(s.len() - places, '.');
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    let neg = if num.is_sign_negative() && s != "0"
+    {
+        "-"
+    }
+    else
+    {
+        ""
+    };
+    if options.notation == Notation::Auto || exp == 0
+    {
+        format!("{}{}", neg, s)
+    }
+    else
+    {
+        format!(
+            "{}{}{}{}{}",
+            neg,
+            s,
+            if options.color { "\x1b[92mE" } else { "E" },
+            exp,
+            if options.color { "\x1b[0m" } else { "" }
+        )
+    }
+}
+// a part rounded to the significant figures asked for, sig writes it out in full when that
+// is short enough and eng keeps the exponent a multiple of 3 with an si prefix for it
+fn figures(num: &Float, options: &Options) -> String
+{
+    let (neg, digits, exp) = num.to_sign_string_exp(10, Some(options.sig));
+    let digits = digits.trim_end_matches('0');
+    let exp = match exp
+    {
+        Some(e) if !digits.is_empty() => e - 1,
+        _ => return "0".to_string(),
+    };
+    // the digits with the point after the first n of them
+    let point = |n: i32| {
+        let mut d = digits.to_string();
+        if n <= 0
+        {
+            d.insert_str(0, &"0".repeat(-n as usize + 1));
+            d.insert(1, '.');
+        }
+        else
+        {
+            if n as usize > d.len()
+            {
+                d.push_str(&"0".repeat(n as usize - d.len()));
+            }
+            d.insert(n as usize, '.');
+        }
+        d.trim_end_matches('.').to_string()
+    };
+    let (mantissa, suffix) = if options.notation == Notation::Eng
+    {
+        let e = exp.div_euclid(3) * 3;
+        let prefix = [
+            "y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y",
+        ];
+        (
+            point(exp - e + 1),
+            match prefix.get(((e + 24) / 3) as usize).filter(|_| e >= -24)
+            {
+                Some(p) => p.to_string(),
+                None => format!("e{}", e),
+            },
+        )
+    }
+    else if exp < -5 || exp >= options.sig as i32
+    {
+        (point(1), format!("e{}", exp))
+    }
+    else
+    {
+        (point(exp + 1), String::new())
+    };
+    let suffix = suffix.replacen('e', "E", 1);
+    format!(
+        "{}{}{}",
+        if neg { "-" } else { "" },
+        add_commas(&mantissa, options.comma),
+        if suffix.is_empty() || !options.color
+        {
+            suffix
+        }
+        else
+        {
+            format!("\x1b[92m{}\x1b[0m", suffix)
+        }
+    )
+}
 fn to_string(num: &Float, decimals: usize, imag: bool) -> String
 {
     let (neg, mut str, exp) = num.to_sign_string_exp(10, None);
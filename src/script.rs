@@ -0,0 +1,444 @@
+use crate::{
+    assignable,
+    complex::{
+        NumStr,
+        NumStr::{Matrix, Num, Str, Tuple, Vector},
+    },
+    math::do_math,
+    options::setting,
+    parse::{get_func, input_var, is_equation, split_equation, user_functions},
+    print::{get_output, tuple_output},
+    Options, COMMANDS,
+};
+use rug::{Complex, Float};
+use std::collections::{hash_map::Entry, HashMap};
+// a script compiled down to a list of steps, the blocks become jumps
+enum Op
+{
+    Run(String),
+    Print(String),
+    If(String, usize),
+    While(String, usize),
+    For(String, String, usize),
+    Jump(usize),
+}
+// hands a script's lines to the main loop one at a time, working out the control flow in between
+// so that conditions see the variables the lines before them set
+pub struct Script
+{
+    name: String,
+    ops: Vec<(usize, Op)>,
+    next: usize,
+    loops: HashMap<usize, (Vec<String>, usize)>,
+}
+impl Script
+{
+    pub fn new(name: &str, text: &str) -> Result<Self, ()>
+    {
+        let mut ops: Vec<(usize, Op)> = Vec::new();
+        // open blocks, the op that started each and the jumps out of it that wait for its end
+        let mut blocks: Vec<(usize, Vec<usize>)> = Vec::new();
+        let error = |line: usize, message: &str| println!("{}:{}: {}", name, line, message);
+        for (line, l) in text.lines().enumerate()
+        {
+            let line = line + 1;
+            let l = strip_comment(l);
+            let l = l.trim();
+            if l.is_empty()
+            {
+                continue;
+            }
+            let (word, rest) = l.split_once(' ').unwrap_or((l, ""));
+            let rest = rest.trim();
+            match word
+            {
+                "if" | "while" if rest.is_empty() =>
+                {
+                    error(line, &format!("{} needs a condition", word));
+                    return Err(());
+                }
+                "if" =>
+                {
+                    blocks.push((ops.len(), Vec::new()));
+                    ops.push((line, Op::If(rest.to_string(), 0)));
+                }
+                "while" =>
+                {
+                    blocks.push((ops.len(), Vec::new()));
+                    ops.push((line, Op::While(rest.to_string(), 0)));
+                }
+                "for" =>
+                {
+                    let Some((var, iter)) = rest.split_once(" in ")
+                    else
+                    {
+                        error(line, "expected for name in range");
+                        return Err(());
+                    };
+                    blocks.push((ops.len(), Vec::new()));
+                    ops.push((
+                        line,
+                        Op::For(var.trim().to_string(), iter.trim().to_string(), 0),
+                    ));
+                }
+                "else" =>
+                {
+                    let Some((start, exits)) = blocks.last_mut()
+                    else
+                    {
+                        error(line, "else without if");
+                        return Err(());
+                    };
+                    if !matches!(ops[*start].1, Op::If(..))
+                    {
+                        error(line, "else without if");
+                        return Err(());
+                    }
+                    // the branch before jumps to the end, a failed condition lands after that jump
+                    exits.push(ops.len());
+                    ops.push((line, Op::Jump(0)));
+                    let after = ops.len();
+                    if let Op::If(_, end) = &mut ops[*start].1
+                    {
+                        *end = after;
+                    }
+                    if rest.is_empty()
+                    {
+                        // the jump stands in for the if so another else is refused
+                        *start = after - 1;
+                    }
+                    else if let Some(cond) = rest.strip_prefix("if ")
+                    {
+                        *start = after;
+                        ops.push((line, Op::If(cond.trim().to_string(), 0)));
+                    }
+                    else
+                    {
+                        error(line, "expected else or else if");
+                        return Err(());
+                    }
+                }
+                "end" =>
+                {
+                    let Some((start, exits)) = blocks.pop()
+                    else
+                    {
+                        error(line, "end without a block");
+                        return Err(());
+                    };
+                    if matches!(ops[start].1, Op::While(..) | Op::For(..))
+                    {
+                        ops.push((line, Op::Jump(start)));
+                    }
+                    let after = ops.len();
+                    match &mut ops[start].1
+                    {
+                        Op::If(_, end) | Op::While(_, end) | Op::For(_, _, end) => *end = after,
+                        Op::Jump(_) | Op::Run(_) | Op::Print(_) => (),
+                    }
+                    for e in exits
+                    {
+                        ops[e].1 = Op::Jump(after);
+                    }
+                }
+                "print" => ops.push((line, Op::Print(rest.to_string()))),
+                _ => ops.push((line, Op::Run(l.to_string()))),
+            }
+        }
+        if let Some((start, _)) = blocks.last()
+        {
+            error(ops[*start].0, "block without end");
+            return Err(());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            ops,
+            next: 0,
+            loops: HashMap::new(),
+        })
+    }
+    // the next line for the main loop to run, none once the script is done
+    pub fn next(&mut self, vars: &[[String; 2]], options: Options) -> Result<Option<String>, ()>
+    {
+        while let Some((line, op)) = self.ops.get(self.next)
+        {
+            let line = *line;
+            let error = |message: &str| {
+                println!("{}:{}: {}", self.name, line, message);
+                Err(())
+            };
+            self.next += 1;
+            match op
+            {
+                Op::Run(l) =>
+                {
+                    if is_equation(l)
+                    {
+                        let (name, value) = split_equation(l).unwrap();
+                        let (name, value) = (name.trim(), value.trim());
+                        // a variable takes its value now, so i=i+1 counts up instead of referring to itself
+                        if assignable(name)
+                            && !name.contains('(')
+                            && setting(name).is_none()
+                            && value != "null"
+                        {
+                            if let Ok(n) = eval(value, vars, options)
+                            {
+                                return Ok(Some(format!("{}={}", name, text(&n))));
+                            }
+                        }
+                        return Ok(Some(format!("{}={}", name, value)));
+                    }
+                    else if !COMMANDS.contains(&l.split(' ').next().unwrap())
+                        && get_func(&input_var(l, vars, None), options.prec).is_err()
+                    {
+                        return error("Invalid function.");
+                    }
+                    return Ok(Some(l.clone()));
+                }
+                Op::Print(s) =>
+                {
+                    let out = if let Some(s) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"'))
+                    {
+                        match interpolate(s, vars, options)
+                        {
+                            Ok(s) => s,
+                            Err(()) => return error("can't evaluate what print was given"),
+                        }
+                    }
+                    else if s.is_empty()
+                    {
+                        String::new()
+                    }
+                    else
+                    {
+                        match eval(s, vars, options)
+                        {
+                            Ok(n) => format(&n, options),
+                            Err(()) => return error("can't evaluate what print was given"),
+                        }
+                    };
+                    println!("{}", out);
+                }
+                Op::If(cond, end) | Op::While(cond, end) =>
+                {
+                    let end = *end;
+                    match eval(cond, vars, options)
+                    {
+                        Ok(Num(n)) if n.real() == &0.0 => self.next = end,
+                        Ok(Num(_)) => (),
+                        _ => return error("can't evaluate the condition"),
+                    }
+                }
+                Op::For(var, iter, end) =>
+                {
+                    let (var, end, index) = (var.clone(), *end, self.next - 1);
+                    // the values are worked out when the loop is entered and dropped when it's done
+                    let (values, i) = match self.loops.entry(index)
+                    {
+                        Entry::Occupied(e) => e.into_mut(),
+                        Entry::Vacant(e) => match values(iter, vars, options)
+                        {
+                            Ok(v) => e.insert((v, 0)),
+                            Err(()) => return error("can't loop over that"),
+                        },
+                    };
+                    if *i == values.len()
+                    {
+                        self.loops.remove(&index);
+                        self.next = end;
+                    }
+                    else
+                    {
+                        *i += 1;
+                        return Ok(Some(format!("{}={}", var, values[*i - 1])));
+                    }
+                }
+                Op::Jump(to) => self.next = *to,
+            }
+        }
+        Ok(None)
+    }
+}
+// a # starting the line or following a space is a comment, otherwise it separates graphs
+fn strip_comment(line: &str) -> &str
+{
+    let mut quoted = false;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices()
+    {
+        match c
+        {
+            '"' => quoted = !quoted,
+            '#' if !quoted && prev.is_whitespace() => return &line[..i],
+            _ => (),
+        }
+        prev = c;
+    }
+    line
+}
+fn eval(input: &str, vars: &[[String; 2]], options: Options) -> Result<NumStr, ()>
+{
+    match do_math(
+        get_func(&input_var(input, vars, None), options.prec)?,
+        options.deg,
+        options.prec,
+        &user_functions(vars, options.prec),
+    )?
+    {
+        Str(_) => Err(()),
+        n => Ok(n),
+    }
+}
+// a..b counts from a to b, anything else is evaluated and its entries or rows are gone through
+fn values(iter: &str, vars: &[[String; 2]], options: Options) -> Result<Vec<String>, ()>
+{
+    if let Some((a, b)) = iter.split_once("..")
+    {
+        let a = eval(a, vars, options)?
+            .num()?
+            .real()
+            .to_integer()
+            .ok_or(())?;
+        let b = eval(b, vars, options)?
+            .num()?
+            .real()
+            .to_integer()
+            .ok_or(())?;
+        let mut values = Vec::new();
+        let mut i = a;
+        while i <= b
+        {
+            values.push(i.to_string());
+            i += 1;
+        }
+        return Ok(values);
+    }
+    Ok(match eval(iter, vars, options)?
+    {
+        Num(n) => vec![number(&n)],
+        Vector(v) => v.iter().map(number).collect(),
+        Matrix(m) => m.into_iter().map(|r| text(&Vector(r))).collect(),
+        _ => return Err(()),
+    })
+}
+// a value written out at full precision so it reads back as the same number
+fn text(n: &NumStr) -> String
+{
+    match n
+    {
+        Num(n) => number(n),
+        Vector(v) => format!(
+            "{{{}}}",
+            v.iter().map(number).collect::<Vec<String>>().join(",")
+        ),
+        Matrix(m) => format!(
+            "{{{}}}",
+            m.iter()
+                .map(|r| text(&Vector(r.clone())))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        Tuple(_) | Str(_) => String::new(),
+    }
+}
+fn number(n: &Complex) -> String
+{
+    let float = |f: &Float| {
+        let s = f.to_string();
+        // E doesn't read back inside a vector so the exponent is written out
+        if let Some((m, e)) = s.split_once('e')
+        {
+            format!("({}*10^({}))", m, e)
+        }
+        else if s.contains('.')
+        {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+        else
+        {
+            s
+        }
+    };
+    if n.imag().is_zero()
+    {
+        format!("({})", float(n.real()))
+    }
+    else
+    {
+        format!("({}+{}i)", float(n.real()), float(n.imag()))
+    }
+}
+// each {expr} in the text is replaced by its value, {{ and }} are literal braces
+fn interpolate(s: &str, vars: &[[String; 2]], options: Options) -> Result<String, ()>
+{
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len()
+    {
+        match chars[i]
+        {
+            '{' if chars.get(i + 1) == Some(&'{') =>
+            {
+                out.push('{');
+                i += 1;
+            }
+            '}' if chars.get(i + 1) == Some(&'}') =>
+            {
+                out.push('}');
+                i += 1;
+            }
+            '{' =>
+            {
+                let mut count = 0;
+                let end = i + chars[i..]
+                    .iter()
+                    .position(|c| {
+                        match c
+                        {
+                            '{' => count += 1,
+                            '}' => count -= 1,
+                            _ => (),
+                        }
+                        count == 0
+                    })
+                    .ok_or(())?;
+                let expr: String = chars[i + 1..end].iter().collect();
+                out.push_str(&format(&eval(&expr, vars, options)?, options));
+                i = end;
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+    Ok(out)
+}
+fn format(n: &NumStr, options: Options) -> String
+{
+    let reset = if options.color { "\x1b[0m" } else { "" };
+    let number = |n: &Complex| {
+        let (a, b) = get_output(&options, n);
+        a + &b + reset
+    };
+    match n
+    {
+        Num(n) => number(n),
+        Vector(v) => format!(
+            "{{{}}}",
+            v.iter().map(number).collect::<Vec<String>>().join(",")
+        ),
+        Matrix(m) => format!(
+            "{{{}}}",
+            m.iter()
+                .map(|r| format!(
+                    "{{{}}}",
+                    r.iter().map(number).collect::<Vec<String>>().join(",")
+                ))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        Tuple(_) => tuple_output(n, options).unwrap_or_default(),
+        Str(s) => s.clone(),
+    }
+}
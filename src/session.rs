@@ -0,0 +1,199 @@
+use crate::{
+    answer,
+    complex::{
+        NumStr,
+        NumStr::{Matrix, Num, Str, Tuple, Vector},
+    },
+    options::{option, OPTIONS},
+    parse::split_equation,
+    print::decimal,
+    Options,
+};
+use rug::{Complex, Float};
+use std::fs::write;
+// a session file is a line per thing, "set name=value" for every option, name=value for each
+// variable and function the user made and "val value input" for each input that gave an answer,
+// the value is kept so it is the same after load whatever the variables are by then, an
+// "ans input" line has no value and is worked out again
+pub fn save(
+    path: &str,
+    vars: &[[String; 2]],
+    defaults: &[[String; 2]],
+    options: &Options,
+    answers: &[(String, NumStr)],
+) -> Result<(), ()>
+{
+    let mut text = String::new();
+    // whether sessions are kept is up to the run that loads one
+    for opt in OPTIONS.iter().filter(|o| o.name != "session")
+    {
+        text.push_str(&format!("set {}={}\n", opt.name, opt.get(options)));
+    }
+    for v in vars.iter().filter(|v| !defaults.contains(v))
+    {
+        text.push_str(&format!("{}={}\n", v[0], v[1]));
+    }
+    for (a, v) in answers
+    {
+        match value(v)
+        {
+            Some(v) => text.push_str(&format!("val {} {}\n", v, a)),
+            None => text.push_str(&format!("ans {}\n", a)),
+        }
+    }
+    write(path, text).map_err(|_| ())
+}
+// restores what save wrote over the current state, the lines it can't use are given back
+pub fn load(
+    text: &str,
+    vars: &mut Vec<[String; 2]>,
+    options: &mut Options,
+    answers: &mut Vec<(String, NumStr)>,
+) -> Vec<(usize, String)>
+{
+    let mut errors = Vec::new();
+    for (i, line) in text.lines().enumerate()
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#')
+        {
+            continue;
+        }
+        if let Some(s) = line.strip_prefix("set ")
+        {
+            let (name, value) = s.split_once('=').unwrap_or((s, ""));
+            match option(name.trim())
+            {
+                Some(opt) =>
+                {
+                    if let Err(e) = opt.set(options, value)
+                    {
+                        errors.push((i + 1, e));
+                    }
+                }
+                None => errors.push((i + 1, format!("Unknown option {}", name.trim()))),
+            }
+        }
+        else if let Some(l) = line.strip_prefix("val ")
+        {
+            let (v, a) = l.split_once(' ').unwrap_or((l, ""));
+            let chars = v.chars().collect::<Vec<char>>();
+            let mut j = 0;
+            match read(&chars, &mut j, options.prec)
+            {
+                Ok(v) if j == chars.len() => answers.push((a.trim().to_string(), v)),
+                _ => errors.push((i + 1, format!("Invalid answer {}", a.trim()))),
+            }
+        }
+        else if let Some(a) = line.strip_prefix("ans ")
+        {
+            // worked out again in order, as later answers can refer to earlier ones
+            match answer(a, vars, *options, answers)
+            {
+                Ok(Str(e)) => errors.push((i + 1, e)),
+                Ok(v) => answers.push((a.to_string(), v)),
+                Err(()) => errors.push((i + 1, format!("Invalid answer {}", a))),
+            }
+        }
+        else if let Some((l, r)) = split_equation(line)
+        {
+            match vars
+                .iter_mut()
+                .find(|v| v[0].split('(').next() == l.split('(').next())
+            {
+                Some(v) => *v = [l.to_string(), r.to_string()],
+                None => vars.push([l.to_string(), r.to_string()]),
+            }
+        }
+        else
+        {
+            errors.push((i + 1, format!("Invalid line {}", line)));
+        }
+    }
+    errors
+}
+// an answer written out in full, (re,im) for a number, {} around a vector or matrix and []
+// around a tuple, no spaces so the input can follow it
+fn value(v: &NumStr) -> Option<String>
+{
+    let num = |z: &Complex| format!("({},{})", decimal(z.real()), decimal(z.imag()));
+    let list = |v: &[Complex]| v.iter().map(num).collect::<Vec<String>>().join(",");
+    Some(match v
+    {
+        Num(z) => num(z),
+        Vector(v) => format!("{{{}}}", list(v)),
+        Matrix(m) => format!(
+            "{{{}}}",
+            m.iter()
+                .map(|r| format!("{{{}}}", list(r)))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        Tuple(t) => format!(
+            "[{}]",
+            t.iter()
+                .map(value)
+                .collect::<Option<Vec<String>>>()?
+                .join(",")
+        ),
+        Str(_) => return None,
+    })
+}
+// reads back what value wrote
+fn read(chars: &[char], i: &mut usize, prec: u32) -> Result<NumStr, ()>
+{
+    let (open, close) = match chars.get(*i)
+    {
+        Some('(') => ('(', ')'),
+        Some('{') => ('{', '}'),
+        Some('[') => ('[', ']'),
+        _ => return Err(()),
+    };
+    *i += 1;
+    if open == '('
+    {
+        let end = *i + chars[*i..].iter().position(|c| *c == ')').ok_or(())?;
+        let part = chars[*i..end].iter().collect::<String>();
+        let (re, im) = part.split_once(',').ok_or(())?;
+        let float = |s: &str| Float::parse(s).map(|f| Float::with_val(prec, f));
+        *i = end + 1;
+        return Ok(Num(Complex::with_val(
+            prec,
+            (float(re).map_err(|_| ())?, float(im).map_err(|_| ())?),
+        )));
+    }
+    let mut items = Vec::new();
+    while chars.get(*i) != Some(&close)
+    {
+        if !items.is_empty()
+        {
+            if chars.get(*i) != Some(&',')
+            {
+                return Err(());
+            }
+            *i += 1;
+        }
+        items.push(read(chars, i, prec)?);
+    }
+    *i += 1;
+    if open == '['
+    {
+        Ok(Tuple(items))
+    }
+    else if items.iter().all(|v| matches!(v, Num(_)))
+    {
+        Ok(Vector(
+            items.into_iter().map(|v| v.num().unwrap()).collect(),
+        ))
+    }
+    else if items.iter().all(|v| matches!(v, Vector(_)))
+    {
+        Ok(Matrix(
+            items.into_iter().map(|v| v.vec().unwrap()).collect(),
+        ))
+    }
+    else
+    {
+        Err(())
+    }
+}
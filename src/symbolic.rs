@@ -0,0 +1,758 @@
+use crate::{
+    options::AngleType,
+    parse::{get_vars, input_var},
+    Options,
+};
+use rug::{ops::Pow, Integer, Rational};
+// exact values are sums of terms of the form (p/q)*pi^a*e^b*sqrt(r)*ln(n),
+// anything that leaves that form gives None so the caller falls back to numeric output
+#[derive(Clone, PartialEq)]
+struct Term
+{
+    coef: Rational,
+    pi: i32,
+    e: i32,
+    root: Integer,
+    ln: Integer,
+}
+#[derive(Clone)]
+struct Exact(Vec<Term>);
+// coefficients past this many bits are left to the numeric output, so repeated powers stay quick
+const BITS: u32 = 4096;
+fn small(r: &Rational) -> bool
+{
+    r.numer().significant_bits() <= BITS && r.denom().significant_bits() <= BITS
+}
+impl Term
+{
+    fn new(coef: Rational) -> Self
+    {
+        Term {
+            coef,
+            pi: 0,
+            e: 0,
+            root: Integer::from(1),
+            ln: Integer::from(1),
+        }
+    }
+    fn like(&self, other: &Self) -> bool
+    {
+        self.pi == other.pi && self.e == other.e && self.root == other.root && self.ln == other.ln
+    }
+    fn is_rational(&self) -> bool
+    {
+        self.pi == 0 && self.e == 0 && self.root == 1 && self.ln == 1
+    }
+    fn mul(&self, other: &Self) -> Option<Self>
+    {
+        if self.ln != 1 && other.ln != 1
+        {
+            return None;
+        }
+        let (k, root) = split_square(Integer::from(&self.root * &other.root));
+        let coef = Rational::from(&self.coef * &other.coef) * k;
+        if !small(&coef)
+        {
+            return None;
+        }
+        Some(Term {
+            coef,
+            pi: self.pi.checked_add(other.pi)?,
+            e: self.e.checked_add(other.e)?,
+            root,
+            ln: if self.ln != 1
+            {
+                self.ln.clone()
+            }
+            else
+            {
+                other.ln.clone()
+            },
+        })
+    }
+    fn recip(&self) -> Option<Self>
+    {
+        if self.ln != 1 || self.coef == 0
+        {
+            return None;
+        }
+        // 1/(c*sqrt(r))=sqrt(r)/(c*r)
+        Some(Term {
+            coef: (self.coef.clone() * self.root.clone()).recip(),
+            pi: -self.pi,
+            e: -self.e,
+            root: self.root.clone(),
+            ln: Integer::from(1),
+        })
+    }
+    fn to_string(&self, options: Options) -> String
+    {
+        let mut coef = self.coef.clone().abs();
+        let mut pi = "π";
+        if options.tau && self.pi != 0
+        {
+            coef /= Rational::from(2).pow(self.pi);
+            pi = "τ";
+        }
+        let mut top = String::new();
+        let mut bottom = String::new();
+        for (n, s) in [(self.pi, pi), (self.e, "e")]
+        {
+            match n
+            {
+                0 => (),
+                1 => top.push_str(s),
+                -1 => bottom.push_str(s),
+                n if n > 0 => top.push_str(&format!("{}^{}", s, n)),
+                n => bottom.push_str(&format!("{}^{}", s, -n)),
+            }
+        }
+        if self.root != 1
+        {
+            top.push_str(&format!("√{}", self.root));
+        }
+        if self.ln != 1
+        {
+            top.push_str(&format!(
+                "{}ln {}",
+                if top.is_empty() { "" } else { " " },
+                self.ln
+            ));
+        }
+        if *coef.numer() != 1 || top.is_empty()
+        {
+            top.insert_str(
+                0,
+                &format!(
+                    "{}{}",
+                    coef.numer(),
+                    if self.ln != 1 && top.starts_with('l')
+                    {
+                        " "
+                    }
+                    else
+                    {
+                        ""
+                    }
+                ),
+            );
+        }
+        if *coef.denom() != 1
+        {
+            bottom.insert_str(0, &coef.denom().to_string());
+        }
+        format!(
+            "{}{}{}",
+            if self.coef < 0 { "-" } else { "" },
+            top,
+            if bottom.is_empty()
+            {
+                String::new()
+            }
+            else if *coef.denom() == 1 && bottom.chars().count() == 1
+                || bottom.chars().all(|c| c.is_ascii_digit())
+            {
+                format!("/{}", bottom)
+            }
+            else
+            {
+                format!("/({})", bottom)
+            }
+        )
+    }
+}
+impl Exact
+{
+    fn rational(r: Rational) -> Self
+    {
+        Exact(vec![Term::new(r)]).normalize()
+    }
+    fn term(term: Term) -> Self
+    {
+        Exact(vec![term]).normalize()
+    }
+    fn normalize(self) -> Self
+    {
+        let mut terms: Vec<Term> = Vec::new();
+        'outer: for t in self.0
+        {
+            for i in terms.iter_mut()
+            {
+                if i.like(&t)
+                {
+                    i.coef += t.coef;
+                    continue 'outer;
+                }
+            }
+            terms.push(t);
+        }
+        terms.retain(|t| t.coef != 0);
+        terms.sort_by(|a, b| {
+            b.is_rational()
+                .cmp(&a.is_rational())
+                .then(a.ln.cmp(&b.ln))
+                .then(a.e.cmp(&b.e))
+                .then(a.pi.cmp(&b.pi))
+                .then(b.root.cmp(&a.root))
+        });
+        Exact(terms)
+    }
+    fn single(&self) -> Option<Term>
+    {
+        match self.0.len()
+        {
+            0 => Some(Term::new(Rational::new())),
+            1 => Some(self.0[0].clone()),
+            _ => None,
+        }
+    }
+    fn as_rational(&self) -> Option<Rational>
+    {
+        let t = self.single()?;
+        if t.is_rational()
+        {
+            Some(t.coef)
+        }
+        else
+        {
+            None
+        }
+    }
+    fn add(&self, other: &Self) -> Self
+    {
+        Exact([self.0.clone(), other.0.clone()].concat()).normalize()
+    }
+    fn neg(&self) -> Self
+    {
+        Exact(
+            self.0
+                .iter()
+                .map(|t| {
+                    let mut t = t.clone();
+                    t.coef = -t.coef;
+                    t
+                })
+                .collect(),
+        )
+    }
+    fn mul(&self, other: &Self) -> Option<Self>
+    {
+        if self.0.len() * other.0.len() > 64
+        {
+            return None;
+        }
+        let mut terms = Vec::new();
+        for a in &self.0
+        {
+            for b in &other.0
+            {
+                terms.push(a.mul(b)?);
+            }
+        }
+        Some(Exact(terms).normalize())
+    }
+    fn div(&self, other: &Self) -> Option<Self>
+    {
+        let mut d = other.single()?;
+        if d.coef == 0
+        {
+            return None;
+        }
+        let mut n = self.clone();
+        if d.ln != 1
+        {
+            // ln(8)/ln(2) only cancels when every term shares the logarithm
+            if n.0.iter().any(|t| t.ln != d.ln)
+            {
+                return None;
+            }
+            for t in n.0.iter_mut()
+            {
+                t.ln = Integer::from(1);
+            }
+            d.ln = Integer::from(1);
+        }
+        n.mul(&Exact(vec![d.recip()?]))
+    }
+    fn pow(&self, other: &Self) -> Option<Self>
+    {
+        let p = other.as_rational()?;
+        if *p.denom() == 2
+        {
+            return self.sqrt()?.pow(&Exact::rational(p * 2));
+        }
+        if *p.denom() != 1
+        {
+            return None;
+        }
+        let n = p.numer().to_i32()?;
+        if n.abs() > 64
+        {
+            return None;
+        }
+        let base = if n < 0
+        {
+            Exact(vec![self.single()?.recip()?])
+        }
+        else
+        {
+            self.clone()
+        };
+        let mut result = Exact::rational(Rational::from(1));
+        for _ in 0..n.abs()
+        {
+            result = result.mul(&base)?;
+        }
+        Some(result)
+    }
+    fn sqrt(&self) -> Option<Self>
+    {
+        let t = self.single()?;
+        if t.coef < 0 || t.ln != 1 || t.root != 1 || t.pi % 2 != 0 || t.e % 2 != 0
+        {
+            return None;
+        }
+        let (k, root) = split_square(Integer::from(t.coef.numer() * t.coef.denom()));
+        Some(Exact::term(Term {
+            coef: Rational::from((k, t.coef.denom().clone())),
+            pi: t.pi / 2,
+            e: t.e / 2,
+            root,
+            ln: Integer::from(1),
+        }))
+    }
+    fn ln(&self) -> Option<Self>
+    {
+        let t = self.single()?;
+        if t.coef <= 0 || t.pi != 0 || t.ln != 1
+        {
+            return None;
+        }
+        let (n, d) = t.coef.into_numer_denom();
+        Some(
+            Exact::rational(Rational::from(t.e))
+                .add(&ln_int(n)?)
+                .add(&ln_int(d)?.neg())
+                .add(&ln_int(t.root)?.mul(&Exact::rational(Rational::from((1, 2))))?),
+        )
+    }
+    fn exp(&self) -> Option<Self>
+    {
+        let t = self.single()?;
+        if t.is_rational() && t.coef.is_integer()
+        {
+            let mut e = Term::new(Rational::from(1));
+            e.e = t.coef.numer().to_i32()?;
+            return Some(Exact::term(e));
+        }
+        if t.pi != 0 || t.e != 0 || t.root != 1 || !t.coef.is_integer()
+        {
+            return None;
+        }
+        // exp(k*ln(n))=n^k
+        Exact::rational(Rational::from(t.ln)).pow(&Exact::rational(t.coef))
+    }
+    // value of sin for a multiple of pi/12
+    fn sin_twelfths(m: i32) -> Self
+    {
+        let m = m.rem_euclid(24);
+        let sign = if m >= 12 { -1 } else { 1 };
+        let m = if m % 12 > 6 { 12 - m % 12 } else { m % 12 };
+        let root = |c: (i32, i32), r: u32| {
+            let mut t = Term::new(Rational::from(c) * sign);
+            t.root = Integer::from(r);
+            t
+        };
+        Exact(match m
+        {
+            0 => vec![],
+            1 => vec![root((1, 4), 6), root((-1, 4), 2)],
+            2 => vec![root((1, 2), 1)],
+            3 => vec![root((1, 2), 2)],
+            4 => vec![root((1, 2), 3)],
+            5 => vec![root((1, 4), 6), root((1, 4), 2)],
+            _ => vec![root((1, 1), 1)],
+        })
+        .normalize()
+    }
+    fn trig(&self, name: &str, deg: AngleType) -> Option<Self>
+    {
+        // express the angle as k*pi
+        let t = self.single()?;
+        let k = match deg
+        {
+            _ if t.coef == 0 => Rational::new(),
+            AngleType::Radians if t.pi == 1 && t.e == 0 && t.root == 1 && t.ln == 1 => t.coef,
+            AngleType::Degrees if t.is_rational() => t.coef / 180,
+            AngleType::Gradians if t.is_rational() => t.coef / 200,
+            _ => return None,
+        };
+        let m: Rational = k * 12;
+        if !m.is_integer()
+        {
+            return None;
+        }
+        let m = Integer::from(m.numer() % 24).to_i32()?;
+        let sin = Exact::sin_twelfths(m);
+        let cos = Exact::sin_twelfths(m + 6);
+        let one = Exact::rational(Rational::from(1));
+        match name
+        {
+            "sin" => Some(sin),
+            "cos" => Some(cos),
+            "tan" => sin.div(&cos),
+            "csc" => one.div(&sin),
+            "sec" => one.div(&cos),
+            "cot" => cos.div(&sin),
+            _ => None,
+        }
+    }
+    fn to_string(&self, options: Options) -> String
+    {
+        if self.0.is_empty()
+        {
+            return "0".to_string();
+        }
+        let mut out = String::new();
+        for (i, t) in self.0.iter().enumerate()
+        {
+            let s = t.to_string(options);
+            if i != 0 && !s.starts_with('-')
+            {
+                out.push('+');
+            }
+            out.push_str(&s);
+        }
+        out
+    }
+}
+// n=k^2*m with m square free, returns (k,m)
+fn split_square(n: Integer) -> (Integer, Integer)
+{
+    if n.is_perfect_square()
+    {
+        return (n.sqrt(), Integer::from(1));
+    }
+    let mut k = Integer::from(1);
+    let mut m = n;
+    let mut p = 2u32;
+    while p < 10000 && Integer::from(p) * p <= m
+    {
+        let sq = Integer::from(p) * p;
+        while m.is_divisible(&sq)
+        {
+            m /= &sq;
+            k *= p;
+        }
+        p += 1;
+    }
+    if m.is_perfect_square()
+    {
+        k *= m.sqrt();
+        m = Integer::from(1);
+    }
+    (k, m)
+}
+// ln(b^k)=k*ln(b) with the smallest possible b
+fn ln_int(n: Integer) -> Option<Exact>
+{
+    if n <= 0
+    {
+        return None;
+    }
+    if n == 1
+    {
+        return Some(Exact(Vec::new()));
+    }
+    let mut t = Term::new(Rational::from(1));
+    for k in (2..=n.significant_bits()).rev()
+    {
+        let b = n.clone().root(k);
+        if b.clone().pow(k) == n
+        {
+            t.coef = Rational::from(k);
+            t.ln = b;
+            return Some(Exact::term(t));
+        }
+    }
+    t.ln = n;
+    Some(Exact::term(t))
+}
+struct Parser
+{
+    chars: Vec<char>,
+    pos: usize,
+    deg: AngleType,
+}
+impl Parser
+{
+    fn peek(&mut self) -> Option<char>
+    {
+        while self.chars.get(self.pos) == Some(&' ')
+        {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+    fn expr(&mut self) -> Option<Exact>
+    {
+        let mut a = self.term()?;
+        loop
+        {
+            match self.peek()
+            {
+                Some('+') =>
+                {
+                    self.pos += 1;
+                    a = a.add(&self.term()?);
+                }
+                Some('-') =>
+                {
+                    self.pos += 1;
+                    a = a.add(&self.term()?.neg());
+                }
+                _ => return Some(a),
+            }
+        }
+    }
+    fn term(&mut self) -> Option<Exact>
+    {
+        let mut a = self.unary()?;
+        loop
+        {
+            match self.peek()
+            {
+                Some('*') =>
+                {
+                    self.pos += 1;
+                    a = a.mul(&self.unary()?)?;
+                }
+                Some('/') =>
+                {
+                    self.pos += 1;
+                    a = a.div(&self.unary()?)?;
+                }
+                Some(c) if c.is_ascii_alphanumeric() || "(.√πτ".contains(c) =>
+                {
+                    a = a.mul(&self.power()?)?;
+                }
+                _ => return Some(a),
+            }
+        }
+    }
+    fn unary(&mut self) -> Option<Exact>
+    {
+        match self.peek()
+        {
+            Some('-') =>
+            {
+                self.pos += 1;
+                Some(self.unary()?.neg())
+            }
+            Some('+') =>
+            {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+    fn power(&mut self) -> Option<Exact>
+    {
+        let base = self.primary()?;
+        if self.peek() == Some('^')
+        {
+            self.pos += 1;
+            let exp = self.unary()?;
+            return base.pow(&exp);
+        }
+        Some(base)
+    }
+    fn primary(&mut self) -> Option<Exact>
+    {
+        let c = self.peek()?;
+        if c.is_ascii_digit() || c == '.'
+        {
+            return self.number();
+        }
+        self.pos += 1;
+        match c
+        {
+            '(' =>
+            {
+                let a = self.expr()?;
+                match self.peek()
+                {
+                    Some(')') => self.pos += 1,
+                    None => (),
+                    _ => return None,
+                }
+                Some(a)
+            }
+            'π' => self.constant("pi"),
+            'τ' => self.constant("tau"),
+            '√' => self.primary()?.sqrt(),
+            c if c.is_ascii_alphabetic() =>
+            {
+                let start = self.pos - 1;
+                while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_alphabetic()
+                {
+                    self.pos += 1;
+                }
+                let word = self.chars[start..self.pos].iter().collect::<String>();
+                // words like "pie" are read as pi*e
+                let name = [
+                    "sqrt", "tau", "phi", "exp", "log", "sin", "cos", "tan", "csc", "sec", "cot",
+                    "ln", "pi", "e",
+                ]
+                .into_iter()
+                .find(|n| word.starts_with(n))?;
+                self.pos = start + name.len();
+                match name
+                {
+                    "pi" | "tau" | "phi" | "e" => self.constant(name),
+                    _ =>
+                    {
+                        let args = self.args()?;
+                        match (name, args.len())
+                        {
+                            ("sqrt", 1) => args[0].sqrt(),
+                            ("exp", 1) => args[0].exp(),
+                            ("ln" | "log", 1) => args[0].ln(),
+                            ("log", 2) => args[1].ln()?.div(&args[0].ln()?),
+                            (_, 1) => args[0].trig(name, self.deg),
+                            _ => None,
+                        }
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+    fn args(&mut self) -> Option<Vec<Exact>>
+    {
+        if self.peek() != Some('(')
+        {
+            return Some(vec![self.power()?]);
+        }
+        self.pos += 1;
+        let mut args = vec![self.expr()?];
+        loop
+        {
+            match self.peek()
+            {
+                Some(',') =>
+                {
+                    self.pos += 1;
+                    args.push(self.expr()?);
+                }
+                Some(')') =>
+                {
+                    self.pos += 1;
+                    return Some(args);
+                }
+                None => return Some(args),
+                _ => return None,
+            }
+        }
+    }
+    fn constant(&self, name: &str) -> Option<Exact>
+    {
+        let mut t = Term::new(Rational::from(1));
+        match name
+        {
+            "pi" => t.pi = 1,
+            "tau" =>
+            {
+                t.pi = 1;
+                t.coef = Rational::from(2);
+            }
+            "e" => t.e = 1,
+            _ =>
+            {
+                // (1+sqrt(5))/2
+                t.coef = Rational::from((1, 2));
+                t.root = Integer::from(5);
+                return Some(Exact(vec![Term::new(Rational::from((1, 2))), t]).normalize());
+            }
+        }
+        Some(Exact::term(t))
+    }
+    fn number(&mut self) -> Option<Exact>
+    {
+        let mut digits = String::new();
+        let mut decimals: i32 = 0;
+        let mut dot = false;
+        while let Some(&c) = self.chars.get(self.pos)
+        {
+            if c.is_ascii_digit()
+            {
+                digits.push(c);
+                if dot
+                {
+                    decimals = decimals.checked_add(1)?;
+                }
+            }
+            else if c == '.' && !dot
+            {
+                dot = true;
+            }
+            else
+            {
+                break;
+            }
+            self.pos += 1;
+        }
+        let mut exponent = 0;
+        if self.chars.get(self.pos) == Some(&'E')
+        {
+            let start = self.pos + 1;
+            self.pos = start;
+            if self.chars.get(self.pos) == Some(&'-')
+            {
+                self.pos += 1;
+            }
+            while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_digit()
+            {
+                self.pos += 1;
+            }
+            exponent = self.chars[start..self.pos]
+                .iter()
+                .collect::<String>()
+                .parse::<i32>()
+                .ok()?;
+        }
+        let power = exponent.checked_sub(decimals)?;
+        if power.unsigned_abs() > BITS
+        {
+            return None;
+        }
+        let n = Rational::from(digits.parse::<Integer>().ok()?) * Rational::from(10).pow(power);
+        if !small(&n)
+        {
+            return None;
+        }
+        Some(Exact::rational(n))
+    }
+}
+pub fn exact(input: &str, vars: &[[String; 2]], options: Options) -> Option<String>
+{
+    // keep the exact constants symbolic unless the user redefined them
+    let constants = get_vars(options.prec);
+    let vars = vars
+        .iter()
+        .filter(|v| !(["pi", "tau", "e", "phi"].contains(&v[0].as_str()) && constants.contains(v)))
+        .cloned()
+        .collect::<Vec<[String; 2]>>();
+    let mut parser = Parser {
+        chars: input_var(input, &vars, None).chars().collect(),
+        pos: 0,
+        deg: options.deg,
+    };
+    let n = parser.expr()?;
+    if parser.peek().is_some()
+    {
+        return None;
+    }
+    Some(n.to_string(options))
+}
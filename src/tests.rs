@@ -1,12 +1,39 @@
 use crate::{
     complex::{
-        Float,
-        NumStr::{Num, Str},
+        Float, NumStr,
+        NumStr::{Num, Str, Vector},
     },
-    math::do_math,
-    parse::{get_func, get_vars, input_var},
+    edit::{complete, highlight, incomplete, Line},
+    graph::get_list_2d,
+    history_matches,
+    linalg::{eig, eigvals, svd},
+    markup::{from_latex, markup},
+    math::{do_math, recall, recursion_limit},
+    options::{arg_opts, option, set_option, setting, AngleType, Config, Format, Notation},
+    parse::{
+        conversion, forward_default, free_vars, get_func, get_vars, graphs, input_var, is_graph,
+        user_functions, Function,
+    },
+    poly::{expand, factor, solve_equation},
+    print::{diagnose, get_output, json_answer, json_error, pretty_matrix, stack},
+    reply,
+    script::Script,
+    session::{load, save},
+    symbolic::exact,
+    write, Options,
 };
 use rug::{float::Constant::Pi, Complex};
+// an input's value with the variables and user functions given
+fn eval(input: &str, vars: &[[String; 2]], funcs: &[Function]) -> NumStr
+{
+    do_math(
+        get_func(&input_var(input, vars, None), 512).unwrap(),
+        AngleType::Radians,
+        512,
+        funcs,
+    )
+    .unwrap()
+}
 #[test]
 fn test_math()
 {
@@ -63,8 +90,14 @@ fn test_math()
         Str(")".to_string()),
         Str(")".to_string()),
     ];
-    let out = do_math(output, 0, 512).unwrap().num().unwrap();
-    let answer = do_math(expected, 0, 512).unwrap().num().unwrap();
+    let out = do_math(output, AngleType::Radians, 512, &[])
+        .unwrap()
+        .num()
+        .unwrap();
+    let answer = do_math(expected, AngleType::Radians, 512, &[])
+        .unwrap()
+        .num()
+        .unwrap();
     assert_eq!(out.real().to_string(), answer.real().to_string());
     assert_eq!(out.imag().to_string(), answer.imag().to_string());
     assert_eq!(&out.real().to_string()[..20], "2.009877988310399125");
@@ -82,4 +115,887 @@ fn test_complex()
     assert_eq!(a.sin(), (-2.8472390868488278, 2.370674169352002));
     assert_eq!(a.cos(), (-2.4591352139173837, -2.7448170067921542));
     assert_eq!(a.tan(), (0.03642336924740369, -1.004682312190235));
-}
\ No newline at end of file
+}
+#[test]
+fn test_exact()
+{
+    let vars = get_vars(512);
+    let options = Options::default();
+    assert_eq!(exact("sqrt(8)", &vars, options).unwrap(), "2√2");
+    assert_eq!(exact("pi/6", &vars, options).unwrap(), "π/6");
+    assert_eq!(exact("ln(4)", &vars, options).unwrap(), "2 ln 2");
+    assert_eq!(exact("1/sqrt(2)", &vars, options).unwrap(), "√2/2");
+    assert_eq!(exact("sin(pi/12)", &vars, options).unwrap(), "√6/4-√2/4");
+    assert_eq!(exact("log(2,8)", &vars, options).unwrap(), "3");
+    assert!(exact("sqrt(2+sqrt(3))", &vars, options).is_none());
+    // coefficients too large to be worth writing out go to the numeric output
+    assert!(exact("((3^64)^64)^64", &vars, options).is_none());
+    assert!(exact("1.5E2147483647", &vars, options).is_none());
+    assert!(exact("0.5E-2147483648", &vars, options).is_none());
+    assert_eq!(exact("2.5E3", &vars, options).unwrap(), "2500");
+}
+#[test]
+fn test_poly()
+{
+    let vars = get_vars(512);
+    let options = Options::default();
+    let roots = do_math(
+        get_func("polyroots({1,-6,11,-6})", 512).unwrap(),
+        AngleType::Radians,
+        512,
+        &[],
+    )
+    .unwrap()
+    .vec()
+    .unwrap();
+    for (r, n) in roots.iter().zip([1.0, 2.0, 3.0])
+    {
+        assert!((r.real().to_f64() - n).abs() < 1e-50 && r.imag().is_zero());
+    }
+    let div = do_math(
+        get_func("polydiv({1,2,3},{1,1})", 512).unwrap(),
+        AngleType::Radians,
+        512,
+        &[],
+    )
+    .unwrap()
+    .mat()
+    .unwrap();
+    assert_eq!(div[0][1], 1.0);
+    assert_eq!(div[1][1], 2.0);
+    // an empty coefficient vector is an error rather than an out of bounds index
+    for input in [
+        "polymul(filter(x->x>5,{1,2}),{1})",
+        "polydiv({1},filter(x->x>5,{1,2}))",
+        "polyder(filter(x->x>5,{1,2}))",
+        "polyroots(filter(x->x>5,{1,2}))",
+        "polyroots({1,0/0,1})",
+    ]
+    {
+        let func = get_func(&input_var(input, &vars, None), 512).unwrap();
+        assert!(do_math(func, AngleType::Radians, 512, &[]).is_err());
+    }
+    assert_eq!(
+        expand("(x-1)(x+2)^2", &vars, options).unwrap(),
+        "x^3+3x^2-4"
+    );
+    assert_eq!(factor("2x^3-2x", &vars, options).unwrap(), "2x(x-1)(x+1)");
+    // factors without rational roots are found too
+    assert_eq!(
+        factor("x^6-1", &vars, options).unwrap(),
+        "(x-1)(x+1)(x^2-x+1)(x^2+x+1)"
+    );
+    assert_eq!(
+        factor("x^4+4", &vars, options).unwrap(),
+        "(x^2-2x+2)(x^2+2x+2)"
+    );
+    assert_eq!(factor("(x^2+1)^2", &vars, options).unwrap(), "(x^2+1)^2");
+    assert_eq!(factor("x^4+1", &vars, options).unwrap(), "x^4+1");
+    assert!(factor("x^16+1", &vars, options)
+        .unwrap()
+        .ends_with("(could not fully factor)"));
+}
+#[test]
+fn test_edit()
+{
+    let mut line = Line::new();
+    line.insert_str("2π+√(3)");
+    line.left();
+    line.left();
+    line.backspace();
+    assert_eq!(line.text(), "2π+√3)");
+    line.word_right();
+    assert_eq!(line.cursor, 5);
+    line.kill_word();
+    assert_eq!(line.text(), "2π+√)");
+    line.word_left();
+    line.kill_end();
+    assert_eq!(line.text(), "");
+    line.insert_str("sin(x)+cos");
+    line.cursor = 6;
+    line.kill_start();
+    line.delete();
+    assert_eq!(line.text(), "cos");
+    assert!(incomplete("{{1,2},") && incomplete("1+\\") && !incomplete("sqrt(2"));
+    let vars = vec![["logistic(x)".to_string(), "1/(1+exp(-x))".to_string()]];
+    let mut line = Line::new();
+    line.insert_str("2polyr");
+    assert!(complete(&mut line, &vars).is_empty());
+    assert_eq!(line.text(), "2polyroots(");
+    // not at the start, where the load command would be a choice too
+    line.set("1+lo");
+    assert!(complete(&mut line, &vars).is_empty());
+    assert_eq!(line.text(), "1+log");
+    assert_eq!(
+        complete(&mut line, &vars),
+        vec!["log(base,num)", "logistic(x)", "logm(x)"]
+    );
+    line.set("tabb");
+    complete(&mut line, &vars);
+    assert_eq!(line.text(), "tabbed");
+    let options = Options::default();
+    line.set("sin(2)+foo");
+    assert_eq!(
+        highlight(&line, 0, 10, Some(3), &vars, options),
+        "\x1b[0;95msin\x1b[0;1;92m(\x1b[0;96m2\x1b[0;1;92m)\x1b[0m+\x1b[0;91mfoo"
+    );
+    line.set("sum(n,n,1,x");
+    assert_eq!(
+        highlight(&line, 3, 11, None, &vars, options),
+        "\x1b[0;91m(\x1b[0;93mn\x1b[0m,\x1b[0;93mn\x1b[0m,\x1b[0;96m1\x1b[0m,\x1b[0;93mx"
+    );
+}
+#[test]
+fn test_free_vars()
+{
+    let vars = get_vars(512);
+    let func = get_func("sum(n*x,n,1,3)+exp(y)", 512).unwrap();
+    assert_eq!(free_vars(&func), vec!["x", "y"]);
+    assert!(is_graph(&func));
+    assert!(free_vars(&get_func("sum(n,n,1,3)", 512).unwrap()).is_empty());
+    let func = get_func("2a+b^2", 512).unwrap();
+    assert_eq!(free_vars(&func), vec!["a", "b"]);
+    assert!(!is_graph(&func));
+    // zeta is a function, not z*eta
+    let func = get_func(&input_var("zeta(2)", &vars, None), 512).unwrap();
+    assert!(free_vars(&func).is_empty());
+    let zeta = eval("zeta(2)-pi^2/6", &vars, &[]).num().unwrap();
+    assert!(zeta.abs().real().to_f64() < 1e-100);
+    let options = Options {
+        color: false,
+        ..Options::default()
+    };
+    assert_eq!(solve_equation("2x+1", "5", &vars, options).unwrap(), "x=2");
+    assert_eq!(
+        solve_equation("t^2", "4", &vars, options).unwrap(),
+        "t=-2\nt=2"
+    );
+    assert_eq!(
+        solve_equation("x+1", "x", &vars, options).unwrap(),
+        "no solution"
+    );
+    assert!(solve_equation("a", "b", &vars, options).is_none());
+    // exp(x) only gets small, newton running off to the left finds no root there
+    assert_eq!(
+        solve_equation("exp(x)", "0", &vars, options).unwrap(),
+        "no solution found"
+    );
+    assert_eq!(
+        solve_equation("(x-1)^2*exp(x)", "0", &vars, options).unwrap(),
+        "x=1"
+    );
+    let hint = |input: &str| diagnose(input, &get_func(input, 512).unwrap(), options, &[]);
+    assert_eq!(hint("2a+1").unwrap(), "unknown variable a");
+    assert_eq!(hint("foo(2)").unwrap(), "unknown function foo");
+    assert_eq!(
+        hint("1+sqrt({1,2}+{1,2,3})").unwrap(),
+        "dimension mismatch: 2-vector + 3-vector"
+    );
+    assert!(hint("ex").is_none());
+}
+#[test]
+fn test_linalg()
+{
+    let a = eval("{{1,2,3},{4,5,6},{7,8,10}}", &[], &[]).mat().unwrap();
+    let (values, vectors) = eig(&a).unwrap();
+    for (k, l) in values.iter().enumerate()
+    {
+        for (row, v) in a.iter().zip(vectors.iter())
+        {
+            let mut n = -l.clone() * &v[k];
+            for (x, w) in row.iter().zip(vectors.iter())
+            {
+                n += x.clone() * &w[k];
+            }
+            assert!(n.abs().real().to_f64() < 1e-100);
+        }
+    }
+    let usv = svd(&a).unwrap();
+    for i in 0..3
+    {
+        for j in 0..3
+        {
+            let mut n = -a[i][j].clone();
+            for (k, (u, v)) in usv[0][i].iter().zip(usv[2][j].iter()).enumerate()
+            {
+                n += u.clone() * &usv[1][k][k] * v.clone().conj();
+            }
+            assert!(n.abs().real().to_f64() < 1e-100);
+        }
+    }
+    let rank = eval("rank({{1,2},{2,4}})", &[], &[]).num().unwrap();
+    assert_eq!(rank, 1.0);
+    let rank = eval("rank({{1,0},{0,1}}/10^100)", &[], &[]).num().unwrap();
+    assert_eq!(rank, 2.0);
+    // nan eigenvalues have no order to sort by
+    assert!(eigvals(&eval("{{0/0,0},{0,1}}", &[], &[]).mat().unwrap()).is_err());
+    // full rank has no basis to give
+    assert!(eval("nullspace({{1,0},{0,1}})", &[], &[])
+        .vec()
+        .unwrap()
+        .is_empty());
+    let l = eval("part(lu({{1,2},{3,4}}),1)", &[], &[]).mat().unwrap();
+    assert_eq!(l[0][0], 1.0);
+    assert_eq!(l[0][1], 0.0);
+    let det = eval("det({{2,0,1,3},{1,1,0,2},{0,3,1,1},{4,1,2,0}})", &[], &[])
+        .num()
+        .unwrap();
+    assert_eq!(det, -32.0);
+    assert!(eval("linsolve({{1,2},{2,4}},{1,2})", &[], &[]).str_is("singular matrix"));
+    // an infinite entry can't be scaled down, it should come back as nan instead of hanging
+    let m = eval("expm({{1/0,1},{0,1}})", &[], &[]).mat().unwrap();
+    assert!(m.iter().flatten().all(|z| z.real().is_nan()));
+    let vars = get_vars(512);
+    for (input, expected) in [
+        ("sqrtm({{2,3},{6,7}})^2", [[2.0, 3.0], [6.0, 7.0]]),
+        ("expm(logm({{2,1},{1,2}}))", [[2.0, 1.0], [1.0, 2.0]]),
+        (
+            "{{2,3},{6,7}}^1.5*{{2,3},{6,7}}^0.5",
+            [[22.0, 27.0], [54.0, 67.0]],
+        ),
+        ("exp({{0,-pi},{pi,0}})", [[-1.0, 0.0], [0.0, -1.0]]),
+        // without a basis of eigenvectors
+        ("{{1,1},{0,1}}^0.5", [[1.0, 0.5], [0.0, 1.0]]),
+        ("sqrtm({{4,1},{0,4}})", [[2.0, 0.25], [0.0, 2.0]]),
+        ("logm({{1,1},{0,1}})", [[0.0, 1.0], [0.0, 0.0]]),
+        ("{{4,1},{0,4}}^1.5", [[8.0, 3.0], [0.0, 8.0]]),
+        ("{{1,2},{3,4}}.^2", [[1.0, 4.0], [9.0, 16.0]]),
+        ("inv({{2,1},{1,3}})*5", [[3.0, -1.0], [-1.0, 2.0]]),
+        // singular is relative to the largest entry, not to 1
+        ("inv({{1,0},{0,1}}/10^100)/10^100", [[1.0, 0.0], [0.0, 1.0]]),
+        ("({{1,0},{0,2}}/10^100)^-1/10^100", [[1.0, 0.0], [0.0, 0.5]]),
+        (
+            "linsolve({{1,0},{0,1}}/10^100,{{1,0},{0,1}})/10^100",
+            [[1.0, 0.0], [0.0, 1.0]],
+        ),
+        (
+            "linsolve({{2,1},{1,3}},{{3,1},{5,0}})*5",
+            [[4.0, 3.0], [7.0, -1.0]],
+        ),
+    ]
+    {
+        let m = eval(input, &vars, &[]).mat().unwrap();
+        for (row, e) in m.iter().zip(expected.iter())
+        {
+            for (z, e) in row.iter().zip(e.iter())
+            {
+                let d: Complex = z.clone() - *e;
+                assert!(d.abs().real().to_f64() < 1e-100);
+            }
+        }
+    }
+}
+#[test]
+fn test_script()
+{
+    let options = Options {
+        color: false,
+        ..Options::default()
+    };
+    let mut vars = get_vars(512);
+    let mut script = Script::new(
+        "test.kalc",
+        "# counts to three\nn = 0\nwhile n < 3\n  n = n+1\n  if n == 2 # the middle\n    print \"n is {n}\"\n  else\n    f(x) = x#x^2\n  end\nend\nfor i in {4,5}\n  i\nend",
+    )
+    .unwrap();
+    let mut lines = Vec::new();
+    while let Some(l) = script.next(&vars, options).unwrap()
+    {
+        // what the main loop does with an assignment
+        if let Some((name, value)) = l.split_once('=')
+        {
+            vars.retain(|v| v[0] != name);
+            vars.push([name.to_string(), value.to_string()]);
+        }
+        lines.push(l);
+    }
+    assert_eq!(
+        lines,
+        vec![
+            "n=(0)",
+            "n=(1)",
+            "f(x)=x#x^2",
+            "n=(2)",
+            "n=(3)",
+            "f(x)=x#x^2",
+            "i=(4)",
+            "i",
+            "i=(5)",
+            "i"
+        ]
+    );
+    assert!(Script::new("test.kalc", "if 1\nelse\nelse\nend").is_err());
+    assert!(Script::new("test.kalc", "for i in 1..3").is_err());
+}
+#[test]
+fn test_functions()
+{
+    let mut vars = get_vars(512);
+    for (l, r) in [
+        ("fib(n)", "if(n<2,n,fib(n-1)+fib(n-2))"),
+        ("p(x,n=2)", "x^n"),
+        ("a", "5"),
+        ("g(a)", "a+1"),
+        ("h(x)", "max(x,1)"),
+        ("c(n)", "if(n<1,0,c(n-1)+1)"),
+        ("k(a=1)", "a"),
+        ("s(x,y=x)", "x+y"),
+    ]
+    {
+        vars.push([l.to_string(), r.to_string()]);
+    }
+    let funcs = user_functions(&vars, 512);
+    let value = |input: &str| eval(input, &vars, &funcs).num().unwrap().real().to_f64();
+    assert_eq!(value("fib(10)"), 55.0);
+    assert_eq!(value("2fib(5)-fib(fib(4))"), 8.0);
+    assert_eq!(value("p(3)"), 9.0);
+    assert_eq!(value("p(3,3)"), 27.0);
+    // the parameter a isn't replaced by the variable a
+    assert_eq!(value("g(2)"), 3.0);
+    assert_eq!(value("h(-3)"), 1.0);
+    assert_eq!(value("if(a>2,1,1/0)"), 1.0);
+    // every argument can be left to its default, and a default can use the parameters before it
+    assert_eq!(value("k()"), 1.0);
+    assert_eq!(value("k(5)"), 5.0);
+    assert_eq!(value("s(2)"), 4.0);
+    assert_eq!(value("s(2,3)"), 5.0);
+    assert_eq!(forward_default("s(x,y=x)", 512), None);
+    assert_eq!(
+        forward_default("m(x=y,y=1)", 512),
+        Some(("x".to_string(), "y".to_string()))
+    );
+    // deep recursion has the stack it needs until the limit, which is said
+    assert_eq!(value("c(250)"), 250.0);
+    let deep = get_func(&input_var("c(300)", &vars, None), 512).unwrap();
+    assert!(do_math(deep.clone(), AngleType::Radians, 512, &funcs).is_err());
+    assert!(recursion_limit());
+    assert_eq!(
+        diagnose("c(300)", &deep, Options::default(), &funcs),
+        Some("recursion limit reached".to_string())
+    );
+}
+#[test]
+fn test_conditionals()
+{
+    let value = |input: &str| eval(input, &[], &[]).num().unwrap().real().to_f64();
+    assert_eq!(value("(-3<1)+(2<=2)+(1>2)+(1==1)"), 3.0);
+    assert_eq!(value("(1<2)&&(3)"), 1.0);
+    assert_eq!(value("0||(2>3)"), 0.0);
+    assert_eq!(value("if(2>1,5,1/0)"), 5.0);
+    assert!(value("if(2<1,5)").is_nan());
+    assert_eq!(value("piecewise({-2>0,1},{-2<0,2},3)"), 2.0);
+    assert_eq!(value("piecewise({2>3,1},{2>4,2},3)"), 3.0);
+    assert!(value("piecewise({2>3,1})").is_nan());
+    let options = Options {
+        samples_2d: 200.0,
+        ..Options::default()
+    };
+    // a step plots as flat pieces with a break at each jump, a line has none
+    let breaks = |input: &str| {
+        get_list_2d(
+            &get_func(input, 512).unwrap(),
+            options,
+            AngleType::Radians,
+            512,
+            &[],
+        )
+        .0
+        .iter()
+        .filter(|p| p[1].is_nan())
+        .count()
+    };
+    assert_eq!(breaks("piecewise({x<0,-1},{x<5,x},2)"), 2);
+    assert_eq!(breaks("(x>0)+(x>3)"), 2);
+    assert_eq!(breaks("2x+1"), 0);
+    assert_eq!(breaks("x^3-20x"), 0);
+    assert_eq!(breaks("if(x<0,x)"), 0);
+}
+#[test]
+fn test_higher_order()
+{
+    let mut vars = get_vars(512);
+    vars.push(["a".to_string(), "5".to_string()]);
+    vars.push(["sq(x)".to_string(), "x^2".to_string()]);
+    let funcs = user_functions(&vars, 512);
+    let eval = |input: &str| {
+        let func = get_func(&input_var(input, &vars, None), 512).unwrap();
+        assert!(!is_graph(&func));
+        do_math(func, AngleType::Radians, 512, &funcs)
+            .unwrap()
+            .vec()
+            .unwrap()
+            .iter()
+            .map(|n| n.real().to_f64())
+            .collect::<Vec<f64>>()
+    };
+    assert_eq!(eval("map(x->x^2,{1,2,3})"), vec![1.0, 4.0, 9.0]);
+    // the parameter a isn't replaced by the variable a, the a outside the lambda is
+    assert_eq!(eval("map(a -> a+1, {1,2})*a"), vec![10.0, 15.0]);
+    assert_eq!(eval("map(sq,{1,2})"), vec![1.0, 4.0]);
+    assert_eq!(eval("map(sqrt,{4,9})"), vec![2.0, 3.0]);
+    assert_eq!(eval("filter(n->n>2,{1,2,3,4})"), vec![3.0, 4.0]);
+    assert_eq!(eval("{reduce((p,q)->p*q,{1,2,3,4})}"), vec![24.0]);
+    assert_eq!(eval("{fold((s,v)->s+v^2,10,{1,2})}"), vec![15.0]);
+    assert_eq!(eval("zipwith((u,v)->u*v,{1,2},{3,4})"), vec![3.0, 8.0]);
+    assert_eq!(eval("table(k^2,k,1,4)"), vec![1.0, 4.0, 9.0, 16.0]);
+    assert_eq!(eval("table(t,t,0,1,0.25)"), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    assert_eq!(eval("table(n,n,3,1,-1)"), vec![3.0, 2.0, 1.0]);
+    // the index a shadows the variable a inside the call, not after it
+    assert_eq!(eval("table(a^2,a,1,5,1)"), vec![1.0, 4.0, 9.0, 16.0, 25.0]);
+    assert_eq!(eval("{sum(a,a,1,4)*a}"), vec![50.0]);
+}
+#[test]
+fn test_options()
+{
+    let mut options = Options::default();
+    let mut args = [
+        "kalc",
+        "--prec=64",
+        "--xr",
+        "-2",
+        "3",
+        "--deg",
+        "--sci",
+        "1+1",
+        "--point=O",
+    ]
+    .map(String::from)
+    .to_vec();
+    assert!(!arg_opts(&mut options, &mut args, &Config::default()));
+    assert_eq!(args, vec!["1+1"]);
+    assert_eq!(set_option("get", "precision", &mut options), "64");
+    assert_eq!(set_option("get", "xr", &mut options), "-2,3");
+    assert_eq!(set_option("get", "deg", &mut options), "true");
+    assert_eq!(set_option("get", "point", &mut options), "O");
+    assert!(options.notation == Notation::Sci);
+    assert_eq!(set_option("set", "base=16", &mut options), "base=16");
+    assert_eq!(
+        set_option("set", "base=40", &mut options),
+        "Invalid base, expected a whole number from 2 to 36"
+    );
+    assert_eq!(set_option("set", "deci=-2", &mut options), "deci=-2");
+    assert_eq!(set_option("reset", "deci", &mut options), "deci=12");
+    assert_eq!(set_option("reset", "prec", &mut options), "prec=512");
+    assert_eq!(
+        set_option("get", "nope", &mut options),
+        "Unknown option nope"
+    );
+    // pi and deg are also a constant and a function, so only set reaches them
+    assert!(setting("pi").is_none() && setting("deg").is_none());
+    assert!(setting("sci").is_some_and(|o| o.name == "sci"));
+    option("sci").unwrap().toggle(&mut options);
+    option("grad").unwrap().toggle(&mut options);
+    assert!(options.notation == Notation::Auto && options.deg == AngleType::Gradians);
+    let mut args = ["kalc", "--prec=0"].map(String::from).to_vec();
+    assert!(arg_opts(&mut options, &mut args, &Config::default()));
+}
+#[test]
+fn test_config()
+{
+    let text = "sci = true # comment
+xr = [-5, 5]
+point = \".\"
+deg
+units = \"si\"
+
+[profile.hex]
+base = 16
+deci = 0
+
+[profile.physics]
+deg = true
+prec = 0
+
+[other]
+base = 2
+";
+    let (config, errors) = Config::parse(text);
+    assert_eq!(
+        errors,
+        vec![
+            (5, "Unknown option units".to_string()),
+            (
+                13,
+                "Invalid prec, expected a whole number above 0".to_string()
+            ),
+            (15, "Unknown table [other]".to_string()),
+        ]
+    );
+    assert_eq!(config.profiles(), vec!["hex", "physics"]);
+    let mut options = Options::default();
+    let mut args = ["kalc", "--profile", "hex", "2"].map(String::from).to_vec();
+    assert!(!arg_opts(&mut options, &mut args, &config));
+    assert_eq!(args, vec!["2"]);
+    assert_eq!(options.base, 16);
+    assert_eq!(
+        config.profile("physics", &mut options),
+        Some("deg=true\nprec=512".to_string())
+    );
+    assert!(config.profile("nope", &mut options).is_none());
+    let mut args = ["kalc", "--profile=nope"].map(String::from).to_vec();
+    assert!(arg_opts(&mut options, &mut args, &config));
+}
+#[test]
+fn test_session()
+{
+    let defaults = get_vars(512);
+    let mut vars = defaults.clone();
+    vars.push(["a".to_string(), "5".to_string()]);
+    vars.push(["f(x)".to_string(), "x^2+a".to_string()]);
+    let mut options = Options::default();
+    set_option("set", "base=16", &mut options);
+    set_option("set", "session=true", &mut options);
+    let answers = vec![
+        ("f(2)".to_string(), Num(Complex::with_val(512, 9))),
+        ("1/3".to_string(), Num(Complex::with_val(512, 1) / 3)),
+    ];
+    let path = std::env::temp_dir().join("kalc_test_session.kalc");
+    let path = path.to_str().unwrap();
+    save(path, &vars, &defaults, &options, &answers).unwrap();
+    let text = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    // the constants aren't written, they come back on their own
+    assert!(text.contains("\na=5\nf(x)=x^2+a\nval (9,0) f(2)\nval (3.33"));
+    assert!(text.contains("e-1,0) 1/3\n"));
+    assert!(!text.contains("session") && !text.contains("\npi="));
+    let (mut vars, mut options, mut answers) = (defaults.clone(), Options::default(), Vec::new());
+    vars.push(["a".to_string(), "1".to_string()]);
+    // the answers keep their values when the variables they used change
+    let errors = load(
+        &(text.replace("a=5", "a=7") + "bogus\nset nope=1\nans $1+$2\nans $9\nval (1,x) 1\n"),
+        &mut vars,
+        &mut options,
+        &mut answers,
+    );
+    assert_eq!(errors.len(), 4);
+    assert_eq!(vars.len(), defaults.len() + 2);
+    assert!(vars.contains(&["a".to_string(), "7".to_string()]));
+    assert_eq!(options.base, 16);
+    assert!(!options.session);
+    assert_eq!(
+        answers.iter().map(|a| a.0.as_str()).collect::<Vec<&str>>(),
+        vec!["f(2)", "1/3", "$1+$2"]
+    );
+    assert_eq!(answers[0].1.num().unwrap(), 9);
+    assert_eq!(answers[1].1.num().unwrap(), Complex::with_val(512, 1) / 3);
+    assert_eq!(answers[2].1.num().unwrap(), Complex::with_val(512, 28) / 3);
+}
+#[test]
+fn test_answers()
+{
+    let vars = get_vars(512);
+    let answers = vec![
+        ("2".to_string(), Num(Complex::with_val(512, 2))),
+        ("3".to_string(), Num(Complex::with_val(512, 3))),
+        (
+            "{1,2}".to_string(),
+            Vector(vec![Complex::with_val(512, 1), Complex::with_val(512, 2)]),
+        ),
+    ];
+    let run = |input: &str| {
+        do_math(
+            recall(
+                get_func(&input_var(input, &vars, None), 512)?,
+                &answers,
+                512,
+            )?,
+            AngleType::Radians,
+            512,
+            &[],
+        )
+    };
+    // $n counts from the first answer, ans(n) from the last when negative and _ is the last one
+    assert_eq!(run("$1+$2").unwrap().num().unwrap(), 5);
+    assert_eq!(run("2$2").unwrap().num().unwrap(), 6);
+    assert_eq!(run("ans(1+1)^2").unwrap().num().unwrap(), 9);
+    assert_eq!(run("ans(-2)").unwrap().num().unwrap(), 3);
+    assert_eq!(run("_").unwrap().vec().unwrap().len(), 2);
+    assert_eq!(run("$1*_").unwrap().vec().unwrap()[1], 4);
+    assert_eq!(run("_-1").unwrap().vec().unwrap()[1], 1);
+    // a missing answer is a message so a script or a pipe carries on
+    assert!(run("$4").unwrap().str_is("no answer 4"));
+    assert!(run("ans(0)").unwrap().str_is("no answer 0"));
+    assert!(run("ans(-4)").unwrap().str_is("no answer -4"));
+    assert!(run("ans(1.5)").is_err());
+    assert!(run("$").is_err());
+    // an underscore in a name is part of the name
+    let mut vars = vars.clone();
+    vars.push(["a_b".to_string(), "7".to_string()]);
+    assert_eq!(eval("a_b+1", &vars, &[]).num().unwrap(), 8);
+}
+#[test]
+fn test_json()
+{
+    let vars = get_vars(512);
+    let run = |input: &str| json_answer(input, &eval(input, &vars, &[]));
+    assert_eq!(
+        run("2.5-3i"),
+        r#"{"input":"2.5-3i","type":"number","real":"2.5","imag":"-3"}"#
+    );
+    assert_eq!(
+        run("{1,i}"),
+        r#"{"input":"{1,i}","type":"vector","value":[{"real":"1","imag":"0"},{"real":"0","imag":"1"}]}"#
+    );
+    assert_eq!(
+        run("{{1,2},{3,4}}"),
+        r#"{"input":"{{1,2},{3,4}}","type":"matrix","value":[[{"real":"1","imag":"0"},{"real":"2","imag":"0"}],[{"real":"3","imag":"0"},{"real":"4","imag":"0"}]]}"#
+    );
+    // the digits go to the full precision
+    assert!(run("1/3").contains(&format!("\"real\":\"3.{}", "3".repeat(150))));
+    assert!(run("2^100").contains("\"real\":\"1267650600228229401496703205376\""));
+    assert_eq!(
+        json_error("q\"+1", "unknown variable q"),
+        r#"{"input":"q\"+1","type":"error","message":"unknown variable q"}"#
+    );
+    // commands answer with a string
+    assert_eq!(
+        reply(
+            "get prec",
+            "512".to_string(),
+            Options {
+                json: true,
+                ..Options::default()
+            }
+        ),
+        r#"{"input":"get prec","type":"string","value":"512"}"#
+    );
+}
+#[test]
+fn test_markup()
+{
+    let vars = get_vars(512);
+    let run = |input: &str, format: Format| {
+        let value = eval(input, &vars, &[]);
+        let options = Options {
+            format,
+            ..Options::default()
+        };
+        markup(input, Some(&value), options, &vars).unwrap()
+    };
+    // answers keep their fractions, π and roots, 2x/3 puts all of 2x over 3
+    assert_eq!(
+        run("(1+i)^2/3", Format::Latex),
+        r"\frac{{\left(1+i\right)}^{2}}{3}=\frac{2}{3}i"
+    );
+    assert_eq!(run("sqrt(8)", Format::Latex), r"\sqrt{8}=2\sqrt{2}");
+    assert_eq!(
+        run("cos(pi)-2i", Format::Latex),
+        r"\cos\left(\pi\right)-2i=-1-2i"
+    );
+    assert_eq!(run("sum(k,k,1,4)", Format::Latex), r"\sum_{k=1}^{4}k=10");
+    assert_eq!(
+        run("{{1,2},{3,4}}^2", Format::Latex),
+        r"{\begin{pmatrix}1 & 2 \\ 3 & 4\end{pmatrix}}^{2}=\begin{pmatrix}7 & 10 \\ 15 & 22\end{pmatrix}"
+    );
+    assert_eq!(
+        run("sqrt(2)/2", Format::MathML),
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mfrac><msqrt><mn>2</mn></msqrt><mn>2</mn></mfrac><mo>=</mo><mfrac><msqrt><mn>2</mn></msqrt><mn>2</mn></mfrac></mrow></math>"
+    );
+    // an equation has no answer of its own, only the input is rendered
+    assert_eq!(
+        markup(
+            "x^2<=4",
+            None,
+            Options {
+                format: Format::Latex,
+                ..Options::default()
+            },
+            &vars
+        )
+        .unwrap(),
+        r"{x}^{2}\le 4"
+    );
+}
+#[test]
+fn test_latex_input()
+{
+    let vars = get_vars(512);
+    let run = |input: &str| eval(&from_latex(input), &vars, &[]);
+    assert_eq!(from_latex("\\frac{1}{2}"), "((1)/(2))");
+    assert_eq!(from_latex("\\sin^{2}(x)"), "sin(x)^2");
+    // a -1 power on a trig function is its inverse
+    assert_eq!(from_latex("\\sin^{-1}(x)"), "asin(x)");
+    let half_pi = run("\\sin^{-1}(1)-\\pi/2").num().unwrap();
+    assert!(half_pi.abs().real().to_f64() < 1e-100);
+    assert_eq!(from_latex("\\sum_{n=1}^{10} n^2"), "sum(n^2,n,1,10)");
+    // without a \ braces are still vectors
+    assert_eq!(from_latex("{1,2}^{2}"), "{1,2}^{2}");
+    assert_eq!(run("\\sqrt[3]{27}\\cdot 2").num().unwrap(), 6);
+    assert_eq!(run("\\sum_{n=1}^{10} n^2 + 1").num().unwrap(), 386);
+    assert_eq!(run("\\log_{2} 8").num().unwrap(), 3);
+    assert_eq!(run("\\binom{5}{2}").num().unwrap(), 10);
+    assert_eq!(
+        run("\\begin{pmatrix} 1 & 2 \\\\ 3 & 4 \\end{pmatrix}")
+            .mat()
+            .unwrap()[1][0],
+        3
+    );
+    assert_eq!(
+        run("\\begin{pmatrix} 1 \\\\ 2 \\end{pmatrix}")
+            .vec()
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(
+        run("\\begin{vmatrix} 1 & 2 \\\\ 3 & 4 \\end{vmatrix}")
+            .num()
+            .unwrap(),
+        -2
+    );
+    // a cut off paste reads as far as it goes
+    for input in [
+        "\\frac{1}",
+        "\\frac",
+        "\\sqrt[",
+        "\\sin^{",
+        "\\sum_",
+        "\\left(",
+        "\\begin{pmatrix} 1 &",
+        "x^",
+        "\\",
+    ]
+    {
+        from_latex(input);
+    }
+    assert_eq!(from_latex("\\frac{1}"), "((1)/())");
+}
+#[test]
+fn test_pretty()
+{
+    let cells = |m: &[&[&str]]| {
+        m.iter()
+            .map(|r| r.iter().map(|c| c.to_string()).collect())
+            .collect::<Vec<Vec<String>>>()
+    };
+    // columns line up on the decimal point, or the end of the real part without one
+    assert_eq!(
+        pretty_matrix(&cells(&[&["1", "2.5"], &["-30.25", "4+2i"]])),
+        vec!["⎡   1     2.5  ⎤", "⎣ -30.25  4+2i ⎦"]
+    );
+    assert_eq!(pretty_matrix(&cells(&[&["1", "2"]])), vec!["[ 1  2 ]"]);
+    assert_eq!(stack("-3π/4"), vec![" 3π", "-──", " 4 "]);
+    assert_eq!(stack("1/3+1/2i"), vec!["1 1 ", "─+─i", "3 2 "]);
+    assert_eq!(
+        stack("{sqrt(2)/2,1}"),
+        vec![" sqrt(2)   ", "{───────,1}", "    2      "]
+    );
+    assert_eq!(stack("0.5"), vec!["0.5"]);
+    // a row with a fraction in it is three lines tall, the rest sit on the middle one
+    assert_eq!(
+        pretty_matrix(&cells(&[&["1/2", "3"], &["4", "5"]])),
+        vec!["⎡ 1    ⎤", "⎢ ─  3 ⎥", "⎢ 2    ⎥", "⎣ 4  5 ⎦"]
+    );
+}
+#[test]
+fn test_notation()
+{
+    let output = |notation, sig, re: f64, im: f64| {
+        let options = Options {
+            notation,
+            sig,
+            color: false,
+            ..Options::default()
+        };
+        let (a, b) = get_output(&options, &Complex::with_val(256, (re, im)));
+        a + &b
+    };
+    assert_eq!(output(Notation::Eng, 6, 4700.0, 0.0), "4.7k");
+    assert_eq!(output(Notation::Eng, 6, 0.000022, 0.0), "22µ");
+    assert_eq!(output(Notation::Eng, 3, -123456.0, 0.0), "-123k");
+    assert_eq!(output(Notation::Eng, 6, 1e30, 0.0), "1E30");
+    assert_eq!(output(Notation::Eng, 6, 1.0, 0.00025), "1+250µi");
+    assert_eq!(output(Notation::Sig, 3, 2.0 / 3.0, 0.0), "0.667");
+    assert_eq!(output(Notation::Sig, 3, 123456.0, 0.0), "1.23E5");
+    assert_eq!(output(Notation::Sig, 6, 0.0, -0.5), "-0.5i");
+}
+#[test]
+fn test_bases()
+{
+    let vars = vec![["x".to_string(), "5".to_string()]];
+    let value = |input: &str| eval(input, &vars, &[]).num().unwrap();
+    assert_eq!(value("0x1F"), 31);
+    assert_eq!(value("0b1010"), 10);
+    assert_eq!(value("0o17"), 15);
+    assert_eq!(value("36#zz"), 1295);
+    assert_eq!(value("0x1.8p3"), 12);
+    assert_eq!(value("-0x1p-2"), -0.25);
+    // the x of the 0x prefix is not the variable x
+    assert_eq!(value("0xff+x"), 260);
+    assert_eq!(value("2*(16#ff)"), 510);
+    // a digit the base does not have is an error rather than a product
+    assert!(get_func("2#102", 512).is_err());
+    assert!(get_func("0x1g", 512).is_err());
+    // a negative number has no -0i to move sqrt or arg onto another branch
+    assert_eq!(value("sqrt(-4)"), Complex::with_val(512, (0, 2)));
+    assert_eq!(value("arg(-1)"), Complex::with_val(512, Pi));
+    assert_eq!(conversion("255 to hex"), Some(Ok(("255", 16))));
+    assert_eq!(conversion("_ to base 3"), Some(Ok(("_", 3))));
+    assert_eq!(conversion("255"), None);
+    for input in ["1 to base 40", "255 to 36", "x to foo"]
+    {
+        assert!(conversion(input).unwrap().is_err());
+    }
+    // fractions are rounded to the decimal places and only use an exponent when it's asked for
+    let output = |input: &str, options: Options| {
+        get_output(
+            &Options {
+                base: 16,
+                ..options
+            },
+            &value(input),
+        )
+        .0
+    };
+    let options = Options {
+        color: false,
+        ..Options::default()
+    };
+    assert_eq!(output("0.5", options), "0.8");
+    assert_eq!(output("-255.75", options), "-ff.c");
+    assert_eq!(output("1/3", options), "0.555555555555");
+    assert_eq!(output("2^-40", options), "0.0000000001");
+    let sci = Options {
+        notation: Notation::Sci,
+        ..options
+    };
+    assert_eq!(output("2^-40", sci), "1E-10");
+    assert_eq!(output("255.5", sci), "f.f8E1");
+    assert_eq!(graphs("16#ff#x"), vec!["16#ff", "x"]);
+    assert_eq!(graphs("x#16#ff"), vec!["x", "16", "ff"]);
+    assert_eq!(graphs("x^2#1"), vec!["x^2", "1"]);
+    assert_eq!(graphs("x#x^2"), vec!["x", "x^2"]);
+}
+#[test]
+fn test_history()
+{
+    let path = std::env::temp_dir().join("kalc_test_history");
+    let path = path.to_str().unwrap();
+    let lines =
+        |history: &[&str]| -> Vec<String> { history.iter().map(|l| l.to_string()).collect() };
+    // a new line is appended, the file isn't written again
+    std::fs::write(path, "old\n").unwrap();
+    let mut history = lines(&["a", "b"]);
+    write("c", path, &mut history, 10);
+    assert_eq!(history, lines(&["a", "b", "c"]));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "old\nc\n");
+    // repeating the last line changes nothing
+    write("c", path, &mut history, 10);
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "old\nc\n");
+    // an earlier line moves to the end and the file is rewritten
+    write("a", path, &mut history, 10);
+    assert_eq!(history, lines(&["b", "c", "a"]));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "b\nc\na\n");
+    // past history_size the oldest lines go
+    write("d", path, &mut history, 2);
+    assert_eq!(history, lines(&["a", "d"]));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "a\nd\n");
+    std::fs::remove_file(path).unwrap();
+    let history = lines(&["sin(x)", "2+2", "sinh(1)", "x=2"]);
+    assert_eq!(
+        history_matches(&history, "search sin"),
+        vec!["sin(x)", "sinh(1)"]
+    );
+    assert_eq!(history_matches(&history, "2+"), vec!["2+2"]);
+    assert!(history_matches(&history, "search cos").is_empty());
+}